@@ -0,0 +1,573 @@
+//! Configuration file for the `usbwatch` command-line tool.
+//!
+//! The file is TOML. Every section and key is optional; options given on
+//! the command line override the values from the file.
+//!
+//! ```toml
+//! [output]
+//! json = false
+//! logfile = "/var/log/usbwatch.jsonl"
+//! logfile_json = true  # text on the console, JSON lines in the file
+//! rotate_size = "10M"
+//! rotate_daily = true
+//! rotate_keep = 7
+//! rotate_compress = true
+//!
+//! [filter]
+//! vid = ["0483", "1366"]
+//! class = ["cdc"]
+//! name_regex = ["(?i)st-?link"]
+//! include = ["event=connected"]      # any KEY=VALUE filter rule
+//! exclude = ["serial=066DFF535155"]
+//!
+//! [watcher]
+//! min_poll_interval_ms = 250
+//! max_poll_interval_ms = 2000
+//! error_backoff_max_ms = 10000
+//! debounce_ms = 300
+//! flap_threshold = 6
+//! flap_window_secs = 10
+//! usb_ids = "/usr/share/hwdata/usb.ids"
+//!
+//! [hooks]
+//! on_connect = "logger -t usbwatch \"connected $USBWATCH_VID:$USBWATCH_PID\""
+//! on_disconnect = "/usr/local/bin/device-gone"
+//! concurrency = 2
+//! timeout_secs = 10
+//!
+//! # Checked in order; the first rule whose `match` rules all hold decides
+//! [[policy]]
+//! action = "allow"                   # report and run hooks
+//! match = ["vid=0483", "pid=3748"]
+//!
+//! [[policy]]
+//! action = "no-hooks"                # report without running hooks
+//! match = ["class=mass-storage"]
+//!
+//! [[policy]]
+//! action = "deny"                    # don't report; no match list matches everything
+//! ```
+
+use crate::error::UsbWatchError;
+use crate::filter::{DeviceFilter, FilterRule};
+use crate::hooks::EventHooks;
+use crate::logger::{parse_size, RotationPolicy};
+use crate::policy::{Policy, PolicyAction};
+use crate::watcher::UsbWatcherBuilder;
+use serde::Deserialize;
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use toml::Spanned;
+
+/// Flap detection window used when only a threshold is configured.
+pub const DEFAULT_FLAP_WINDOW: Duration = Duration::from_secs(10);
+
+/// Settings loaded from a configuration file.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use usbwatch_rs::config::Config;
+/// use usbwatch_rs::UsbWatcherBuilder;
+///
+/// let config = Config::load("usbwatch.toml")?;
+/// let filter = config.filter.device_filter()?;
+/// let builder = config.watcher.apply(UsbWatcherBuilder::new().filter(filter));
+/// # Ok::<(), usbwatch_rs::UsbWatchError>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct Config {
+    /// The `[output]` section
+    pub output: OutputConfig,
+    /// The `[filter]` section
+    pub filter: FilterConfig,
+    /// The `[watcher]` section
+    pub watcher: WatcherSettings,
+    /// The `[hooks]` section
+    pub hooks: HooksConfig,
+    /// The `[[policy]]` rules
+    pub policy: Policy,
+}
+
+/// Output format and log file.
+#[derive(Debug, Clone, Default)]
+pub struct OutputConfig {
+    /// Whether to print events as JSON
+    pub json: Option<bool>,
+    /// File to log events to
+    pub logfile: Option<String>,
+    /// Whether the log file is written as JSON lines; defaults to `json`
+    pub logfile_json: Option<bool>,
+    /// Size in bytes at which the log file is rotated
+    pub rotate_size: Option<u64>,
+    /// Whether the log file is rotated every day
+    pub rotate_daily: Option<bool>,
+    /// Number of rotated log files to keep
+    pub rotate_keep: Option<usize>,
+    /// Whether rotated log files are compressed
+    pub rotate_compress: Option<bool>,
+}
+
+/// Which devices are reported.
+///
+/// Each list holds alternatives, as with the repeatable command-line options.
+#[derive(Debug, Clone, Default)]
+pub struct FilterConfig {
+    /// Vendor IDs in hex
+    pub vid: Vec<String>,
+    /// Product IDs in hex
+    pub pid: Vec<String>,
+    /// Classes by name or hex code
+    pub class: Vec<String>,
+    /// Regular expressions matched against the device name
+    pub name_regex: Vec<String>,
+    /// Further include rules in `KEY=VALUE` form (see [`FilterRule`])
+    pub include: Vec<String>,
+    /// Exclude rules in `KEY=VALUE` form
+    pub exclude: Vec<String>,
+}
+
+/// Polling, debouncing and flap detection.
+#[derive(Debug, Clone, Default)]
+pub struct WatcherSettings {
+    /// Shortest interval between scans
+    pub min_poll_interval: Option<Duration>,
+    /// Longest interval between scans
+    pub max_poll_interval: Option<Duration>,
+    /// Ceiling for the backoff after failed scans; if unset, raised to the
+    /// minimum poll interval when that is longer than the default ceiling
+    pub error_backoff_max: Option<Duration>,
+    /// Debounce window
+    pub debounce: Option<Duration>,
+    /// Number of connects and disconnects that make a device flapping
+    pub flap_threshold: Option<usize>,
+    /// Flap detection window, [`DEFAULT_FLAP_WINDOW`] if unset
+    pub flap_window: Option<Duration>,
+    /// `usb.ids` file to name devices from, instead of the system's; not
+    /// applied by [`apply`](Self::apply), load it with
+    /// [`UsbIds::load`](crate::usb_ids::UsbIds::load)
+    pub usb_ids: Option<PathBuf>,
+}
+
+/// Commands run on device events.
+#[derive(Debug, Clone, Default)]
+pub struct HooksConfig {
+    /// Command run when a device connects
+    pub on_connect: Option<String>,
+    /// Command run when a device disconnects
+    pub on_disconnect: Option<String>,
+    /// Maximum number of commands running at the same time
+    pub concurrency: Option<usize>,
+    /// Time after which a command is killed
+    pub timeout: Option<Duration>,
+}
+
+impl Config {
+    /// Loads and validates a configuration file.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`UsbWatchError::InvalidConfigFile`], with the offending line
+    /// where there is one, if
+    /// the file is not valid TOML, has unknown keys or values of the wrong
+    /// type, or contains invalid filter rules, policy actions or settings. Returns an I/O
+    /// error if the file cannot be read.
+    pub fn load(path: impl AsRef<Path>) -> crate::Result<Self> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|e| {
+            UsbWatchError::io(
+                format!("Failed to read configuration file '{}'", path.display()),
+                e,
+            )
+        })?;
+        parse(&text).map_err(|(span, message)| UsbWatchError::InvalidConfigFile {
+            path: path.to_path_buf(),
+            line: span.map(|span| line_of(&text, span.start)),
+            message,
+        })
+    }
+
+    /// Returns where the configuration file is looked for when no path is given:
+    /// `$XDG_CONFIG_HOME/usbwatch/config.toml`, falling back to
+    /// `~/.config/usbwatch/config.toml` (`%APPDATA%\usbwatch\config.toml` on
+    /// Windows).
+    pub fn default_path() -> Option<PathBuf> {
+        let non_empty = |name| std::env::var_os(name).filter(|value| !value.is_empty());
+        let config_dir = if cfg!(windows) {
+            non_empty("APPDATA").map(PathBuf::from)
+        } else {
+            non_empty("XDG_CONFIG_HOME")
+                .map(PathBuf::from)
+                .or_else(|| non_empty("HOME").map(|home| Path::new(&home).join(".config")))
+        };
+        Some(config_dir?.join("usbwatch").join("config.toml"))
+    }
+}
+
+impl OutputConfig {
+    /// Returns these settings with every value set in `other` replacing the one here.
+    pub fn overridden_by(self, other: Self) -> Self {
+        Self {
+            json: other.json.or(self.json),
+            logfile: other.logfile.or(self.logfile),
+            logfile_json: other.logfile_json.or(self.logfile_json),
+            rotate_size: other.rotate_size.or(self.rotate_size),
+            rotate_daily: other.rotate_daily.or(self.rotate_daily),
+            rotate_keep: other.rotate_keep.or(self.rotate_keep),
+            rotate_compress: other.rotate_compress.or(self.rotate_compress),
+        }
+    }
+
+    /// Returns the rotation policy for the log file, or `None` if neither a
+    /// size limit nor daily rotation is set.
+    pub fn rotation_policy(&self) -> Option<RotationPolicy> {
+        let daily = self.rotate_daily.unwrap_or(false);
+        if self.rotate_size.is_none() && !daily {
+            return None;
+        }
+        let mut policy = RotationPolicy::new();
+        if let Some(bytes) = self.rotate_size {
+            policy = policy.max_size(bytes);
+        }
+        if daily {
+            policy = policy.daily();
+        }
+        if let Some(count) = self.rotate_keep {
+            policy = policy.keep(count);
+        }
+        Some(policy.compress(self.rotate_compress.unwrap_or(false)))
+    }
+}
+
+impl FilterConfig {
+    /// Returns these settings with every non-empty list in `other` replacing the one here.
+    pub fn overridden_by(self, other: Self) -> Self {
+        let pick = |mine: Vec<String>, theirs: Vec<String>| {
+            if theirs.is_empty() {
+                mine
+            } else {
+                theirs
+            }
+        };
+        Self {
+            vid: pick(self.vid, other.vid),
+            pid: pick(self.pid, other.pid),
+            class: pick(self.class, other.class),
+            name_regex: pick(self.name_regex, other.name_regex),
+            include: pick(self.include, other.include),
+            exclude: pick(self.exclude, other.exclude),
+        }
+    }
+
+    /// Builds the device filter described by the settings.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`UsbWatchError::InvalidConfig`] if a value is not a valid
+    /// ID, class, regular expression or rule.
+    pub fn device_filter(&self) -> crate::Result<DeviceFilter> {
+        let mut filter = DeviceFilter::new();
+        for rule in self.include_rules() {
+            filter = filter.include(rule.parse()?);
+        }
+        for rule in &self.exclude {
+            filter = filter.exclude(rule.parse()?);
+        }
+        Ok(filter)
+    }
+
+    /// Returns every include setting as a `KEY=VALUE` rule.
+    fn include_rules(&self) -> impl Iterator<Item = String> + '_ {
+        [
+            ("vid", &self.vid),
+            ("pid", &self.pid),
+            ("class", &self.class),
+            ("name", &self.name_regex),
+        ]
+        .into_iter()
+        .flat_map(|(key, values)| values.iter().map(move |value| format!("{key}={value}")))
+        .chain(self.include.iter().cloned())
+    }
+}
+
+impl WatcherSettings {
+    /// Returns these settings with every value set in `other` replacing the one here.
+    pub fn overridden_by(self, other: Self) -> Self {
+        Self {
+            min_poll_interval: other.min_poll_interval.or(self.min_poll_interval),
+            max_poll_interval: other.max_poll_interval.or(self.max_poll_interval),
+            error_backoff_max: other.error_backoff_max.or(self.error_backoff_max),
+            debounce: other.debounce.or(self.debounce),
+            flap_threshold: other.flap_threshold.or(self.flap_threshold),
+            flap_window: other.flap_window.or(self.flap_window),
+            usb_ids: other.usb_ids.or(self.usb_ids),
+        }
+    }
+
+    /// Applies the settings to a watcher builder.
+    pub fn apply(&self, mut builder: UsbWatcherBuilder) -> UsbWatcherBuilder {
+        if let Some(interval) = self.min_poll_interval {
+            let ceiling = builder.config().error_backoff_max.max(interval);
+            builder = builder
+                .min_poll_interval(interval)
+                .error_backoff_max(ceiling);
+        }
+        if let Some(interval) = self.max_poll_interval {
+            builder = builder.max_poll_interval(interval);
+        }
+        if let Some(ceiling) = self.error_backoff_max {
+            builder = builder.error_backoff_max(ceiling);
+        }
+        if let Some(window) = self.debounce {
+            builder = builder.debounce(window);
+        }
+        if let Some(threshold) = self.flap_threshold {
+            builder =
+                builder.flap_detection(threshold, self.flap_window.unwrap_or(DEFAULT_FLAP_WINDOW));
+        }
+        builder
+    }
+}
+
+impl HooksConfig {
+    /// Returns these settings with every value set in `other` replacing the one here.
+    pub fn overridden_by(self, other: Self) -> Self {
+        Self {
+            on_connect: other.on_connect.or(self.on_connect),
+            on_disconnect: other.on_disconnect.or(self.on_disconnect),
+            concurrency: other.concurrency.or(self.concurrency),
+            timeout: other.timeout.or(self.timeout),
+        }
+    }
+
+    /// Builds the event hooks described by the settings.
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`concurrency`](Self::concurrency) is zero.
+    pub fn event_hooks(&self) -> EventHooks {
+        let mut hooks = EventHooks::new();
+        if let Some(command) = &self.on_connect {
+            hooks = hooks.on_connect(command);
+        }
+        if let Some(command) = &self.on_disconnect {
+            hooks = hooks.on_disconnect(command);
+        }
+        if let Some(limit) = self.concurrency {
+            hooks = hooks.max_concurrent(limit);
+        }
+        if let Some(timeout) = self.timeout {
+            hooks = hooks.timeout(timeout);
+        }
+        hooks
+    }
+}
+
+/// The file as written, with the location of values that are checked after parsing.
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct RawConfig {
+    output: RawOutput,
+    filter: RawFilter,
+    watcher: RawWatcher,
+    hooks: RawHooks,
+    policy: Vec<RawPolicyRule>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct RawOutput {
+    json: Option<bool>,
+    logfile: Option<String>,
+    logfile_json: Option<bool>,
+    rotate_size: Option<Spanned<String>>,
+    rotate_daily: Option<bool>,
+    rotate_keep: Option<usize>,
+    rotate_compress: Option<bool>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct RawFilter {
+    vid: Vec<Spanned<String>>,
+    pid: Vec<Spanned<String>>,
+    class: Vec<Spanned<String>>,
+    name_regex: Vec<Spanned<String>>,
+    include: Vec<Spanned<String>>,
+    exclude: Vec<Spanned<String>>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct RawWatcher {
+    min_poll_interval_ms: Option<Spanned<u64>>,
+    max_poll_interval_ms: Option<Spanned<u64>>,
+    error_backoff_max_ms: Option<Spanned<u64>>,
+    debounce_ms: Option<u64>,
+    flap_threshold: Option<Spanned<usize>>,
+    flap_window_secs: Option<Spanned<u64>>,
+    usb_ids: Option<PathBuf>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct RawHooks {
+    on_connect: Option<String>,
+    on_disconnect: Option<String>,
+    concurrency: Option<Spanned<usize>>,
+    timeout_secs: Option<u64>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawPolicyRule {
+    action: Spanned<String>,
+    #[serde(default, rename = "match")]
+    rules: Vec<Spanned<String>>,
+}
+
+/// An error message and the bytes of the file it refers to, if any.
+type SpannedError = (Option<Range<usize>>, String);
+
+fn parse(text: &str) -> Result<Config, SpannedError> {
+    let raw: RawConfig = toml::from_str(text).map_err(|e| (e.span(), e.message().to_string()))?;
+
+    let config = Config {
+        output: parse_output(raw.output)?,
+        filter: parse_filter(raw.filter)?,
+        watcher: parse_watcher(&raw.watcher)?,
+        hooks: HooksConfig {
+            on_connect: raw.hooks.on_connect,
+            on_disconnect: raw.hooks.on_disconnect,
+            concurrency: positive(&raw.hooks.concurrency, "Hook concurrency")?,
+            timeout: raw.hooks.timeout_secs.map(Duration::from_secs),
+        },
+        policy: parse_policy(raw.policy)?,
+    };
+    Ok(config)
+}
+
+fn parse_output(raw: RawOutput) -> Result<OutputConfig, SpannedError> {
+    let rotate_size = raw
+        .rotate_size
+        .map(|size| match parse_size(size.get_ref()) {
+            Some(0) | None => Err((
+                Some(size.span()),
+                format!("Invalid log file size '{}'", size.get_ref()),
+            )),
+            Some(bytes) => Ok(bytes),
+        })
+        .transpose()?;
+    Ok(OutputConfig {
+        json: raw.json,
+        logfile: raw.logfile,
+        logfile_json: raw.logfile_json,
+        rotate_size,
+        rotate_daily: raw.rotate_daily,
+        rotate_keep: raw.rotate_keep,
+        rotate_compress: raw.rotate_compress,
+    })
+}
+
+fn parse_filter(raw: RawFilter) -> Result<FilterConfig, SpannedError> {
+    // Check each value on its own, so an error points at the right line
+    let check = |key: &str, values: Vec<Spanned<String>>| {
+        values
+            .into_iter()
+            .map(|value| {
+                let rule = match key {
+                    "" => value.get_ref().clone(),
+                    key => format!("{key}={}", value.get_ref()),
+                };
+                match rule.parse::<FilterRule>() {
+                    Ok(_) => Ok(value.into_inner()),
+                    Err(e) => Err((Some(value.span()), e.to_string())),
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()
+    };
+    Ok(FilterConfig {
+        vid: check("vid", raw.vid)?,
+        pid: check("pid", raw.pid)?,
+        class: check("class", raw.class)?,
+        name_regex: check("name", raw.name_regex)?,
+        include: check("", raw.include)?,
+        exclude: check("", raw.exclude)?,
+    })
+}
+
+fn parse_policy(raw: Vec<RawPolicyRule>) -> Result<Policy, SpannedError> {
+    raw.into_iter().try_fold(Policy::new(), |policy, rule| {
+        let action = rule
+            .action
+            .get_ref()
+            .parse::<PolicyAction>()
+            .map_err(|e| (Some(rule.action.span()), e.to_string()))?;
+        let rules = rule
+            .rules
+            .into_iter()
+            .map(|value| {
+                value
+                    .get_ref()
+                    .parse::<FilterRule>()
+                    .map_err(|e| (Some(value.span()), e.to_string()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(policy.rule(action, rules))
+    })
+}
+
+fn parse_watcher(raw: &RawWatcher) -> Result<WatcherSettings, SpannedError> {
+    let settings = WatcherSettings {
+        min_poll_interval: positive(&raw.min_poll_interval_ms, "Minimum poll interval")?
+            .map(Duration::from_millis),
+        max_poll_interval: positive(&raw.max_poll_interval_ms, "Maximum poll interval")?
+            .map(Duration::from_millis),
+        error_backoff_max: positive(&raw.error_backoff_max_ms, "Error backoff ceiling")?
+            .map(Duration::from_millis),
+        debounce: raw.debounce_ms.map(Duration::from_millis),
+        flap_threshold: positive(&raw.flap_threshold, "Flap detection threshold")?,
+        flap_window: positive(&raw.flap_window_secs, "Flap detection window")?
+            .map(Duration::from_secs),
+        usb_ids: raw.usb_ids.clone(),
+    };
+
+    // The remaining inconsistencies are between the poll intervals and the backoff ceiling
+    if let Err(e) = settings.apply(UsbWatcherBuilder::new()).config().validate() {
+        let span = raw
+            .min_poll_interval_ms
+            .as_ref()
+            .or(raw.max_poll_interval_ms.as_ref())
+            .or(raw.error_backoff_max_ms.as_ref())
+            .map(Spanned::span);
+        return Err((span, e.to_string()));
+    }
+    Ok(settings)
+}
+
+/// Rejects zero, which no count or interval in the file may be.
+fn positive<T>(value: &Option<Spanned<T>>, what: &str) -> Result<Option<T>, SpannedError>
+where
+    T: Copy + Default + PartialEq,
+{
+    match value {
+        Some(value) if *value.get_ref() == T::default() => Err((
+            Some(value.span()),
+            format!("{what} must be greater than zero"),
+        )),
+        value => Ok(value.as_ref().map(|value| *value.get_ref())),
+    }
+}
+
+/// Returns the 1-based line containing byte `offset` of `text`.
+fn line_of(text: &str, offset: usize) -> usize {
+    let offset = offset.min(text.len());
+    text.as_bytes()[..offset]
+        .iter()
+        .filter(|&&byte| byte == b'\n')
+        .count()
+        + 1
+}
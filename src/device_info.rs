@@ -3,8 +3,11 @@
 //! Core data structures for representing USB device information and events in the usbwatch monitoring system.
 //! Supports Linux, Windows, and macOS device handles and event types.
 
+use crate::error::UsbWatchError;
 use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
+use serde::ser::SerializeStruct;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::str::FromStr;
 
 /// Platform-specific device handle for advanced operations.
 ///
@@ -18,8 +21,9 @@ pub enum DeviceHandle {
     Linux {
         /// Path to the device in sysfs (e.g., "/sys/bus/usb/devices/1-1")
         sysfs_path: String,
-        /// Device node path if available (e.g., "/dev/ttyUSB0")
-        device_node: Option<String>,
+        /// `/dev` nodes created for the device and its interfaces
+        /// (e.g., "/dev/bus/usb/001/004" and "/dev/ttyUSB0")
+        device_nodes: Vec<String>,
     },
     /// Windows device handle with instance information
     #[cfg(target_os = "windows")]
@@ -60,6 +64,486 @@ pub trait AsDeviceHandle {
     }
 }
 
+/// Stable identifier for a physical USB device.
+///
+/// Use it to correlate a device's `Connected` and `Disconnected` events.
+/// Devices with a serial number are identified as `vendor:product:serial`, so
+/// the id survives moving the device to another port. Devices without one are
+/// identified by where they are plugged in as well (`vendor:product@location`),
+/// so two identical serial-less devices never share an id. The location is
+/// platform-specific: the sysfs name on Linux (e.g., "1-1.4"), the port and
+/// hub on Windows, and the IOKit location ID on macOS.
+///
+/// # Examples
+///
+/// ```
+/// use usbwatch_rs::device_info::{DeviceId, ProductId, VendorId};
+///
+/// let drive = DeviceId::new(VendorId(0x0781), ProductId(0x5583), Some("4C530001"), Some("1-2"));
+/// assert_eq!(drive.as_str(), "0781:5583:4C530001");
+///
+/// let mouse = (VendorId(0x046d), ProductId(0xc31c));
+/// let left = DeviceId::new(mouse.0, mouse.1, None, Some("1-1.1"));
+/// let right = DeviceId::new(mouse.0, mouse.1, None, Some("1-1.2"));
+/// assert_ne!(left, right);
+/// assert_eq!(left.to_string(), "046d:c31c@1-1.1");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct DeviceId(String);
+
+impl DeviceId {
+    /// Builds the id for a device from its descriptors and, if known, its location.
+    pub fn new(
+        vendor_id: VendorId,
+        product_id: ProductId,
+        serial_number: Option<&str>,
+        location: Option<&str>,
+    ) -> Self {
+        match (serial_number, location) {
+            (Some(serial), _) => Self(format!("{vendor_id}:{product_id}:{serial}")),
+            (None, Some(location)) => Self(format!("{vendor_id}:{product_id}@{location}")),
+            (None, None) => Self(format!("{vendor_id}:{product_id}")),
+        }
+    }
+
+    /// Returns the id as a string slice.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::fmt::Display for DeviceId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+macro_rules! usb_id {
+    ($(#[$attr:meta])* $name:ident) => {
+        $(#[$attr])*
+        #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        pub struct $name(pub u16);
+
+        impl From<u16> for $name {
+            fn from(id: u16) -> Self {
+                Self(id)
+            }
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "{:04x}", self.0)
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = UsbWatchError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                let value = s.trim();
+                let digits = value
+                    .strip_prefix("0x")
+                    .or_else(|| value.strip_prefix("0X"))
+                    .unwrap_or(value);
+                // from_str_radix would also accept a sign
+                if digits.is_empty() || digits.len() > 4 || !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+                    return Err(UsbWatchError::InvalidConfig(format!(
+                        "Invalid USB ID '{s}': expected up to 4 hex digits"
+                    )));
+                }
+                Ok(Self(u16::from_str_radix(digits, 16).expect("checked hex digits")))
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_str(self)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let id = String::deserialize(deserializer)?;
+                id.parse().map_err(de::Error::custom)
+            }
+        }
+    };
+}
+
+usb_id! {
+    /// USB vendor ID (`idVendor`).
+    ///
+    /// Displays and serializes as four lowercase hex digits, as `lsusb` shows
+    /// it, and parses from up to four hex digits with an optional `0x` prefix.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use usbwatch_rs::device_info::VendorId;
+    ///
+    /// let st: VendorId = "0x483".parse()?;
+    /// assert_eq!(st, VendorId(0x0483));
+    /// assert_eq!(st.to_string(), "0483");
+    /// assert_eq!(serde_json::to_string(&st).unwrap(), "\"0483\"");
+    /// # Ok::<(), usbwatch_rs::UsbWatchError>(())
+    /// ```
+    VendorId
+}
+
+usb_id! {
+    /// USB product ID (`idProduct`), formatted and parsed like [`VendorId`].
+    ProductId
+}
+
+/// A binary-coded decimal release number from a device descriptor: the USB
+/// specification the device complies with (`bcdUSB`) or the device's own
+/// release (`bcdDevice`).
+///
+/// Displays and parses as `major.minor`, the way `lsusb` shows it, and
+/// compares numerically.
+///
+/// # Examples
+///
+/// ```
+/// use usbwatch_rs::device_info::BcdVersion;
+///
+/// let usb2 = BcdVersion::from_bcd(0x0210);
+/// assert_eq!(usb2.to_string(), "2.10");
+/// assert_eq!((usb2.major(), usb2.minor()), (2, 10));
+/// assert!(usb2 < "3.0".parse()?);
+/// # Ok::<(), usbwatch_rs::UsbWatchError>(())
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BcdVersion(u16);
+
+impl BcdVersion {
+    /// Wraps a descriptor field such as `0x0210` for USB 2.1.
+    pub fn from_bcd(bcd: u16) -> Self {
+        Self(bcd)
+    }
+
+    /// Returns the descriptor field.
+    pub fn to_bcd(self) -> u16 {
+        self.0
+    }
+
+    /// Returns the major version, e.g. 2 for "2.10".
+    pub fn major(self) -> u8 {
+        bcd_to_decimal((self.0 >> 8) as u8)
+    }
+
+    /// Returns the two minor digits, e.g. 10 for "2.10" and 1 for "2.01".
+    pub fn minor(self) -> u8 {
+        bcd_to_decimal(self.0 as u8)
+    }
+}
+
+fn bcd_to_decimal(byte: u8) -> u8 {
+    (byte >> 4) * 10 + (byte & 0x0f)
+}
+
+impl std::fmt::Display for BcdVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Hex digits of a BCD byte are its decimal digits
+        write!(f, "{:x}.{:02x}", self.0 >> 8, self.0 & 0xff)
+    }
+}
+
+impl BcdVersion {
+    /// Parses `major.minor`, allowing the hex digits a-f if `hex_digits` is set.
+    fn parse(s: &str, hex_digits: bool) -> crate::Result<Self> {
+        let invalid =
+            || UsbWatchError::InvalidConfig(format!("Invalid version '{s}': expected MAJOR.MINOR"));
+        let (major, minor) = s.trim().split_once('.').ok_or_else(invalid)?;
+        let digits_ok = |part: &str| {
+            (1..=2).contains(&part.len())
+                && part.bytes().all(|b| {
+                    if hex_digits {
+                        b.is_ascii_hexdigit()
+                    } else {
+                        b.is_ascii_digit()
+                    }
+                })
+        };
+        if !digits_ok(major) || !digits_ok(minor) {
+            return Err(invalid());
+        }
+        let major = u16::from_str_radix(major, 16).map_err(|_| invalid())?;
+        let mut minor_digits = minor.to_string();
+        if minor_digits.len() == 1 {
+            minor_digits.push('0');
+        }
+        let minor = u16::from_str_radix(&minor_digits, 16).map_err(|_| invalid())?;
+        Ok(Self(major << 8 | minor))
+    }
+}
+
+/// Parses `major.minor` with up to two decimal digits on each side. A single
+/// minor digit is the first one, so "1.1" is the same as "1.10".
+impl FromStr for BcdVersion {
+    type Err = UsbWatchError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s, false)
+    }
+}
+
+impl Serialize for BcdVersion {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for BcdVersion {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        // Some devices put digits above 9 in bcdDevice, which display as a-f
+        let version = String::deserialize(deserializer)?;
+        Self::parse(&version, true).map_err(de::Error::custom)
+    }
+}
+
+/// Negotiated link speed of a device, ordered from slowest to fastest.
+///
+/// # Examples
+///
+/// ```
+/// use usbwatch_rs::device_info::UsbSpeed;
+///
+/// // sysfs reports the speed in Mbit/s
+/// let speed: UsbSpeed = "480".parse()?;
+/// assert_eq!(speed, UsbSpeed::High);
+/// assert_eq!(speed.to_string(), "480 Mbit/s");
+/// assert!(speed < UsbSpeed::Super);
+/// # Ok::<(), usbwatch_rs::UsbWatchError>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UsbSpeed {
+    /// Low Speed, 1.5 Mbit/s (USB 1.0)
+    Low,
+    /// Full Speed, 12 Mbit/s (USB 1.1)
+    Full,
+    /// High Speed, 480 Mbit/s (USB 2.0)
+    High,
+    /// SuperSpeed, 5 Gbit/s (USB 3.2 Gen 1)
+    Super,
+    /// SuperSpeed+, 10 Gbit/s (USB 3.2 Gen 2)
+    SuperPlus,
+    /// SuperSpeed+ over two lanes, 20 Gbit/s (USB 3.2 Gen 2x2)
+    SuperPlusX2,
+}
+
+impl UsbSpeed {
+    /// Returns the signalling rate in Mbit/s.
+    pub fn mbps(&self) -> f64 {
+        match self {
+            UsbSpeed::Low => 1.5,
+            UsbSpeed::Full => 12.0,
+            UsbSpeed::High => 480.0,
+            UsbSpeed::Super => 5000.0,
+            UsbSpeed::SuperPlus => 10000.0,
+            UsbSpeed::SuperPlusX2 => 20000.0,
+        }
+    }
+}
+
+impl std::fmt::Display for UsbSpeed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} Mbit/s", self.mbps())
+    }
+}
+
+/// Parses the rate in Mbit/s, as found in the sysfs `speed` attribute.
+impl FromStr for UsbSpeed {
+    type Err = UsbWatchError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "1.5" => Ok(UsbSpeed::Low),
+            "12" => Ok(UsbSpeed::Full),
+            "480" => Ok(UsbSpeed::High),
+            "5000" => Ok(UsbSpeed::Super),
+            "10000" => Ok(UsbSpeed::SuperPlus),
+            "20000" => Ok(UsbSpeed::SuperPlusX2),
+            _ => Err(UsbWatchError::InvalidConfig(format!(
+                "Unknown USB speed '{s}': expected 1.5, 12, 480, 5000, 10000 or 20000"
+            ))),
+        }
+    }
+}
+
+/// USB class code triple of a device or interface.
+///
+/// Serializes with the human-readable [`name`](Self::name) alongside the codes.
+///
+/// # Examples
+///
+/// ```
+/// use usbwatch_rs::device_info::UsbClass;
+///
+/// let class = UsbClass::new(0x08, 0x06, 0x50);
+/// assert_eq!(class.name(), "Mass Storage");
+/// assert_eq!(class.to_string(), "Mass Storage (08/06/50)");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+pub struct UsbClass {
+    /// Base class code (`bDeviceClass` or `bInterfaceClass`)
+    pub class: u8,
+    /// Subclass code
+    pub subclass: u8,
+    /// Protocol code
+    pub protocol: u8,
+}
+
+impl UsbClass {
+    /// Creates a class triple from its codes.
+    pub fn new(class: u8, subclass: u8, protocol: u8) -> Self {
+        Self {
+            class,
+            subclass,
+            protocol,
+        }
+    }
+
+    /// Returns the name of the base class as defined by the USB-IF.
+    pub fn name(&self) -> &'static str {
+        match self.class {
+            0x00 => "Per Interface",
+            0x01 => "Audio",
+            0x02 => "Communications",
+            0x03 => "HID",
+            0x05 => "Physical",
+            0x06 => "Image",
+            0x07 => "Printer",
+            0x08 => "Mass Storage",
+            0x09 => "Hub",
+            0x0a => "CDC Data",
+            0x0b => "Smart Card",
+            0x0d => "Content Security",
+            0x0e => "Video",
+            0x0f => "Personal Healthcare",
+            0x10 => "Audio/Video",
+            0x11 => "Billboard",
+            0x12 => "Type-C Bridge",
+            0x3c => "I3C",
+            0xdc => "Diagnostic",
+            0xe0 => "Wireless",
+            0xef => "Miscellaneous",
+            0xfe => "Application Specific",
+            0xff => "Vendor Specific",
+            _ => "Unknown",
+        }
+    }
+}
+
+impl Serialize for UsbClass {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("UsbClass", 4)?;
+        state.serialize_field("class", &self.class)?;
+        state.serialize_field("subclass", &self.subclass)?;
+        state.serialize_field("protocol", &self.protocol)?;
+        state.serialize_field("name", self.name())?;
+        state.end()
+    }
+}
+
+impl std::fmt::Display for UsbClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} ({:02x}/{:02x}/{:02x})",
+            self.name(),
+            self.class,
+            self.subclass,
+            self.protocol
+        )
+    }
+}
+
+/// An interface exposed by a USB device.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UsbInterface {
+    /// Interface number (`bInterfaceNumber`)
+    pub number: u8,
+    /// Class triple of the interface
+    pub class: UsbClass,
+    /// Kernel driver bound to the interface, if any (e.g., "usb-storage")
+    pub driver: Option<String>,
+}
+
+/// A network interface of a USB Ethernet adapter, modem or tethered phone.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NetworkInterface {
+    /// Interface name given by the kernel or udev (e.g., "enx0050b6123456" or "usb0")
+    pub name: String,
+    /// Hardware address, e.g. "00:50:b6:12:34:56", if the interface has one
+    pub mac_address: Option<String>,
+    /// Number of the USB interface it is provided by
+    pub usb_interface: u8,
+}
+
+/// Describes a network interface, e.g. `usb0 (02:11:22:33:44:55)`.
+impl std::fmt::Display for NetworkInterface {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.name)?;
+        if let Some(mac_address) = &self.mac_address {
+            write!(f, " ({mac_address})")?;
+        }
+        Ok(())
+    }
+}
+
+/// A disk of a USB storage device, or one of its partitions.
+///
+/// A card reader has a disk per slot, and flash drives are sometimes
+/// formatted without a partition table, so the disk itself may carry a
+/// filesystem and be mounted.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockDevice {
+    /// Device file (e.g., "/dev/sdb" or "/dev/sdb1")
+    pub path: String,
+    /// Capacity in bytes, if known
+    pub size_bytes: Option<u64>,
+    /// Filesystem type (e.g., "vfat"), if known
+    pub filesystem: Option<String>,
+    /// Filesystem label, if it has one
+    pub label: Option<String>,
+    /// Filesystem UUID, if known
+    pub uuid: Option<String>,
+    /// Where the filesystem is mounted, in mount order
+    #[serde(default)]
+    pub mount_points: Vec<String>,
+    /// Partitions of a disk, ordered by partition number; always empty for
+    /// a partition
+    #[serde(default)]
+    pub partitions: Vec<BlockDevice>,
+}
+
+impl BlockDevice {
+    /// Returns the disk followed by its partitions.
+    pub fn volumes(&self) -> impl Iterator<Item = &BlockDevice> {
+        std::iter::once(self).chain(&self.partitions)
+    }
+}
+
+/// Describes a volume and where it is mounted, e.g. `/dev/sdb1 (vfat "DATA") on /media/data`.
+impl std::fmt::Display for BlockDevice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.path)?;
+        match (&self.filesystem, &self.label) {
+            (Some(filesystem), Some(label)) => write!(f, " ({filesystem} \"{label}\")")?,
+            (Some(filesystem), None) => write!(f, " ({filesystem})")?,
+            (None, Some(label)) => write!(f, " (\"{label}\")")?,
+            (None, None) => {}
+        }
+        if !self.mount_points.is_empty() {
+            write!(f, " on {}", self.mount_points.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
 /// Information about a USB device and its connection event.
 ///
 /// This structure contains all relevant metadata about a USB device,
@@ -69,16 +553,91 @@ pub trait AsDeviceHandle {
 pub struct UsbDeviceInfo {
     /// Human-readable name of the device
     pub device_name: String,
-    /// USB Vendor ID in hexadecimal format (e.g., "1d6b")
-    pub vendor_id: String,
-    /// USB Product ID in hexadecimal format (e.g., "0002")
-    pub product_id: String,
+    /// USB Vendor ID, serialized in hexadecimal (e.g., "1d6b")
+    pub vendor_id: VendorId,
+    /// USB Product ID, serialized in hexadecimal (e.g., "0002")
+    pub product_id: ProductId,
+    /// Vendor name from the USB ID database (e.g., "STMicroelectronics"), if
+    /// the watcher has a database listing the vendor; see
+    /// [`UsbIds::annotate`](crate::usb_ids::UsbIds::annotate)
+    #[serde(default)]
+    pub vendor_name: Option<String>,
+    /// Product name from the USB ID database (e.g., "ST-LINK/V2"), which can
+    /// differ from `device_name`, the name the device reports itself
+    #[serde(default)]
+    pub product_name: Option<String>,
+    /// Device class named by the USB ID database (e.g., "Hub / Unused / Single
+    /// TT"), or `None` if the class is defined by the interfaces
+    #[serde(default)]
+    pub class_name: Option<String>,
     /// Optional serial number of the device
     pub serial_number: Option<String>,
+    /// Stable identifier used to correlate connect and disconnect events
+    #[serde(default)]
+    pub device_id: DeviceId,
     /// UTC timestamp when the event occurred
     pub timestamp: DateTime<Utc>,
     /// Type of device event (connected or disconnected)
     pub event_type: DeviceEventType,
+    /// USB bus number the device is attached to, if known
+    #[serde(default)]
+    pub bus_number: Option<u8>,
+    /// Dot-separated chain of hub ports from the root hub to the device (e.g., "1.4"),
+    /// or `None` for root hubs and when the topology is unknown
+    #[serde(default)]
+    pub port_path: Option<String>,
+    /// Identifier of the hub the device is plugged into (the Linux sysfs name,
+    /// e.g., "1-1" or "usb1"), or `None` for root hubs
+    #[serde(default)]
+    pub parent: Option<String>,
+    /// Class triple from the device descriptor, if known
+    #[serde(default)]
+    pub device_class: Option<UsbClass>,
+    /// USB specification release the device complies with (`bcdUSB`), if known
+    #[serde(default)]
+    pub usb_version: Option<BcdVersion>,
+    /// Release number of the device (`bcdDevice`), if known
+    #[serde(default)]
+    pub device_version: Option<BcdVersion>,
+    /// Negotiated link speed, currently only reported on Linux
+    #[serde(default)]
+    pub speed: Option<UsbSpeed>,
+    /// Most current the device draws from the bus in its active configuration,
+    /// in mA (`bMaxPower`), currently only reported on Linux
+    #[serde(default)]
+    pub max_power_ma: Option<u16>,
+    /// Active configuration (`bConfigurationValue`), or `None` if the device
+    /// is unconfigured or the platform doesn't report it
+    #[serde(default)]
+    pub configuration: Option<u8>,
+    /// Number of interfaces of the active configuration (`bNumInterfaces`)
+    #[serde(default)]
+    pub num_interfaces: Option<u8>,
+    /// Number of downstream ports of a hub (`maxchild`), 0 for other devices
+    #[serde(default)]
+    pub max_child: Option<u8>,
+    /// Whether the device is plugged into a port the user can reach, as
+    /// opposed to one wired inside the machine, if the platform knows
+    #[serde(default)]
+    pub removable: Option<bool>,
+    /// Interfaces of the active configuration, ordered by interface number
+    #[serde(default)]
+    pub interfaces: Vec<UsbInterface>,
+    /// Whether the device is authorized to be used, if the platform reports it
+    #[serde(default)]
+    pub authorized: Option<bool>,
+    /// Device files for the device and its interfaces (e.g., "/dev/ttyACM0"),
+    /// currently only reported on Linux
+    #[serde(default)]
+    pub device_nodes: Vec<String>,
+    /// Disks of a storage device with their partitions and mount points,
+    /// currently only reported on Linux
+    #[serde(default)]
+    pub storage: Vec<BlockDevice>,
+    /// Network interfaces of the device ordered by USB interface, currently
+    /// only reported on Linux
+    #[serde(default)]
+    pub network_interfaces: Vec<NetworkInterface>,
     /// Platform-specific device handle for advanced operations
     #[serde(skip)]
     pub device_handle: DeviceHandle,
@@ -91,6 +650,65 @@ pub enum DeviceEventType {
     Connected,
     /// Device was disconnected from the system
     Disconnected,
+    /// Attributes of a connected device changed, e.g. a driver was bound or
+    /// the device was deauthorized
+    Changed(Vec<FieldChange>),
+    /// Device kept connecting and disconnecting; `count` is the number of
+    /// toggles seen within the flap detection window
+    Flapping {
+        /// Number of connects and disconnects within the window
+        count: usize,
+    },
+}
+
+impl DeviceEventType {
+    /// Returns the kind of event, without the data it carries.
+    pub fn kind(&self) -> EventKind {
+        match self {
+            DeviceEventType::Connected => EventKind::Connected,
+            DeviceEventType::Disconnected => EventKind::Disconnected,
+            DeviceEventType::Changed(_) => EventKind::Changed,
+            DeviceEventType::Flapping { .. } => EventKind::Flapping,
+        }
+    }
+}
+
+/// The kinds of [`DeviceEventType`], for matching events without their data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EventKind {
+    /// [`DeviceEventType::Connected`]
+    Connected,
+    /// [`DeviceEventType::Disconnected`]
+    Disconnected,
+    /// [`DeviceEventType::Changed`], whatever changed
+    Changed,
+    /// [`DeviceEventType::Flapping`], whatever the count
+    Flapping,
+}
+
+/// A device attribute that changed between two observations.
+///
+/// Values are rendered as strings; `None` means the attribute was absent.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct FieldChange {
+    /// Name of the changed field, as it appears in JSON output (e.g., "interfaces")
+    pub field: String,
+    /// Value before the change
+    pub old: Option<String>,
+    /// Value after the change
+    pub new: Option<String>,
+}
+
+impl std::fmt::Display for FieldChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {} -> {}",
+            self.field,
+            self.old.as_deref().unwrap_or("-"),
+            self.new.as_deref().unwrap_or("-")
+        )
+    }
 }
 
 impl UsbDeviceInfo {
@@ -102,20 +720,20 @@ impl UsbDeviceInfo {
     /// # Arguments
     ///
     /// * `device_name` - Human-readable name of the device
-    /// * `vendor_id` - USB Vendor ID in hexadecimal format
-    /// * `product_id` - USB Product ID in hexadecimal format
+    /// * `vendor_id` - USB Vendor ID
+    /// * `product_id` - USB Product ID
     /// * `serial_number` - Optional serial number
     /// * `event_type` - Type of device event
     ///
     /// # Examples
     ///
     /// ```
-    /// use usbwatch_rs::device_info::{UsbDeviceInfo, DeviceEventType};
+    /// use usbwatch_rs::device_info::{UsbDeviceInfo, DeviceEventType, ProductId, VendorId};
     ///
     /// let device = UsbDeviceInfo::new(
     ///     "USB Storage Device".to_string(),
-    ///     "0781".to_string(),
-    ///     "5583".to_string(),
+    ///     VendorId(0x0781),
+    ///     ProductId(0x5583),
     ///     Some("1234567890".to_string()),
     ///     DeviceEventType::Connected,
     /// );
@@ -123,18 +741,39 @@ impl UsbDeviceInfo {
     #[allow(dead_code)] // Used by platform implementations and library consumers
     pub fn new(
         device_name: String,
-        vendor_id: String,
-        product_id: String,
+        vendor_id: VendorId,
+        product_id: ProductId,
         serial_number: Option<String>,
         event_type: DeviceEventType,
     ) -> Self {
         Self {
+            device_id: DeviceId::new(vendor_id, product_id, serial_number.as_deref(), None),
             device_name,
             vendor_id,
             product_id,
+            vendor_name: None,
+            product_name: None,
+            class_name: None,
             serial_number,
             timestamp: Utc::now(),
             event_type,
+            bus_number: None,
+            port_path: None,
+            parent: None,
+            device_class: None,
+            usb_version: None,
+            device_version: None,
+            speed: None,
+            max_power_ma: None,
+            configuration: None,
+            num_interfaces: None,
+            max_child: None,
+            removable: None,
+            interfaces: Vec::new(),
+            authorized: None,
+            device_nodes: Vec::new(),
+            storage: Vec::new(),
+            network_interfaces: Vec::new(),
             device_handle: DeviceHandle::Unknown,
         }
     }
@@ -144,8 +783,8 @@ impl UsbDeviceInfo {
     /// # Arguments
     ///
     /// * `device_name` - Human-readable name of the device
-    /// * `vendor_id` - USB Vendor ID in hexadecimal format
-    /// * `product_id` - USB Product ID in hexadecimal format
+    /// * `vendor_id` - USB Vendor ID
+    /// * `product_id` - USB Product ID
     /// * `serial_number` - Optional serial number
     /// * `event_type` - Type of device event
     /// * `device_handle` - Platform-specific device handle
@@ -153,20 +792,20 @@ impl UsbDeviceInfo {
     /// # Examples
     ///
     /// ```
-    /// use usbwatch_rs::device_info::{UsbDeviceInfo, DeviceEventType, DeviceHandle};
+    /// use usbwatch_rs::device_info::{UsbDeviceInfo, DeviceEventType, DeviceHandle, ProductId, VendorId};
     ///
     /// # #[cfg(target_os = "linux")]
     /// let handle = DeviceHandle::Linux {
     ///     sysfs_path: "/sys/bus/usb/devices/1-1".to_string(),
-    ///     device_node: Some("/dev/ttyUSB0".to_string()),
+    ///     device_nodes: vec!["/dev/ttyUSB0".to_string()],
     /// };
     /// # #[cfg(not(target_os = "linux"))]
     /// # let handle = DeviceHandle::Unknown;
     ///
     /// let device = UsbDeviceInfo::with_handle(
     ///     "USB Serial".to_string(),
-    ///     "0403".to_string(),
-    ///     "6001".to_string(),
+    ///     VendorId(0x0403),
+    ///     ProductId(0x6001),
     ///     None,
     ///     DeviceEventType::Connected,
     ///     handle,
@@ -175,23 +814,227 @@ impl UsbDeviceInfo {
     #[allow(dead_code)]
     pub fn with_handle(
         device_name: String,
-        vendor_id: String,
-        product_id: String,
+        vendor_id: VendorId,
+        product_id: ProductId,
         serial_number: Option<String>,
         event_type: DeviceEventType,
         device_handle: DeviceHandle,
     ) -> Self {
         Self {
+            device_id: DeviceId::new(vendor_id, product_id, serial_number.as_deref(), None),
             device_name,
             vendor_id,
             product_id,
+            vendor_name: None,
+            product_name: None,
+            class_name: None,
             serial_number,
             timestamp: Utc::now(),
             event_type,
+            bus_number: None,
+            port_path: None,
+            parent: None,
+            device_class: None,
+            usb_version: None,
+            device_version: None,
+            speed: None,
+            max_power_ma: None,
+            configuration: None,
+            num_interfaces: None,
+            max_child: None,
+            removable: None,
+            interfaces: Vec::new(),
+            authorized: None,
+            device_nodes: Vec::new(),
+            storage: Vec::new(),
+            network_interfaces: Vec::new(),
             device_handle,
         }
     }
 
+    /// Updates the [`DeviceId`] for a device plugged in at `location`.
+    ///
+    /// Only devices without a serial number are identified by location, so
+    /// this leaves the id of other devices unchanged.
+    pub fn set_location(&mut self, location: &str) {
+        self.device_id = DeviceId::new(
+            self.vendor_id,
+            self.product_id,
+            self.serial_number.as_deref(),
+            Some(location),
+        );
+    }
+
+    /// Returns the class names describing what the device is, e.g. `["Mass Storage"]`.
+    ///
+    /// Uses the device class unless it defers to the interfaces, in which case
+    /// the distinct interface classes are listed in interface order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use usbwatch_rs::device_info::{
+    ///     DeviceEventType, ProductId, UsbClass, UsbDeviceInfo, UsbInterface, VendorId,
+    /// };
+    ///
+    /// let mut headset = UsbDeviceInfo::new(
+    ///     "USB Headset".to_string(),
+    ///     VendorId(0x046d),
+    ///     ProductId(0x0a8f),
+    ///     None,
+    ///     DeviceEventType::Connected,
+    /// );
+    /// headset.device_class = Some(UsbClass::new(0x00, 0x00, 0x00));
+    /// for (number, class) in [(0, 0x01), (1, 0x01), (3, 0x03)] {
+    ///     headset.interfaces.push(UsbInterface {
+    ///         number,
+    ///         class: UsbClass::new(class, 0x00, 0x00),
+    ///         driver: None,
+    ///     });
+    /// }
+    ///
+    /// assert_eq!(headset.class_names(), ["Audio", "HID"]);
+    /// ```
+    pub fn class_names(&self) -> Vec<&'static str> {
+        match self.device_class {
+            // Class 0x00 and the interface association class 0xef/0x02/0x01 defer to the interfaces
+            Some(class) if class.class != 0x00 && class != UsbClass::new(0xef, 0x02, 0x01) => {
+                vec![class.name()]
+            }
+            _ => {
+                let mut names: Vec<&'static str> = Vec::new();
+                for interface in &self.interfaces {
+                    let name = interface.class.name();
+                    if !names.contains(&name) {
+                        names.push(name);
+                    }
+                }
+                names
+            }
+        }
+    }
+
+    /// Lists the attributes that differ from an earlier observation of the same device.
+    ///
+    /// Compares everything except the identifiers, timestamp, event type and
+    /// device handle.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use usbwatch_rs::device_info::{DeviceEventType, ProductId, UsbDeviceInfo, VendorId};
+    ///
+    /// let before = UsbDeviceInfo::new(
+    ///     "USB Serial".to_string(),
+    ///     VendorId(0x0403),
+    ///     ProductId(0x6001),
+    ///     None,
+    ///     DeviceEventType::Connected,
+    /// );
+    /// let mut after = before.clone();
+    /// after.authorized = Some(false);
+    ///
+    /// let changes = after.changes_from(&before);
+    /// assert_eq!(changes.len(), 1);
+    /// assert_eq!(changes[0].to_string(), "authorized: - -> false");
+    /// ```
+    pub fn changes_from(&self, previous: &UsbDeviceInfo) -> Vec<FieldChange> {
+        fn show<T: ToString>(value: &Option<T>) -> Option<String> {
+            value.as_ref().map(ToString::to_string)
+        }
+        fn show_list<T>(values: &[T], item: impl Fn(&T) -> String) -> Option<String> {
+            (!values.is_empty()).then(|| values.iter().map(item).collect::<Vec<_>>().join(", "))
+        }
+        fn show_storage(disk: &BlockDevice) -> String {
+            disk.volumes()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        }
+        fn show_interface(interface: &UsbInterface) -> String {
+            format!(
+                "{}: {} [{}]",
+                interface.number,
+                interface.class,
+                interface.driver.as_deref().unwrap_or("-")
+            )
+        }
+
+        let fields = [
+            (
+                "device_name",
+                Some(previous.device_name.clone()),
+                Some(self.device_name.clone()),
+            ),
+            (
+                "serial_number",
+                previous.serial_number.clone(),
+                self.serial_number.clone(),
+            ),
+            (
+                "bus_number",
+                show(&previous.bus_number),
+                show(&self.bus_number),
+            ),
+            (
+                "port_path",
+                previous.port_path.clone(),
+                self.port_path.clone(),
+            ),
+            ("parent", previous.parent.clone(), self.parent.clone()),
+            (
+                "device_class",
+                show(&previous.device_class),
+                show(&self.device_class),
+            ),
+            (
+                "configuration",
+                show(&previous.configuration),
+                show(&self.configuration),
+            ),
+            (
+                "max_power_ma",
+                show(&previous.max_power_ma),
+                show(&self.max_power_ma),
+            ),
+            (
+                "interfaces",
+                show_list(&previous.interfaces, show_interface),
+                show_list(&self.interfaces, show_interface),
+            ),
+            (
+                "authorized",
+                show(&previous.authorized),
+                show(&self.authorized),
+            ),
+            (
+                "device_nodes",
+                show_list(&previous.device_nodes, String::clone),
+                show_list(&self.device_nodes, String::clone),
+            ),
+            (
+                "storage",
+                show_list(&previous.storage, show_storage),
+                show_list(&self.storage, show_storage),
+            ),
+            (
+                "network_interfaces",
+                show_list(&previous.network_interfaces, ToString::to_string),
+                show_list(&self.network_interfaces, ToString::to_string),
+            ),
+        ];
+
+        fields
+            .into_iter()
+            .filter(|(_, old, new)| old != new)
+            .map(|(field, old, new)| FieldChange {
+                field: field.to_string(),
+                old,
+                new,
+            })
+            .collect()
+    }
+
     /// Formats the device information as a human-readable string.
     ///
     /// Returns a formatted string suitable for console output or log files.
@@ -201,12 +1044,12 @@ impl UsbDeviceInfo {
     /// # Examples
     ///
     /// ```
-    /// use usbwatch_rs::device_info::{UsbDeviceInfo, DeviceEventType};
+    /// use usbwatch_rs::device_info::{UsbDeviceInfo, DeviceEventType, ProductId, VendorId};
     ///
     /// let device = UsbDeviceInfo::new(
     ///     "USB Storage".to_string(),
-    ///     "0781".to_string(),
-    ///     "5583".to_string(),
+    ///     VendorId(0x0781),
+    ///     ProductId(0x5583),
     ///     None,
     ///     DeviceEventType::Connected,
     /// );
@@ -218,6 +1061,8 @@ impl UsbDeviceInfo {
         let event_str = match self.event_type {
             DeviceEventType::Connected => "CONNECTED",
             DeviceEventType::Disconnected => "DISCONNECTED",
+            DeviceEventType::Changed(_) => "CHANGED",
+            DeviceEventType::Flapping { .. } => "FLAPPING",
         };
 
         let serial_str = self
@@ -226,14 +1071,28 @@ impl UsbDeviceInfo {
             .map(|s| format!(" Serial: {s}"))
             .unwrap_or_default();
 
+        let changes_str = match &self.event_type {
+            DeviceEventType::Changed(changes) => format!(
+                " [{}]",
+                changes
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join("; ")
+            ),
+            DeviceEventType::Flapping { count } => format!(" [{count} toggles]"),
+            _ => String::new(),
+        };
+
         format!(
-            "[{}] {} - {} (VID: {}, PID: {}){}",
+            "[{}] {} - {} (VID: {}, PID: {}){}{}",
             self.timestamp.format("%Y-%m-%d %H:%M:%S UTC"),
             event_str,
             self.device_name,
             self.vendor_id,
             self.product_id,
-            serial_str
+            serial_str,
+            changes_str
         )
     }
 }
@@ -255,6 +1114,8 @@ impl std::fmt::Display for DeviceEventType {
         match self {
             DeviceEventType::Connected => write!(f, "Connected"),
             DeviceEventType::Disconnected => write!(f, "Disconnected"),
+            DeviceEventType::Changed(_) => write!(f, "Changed"),
+            DeviceEventType::Flapping { .. } => write!(f, "Flapping"),
         }
     }
 }
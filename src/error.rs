@@ -0,0 +1,159 @@
+//! Error type for USB monitoring operations.
+//!
+//! Every fallible function in the crate returns [`UsbWatchError`], so callers
+//! can match on the cause of a failure instead of inspecting messages.
+
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+/// Errors returned by USB monitoring operations.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use usbwatch_rs::{list_devices, UsbWatchError};
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// match list_devices().await {
+///     Ok(devices) => println!("{} devices", devices.len()),
+///     Err(UsbWatchError::PermissionDenied { .. }) => eprintln!("Try running as root"),
+///     Err(e) => eprintln!("Error: {e}"),
+/// }
+/// # }
+/// ```
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum UsbWatchError {
+    /// The sysfs USB device directory does not exist, e.g. because sysfs is
+    /// not mounted or a custom sysfs root is wrong
+    SysfsUnavailable {
+        /// Directory that was expected to list the USB devices
+        path: PathBuf,
+    },
+    /// The operating system refused access to a file, socket or device
+    PermissionDenied {
+        /// What was being accessed
+        context: String,
+        /// Underlying error
+        source: io::Error,
+    },
+    /// The receiving end of the event channel was dropped
+    ChannelClosed,
+    /// An event could not be serialised to JSON
+    Serialization(serde_json::Error),
+    /// A line of a recorded session is not a valid event
+    InvalidRecording {
+        /// Line number, starting at 1
+        line: usize,
+        /// Underlying error
+        source: serde_json::Error,
+    },
+    /// A configuration file is malformed or contains invalid settings
+    InvalidConfigFile {
+        /// Path of the file
+        path: PathBuf,
+        /// Line number, starting at 1, or `None` if the error is not about
+        /// one line
+        line: Option<usize>,
+        /// What is wrong
+        message: String,
+    },
+    /// Any other I/O failure
+    Io {
+        /// What was being done
+        context: String,
+        /// Underlying error
+        source: io::Error,
+    },
+    /// The operation is not available on this platform
+    Unsupported(String),
+    /// Options, filter rules or other input were rejected
+    InvalidConfig(String),
+    /// A platform API such as SetupAPI or IOKit reported an error
+    Platform(String),
+    /// The background task running the watcher panicked or was cancelled
+    TaskFailed(String),
+}
+
+impl UsbWatchError {
+    /// Wraps an I/O error, reporting permission errors as
+    /// [`PermissionDenied`](Self::PermissionDenied).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io;
+    /// use usbwatch_rs::UsbWatchError;
+    ///
+    /// let err = UsbWatchError::io(
+    ///     "Failed to open log file 'usb.log'",
+    ///     io::Error::from(io::ErrorKind::PermissionDenied),
+    /// );
+    /// assert!(matches!(err, UsbWatchError::PermissionDenied { .. }));
+    /// ```
+    pub fn io(context: impl Into<String>, source: io::Error) -> Self {
+        let context = context.into();
+        if source.kind() == io::ErrorKind::PermissionDenied {
+            UsbWatchError::PermissionDenied { context, source }
+        } else {
+            UsbWatchError::Io { context, source }
+        }
+    }
+}
+
+impl fmt::Display for UsbWatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UsbWatchError::SysfsUnavailable { path } => write!(
+                f,
+                "USB devices path {} not found. Make sure you're running on Linux with USB support.",
+                path.display()
+            ),
+            UsbWatchError::PermissionDenied { context, source } => {
+                write!(f, "{context}: {source}")
+            }
+            UsbWatchError::ChannelClosed => write!(f, "Event channel closed"),
+            UsbWatchError::Serialization(e) => write!(f, "Failed to serialise event: {e}"),
+            UsbWatchError::InvalidRecording { line, source } => {
+                write!(f, "Invalid event on line {line} of recording: {source}")
+            }
+            UsbWatchError::InvalidConfigFile {
+                path,
+                line: Some(line),
+                message,
+            } => write!(f, "{}:{line}: {message}", path.display()),
+            UsbWatchError::InvalidConfigFile {
+                path,
+                line: None,
+                message,
+            } => write!(f, "{}: {message}", path.display()),
+            UsbWatchError::Io { context, source } => write!(f, "{context}: {source}"),
+            UsbWatchError::Unsupported(what) => write!(f, "{what}"),
+            UsbWatchError::InvalidConfig(message) => write!(f, "{message}"),
+            UsbWatchError::Platform(message) => write!(f, "{message}"),
+            UsbWatchError::TaskFailed(message) => write!(f, "USB watcher task failed: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for UsbWatchError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            UsbWatchError::PermissionDenied { source, .. } | UsbWatchError::Io { source, .. } => {
+                Some(source)
+            }
+            UsbWatchError::Serialization(e) | UsbWatchError::InvalidRecording { source: e, .. } => {
+                Some(e)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl From<serde_json::Error> for UsbWatchError {
+    fn from(e: serde_json::Error) -> Self {
+        UsbWatchError::Serialization(e)
+    }
+}
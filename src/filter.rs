@@ -0,0 +1,298 @@
+//! Device filtering.
+//!
+//! A [`DeviceFilter`] decides which devices a [`UsbWatcher`](crate::UsbWatcher)
+//! reports. Attach one with [`UsbWatcherBuilder::filter`](crate::UsbWatcherBuilder::filter).
+
+use crate::device_info::{EventKind, ProductId, UsbClass, UsbDeviceInfo, VendorId};
+use crate::error::UsbWatchError;
+use regex::Regex;
+use std::str::FromStr;
+
+/// A single condition on a device or event.
+#[derive(Debug, Clone)]
+pub enum FilterRule {
+    /// USB Vendor ID
+    VendorId(VendorId),
+    /// USB Product ID
+    ProductId(ProductId),
+    /// Exact serial number
+    Serial(String),
+    /// Regular expression matched against the device name
+    NameRegex(Regex),
+    /// Base class code of the device or any of its interfaces (e.g., 0x03 for HID)
+    Class(u8),
+    /// Devices providing a network interface, such as Ethernet adapters and
+    /// tethered phones, whatever their class; written `class=net`. The Linux
+    /// watcher reports a device as connected once its interface appears, and as
+    /// disconnected once it goes away
+    Network,
+    /// Kind of the event
+    EventType(EventKind),
+}
+
+impl FilterRule {
+    /// Returns true if the device satisfies this rule.
+    pub fn matches(&self, device: &UsbDeviceInfo) -> bool {
+        match self {
+            FilterRule::VendorId(id) => device.vendor_id == *id,
+            FilterRule::ProductId(id) => device.product_id == *id,
+            FilterRule::Serial(serial) => device.serial_number.as_deref() == Some(serial.as_str()),
+            FilterRule::NameRegex(regex) => regex.is_match(&device.device_name),
+            FilterRule::Class(class) => {
+                device.device_class.is_some_and(|c| c.class == *class)
+                    || device.interfaces.iter().any(|i| i.class.class == *class)
+            }
+            FilterRule::Network => !device.network_interfaces.is_empty(),
+            FilterRule::EventType(kind) => device.event_type.kind() == *kind,
+        }
+    }
+
+    /// Returns true if both rules test the same property.
+    fn same_kind(&self, other: &FilterRule) -> bool {
+        // `class=net` is an alternative to the class codes
+        let kind = |rule: &FilterRule| match rule {
+            FilterRule::Network => std::mem::discriminant(&FilterRule::Class(0)),
+            rule => std::mem::discriminant(rule),
+        };
+        kind(self) == kind(other)
+    }
+}
+
+/// Parses a rule written as `key=value`.
+///
+/// The keys are `vid`, `pid`, `serial`, `name` (a regular expression),
+/// `class` (see [`parse_class`], or `net` for [`FilterRule::Network`]) and
+/// `event` (`connected`, `disconnected`, `changed` or `flapping`).
+///
+/// # Examples
+///
+/// ```
+/// use usbwatch_rs::filter::FilterRule;
+/// use usbwatch_rs::EventKind;
+///
+/// let rule: FilterRule = "class=hub".parse()?;
+/// assert!(matches!(rule, FilterRule::Class(0x09)));
+/// assert!(matches!("class=net".parse()?, FilterRule::Network));
+/// assert!(matches!(
+///     "event=changed".parse()?,
+///     FilterRule::EventType(EventKind::Changed)
+/// ));
+///
+/// assert!("colour=blue".parse::<FilterRule>().is_err());
+/// # Ok::<(), usbwatch_rs::UsbWatchError>(())
+/// ```
+impl FromStr for FilterRule {
+    type Err = UsbWatchError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (key, value) = s
+            .split_once('=')
+            .ok_or_else(|| invalid(format!("Invalid filter rule '{s}': expected KEY=VALUE")))?;
+
+        match key.trim().to_ascii_lowercase().as_str() {
+            "vid" => Ok(FilterRule::VendorId(value.parse()?)),
+            "pid" => Ok(FilterRule::ProductId(value.parse()?)),
+            "serial" => Ok(FilterRule::Serial(value.to_string())),
+            "name" => Regex::new(value)
+                .map(FilterRule::NameRegex)
+                .map_err(|e| invalid(format!("Invalid name pattern '{value}': {e}"))),
+            "class" if is_network_class(value) => Ok(FilterRule::Network),
+            "class" => parse_class(value)
+                .map(FilterRule::Class)
+                .ok_or_else(|| invalid(format!("Unknown USB class '{value}'"))),
+            "event" => match value.trim().to_ascii_lowercase().as_str() {
+                "connected" => Ok(FilterRule::EventType(EventKind::Connected)),
+                "disconnected" => Ok(FilterRule::EventType(EventKind::Disconnected)),
+                "changed" => Ok(FilterRule::EventType(EventKind::Changed)),
+                "flapping" => Ok(FilterRule::EventType(EventKind::Flapping)),
+                _ => Err(invalid(format!("Unknown event type '{value}'"))),
+            },
+            _ => Err(invalid(format!(
+                "Unknown filter key '{key}': expected vid, pid, serial, name, class or event"
+            ))),
+        }
+    }
+}
+
+/// Include and exclude rules deciding which devices are reported.
+///
+/// A device passes the filter when it satisfies the include rules and none of
+/// the exclude rules. Include rules on the same property are alternatives,
+/// while rules on different properties must all hold, so
+/// `.vendor_id(0x0483).vendor_id(0x1366).class(0x02)` accepts CDC devices from
+/// either vendor. A filter without include rules accepts every device that
+/// isn't excluded.
+///
+/// # Examples
+///
+/// ```
+/// use usbwatch_rs::device_info::{DeviceEventType, ProductId, UsbDeviceInfo, VendorId};
+/// use usbwatch_rs::filter::{DeviceFilter, FilterRule};
+///
+/// // ST-Link and J-Link debug probes, but not the J-Link's mass storage mode
+/// let filter = DeviceFilter::new()
+///     .vendor_id(0x0483)
+///     .vendor_id(0x1366)
+///     .exclude(FilterRule::Class(0x08));
+///
+/// let probe = UsbDeviceInfo::new(
+///     "ST-Link V2".to_string(),
+///     VendorId(0x0483),
+///     ProductId(0x3748),
+///     None,
+///     DeviceEventType::Connected,
+/// );
+/// assert!(filter.matches(&probe));
+///
+/// let hub = UsbDeviceInfo::new(
+///     "USB2.0 Hub".to_string(),
+///     VendorId(0x05e3),
+///     ProductId(0x0608),
+///     None,
+///     DeviceEventType::Connected,
+/// );
+/// assert!(!filter.matches(&hub));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DeviceFilter {
+    include: Vec<FilterRule>,
+    exclude: Vec<FilterRule>,
+}
+
+impl DeviceFilter {
+    /// Creates a filter that accepts every device.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an include rule.
+    pub fn include(mut self, rule: FilterRule) -> Self {
+        self.include.push(rule);
+        self
+    }
+
+    /// Adds an exclude rule; devices matching it are never reported.
+    pub fn exclude(mut self, rule: FilterRule) -> Self {
+        self.exclude.push(rule);
+        self
+    }
+
+    /// Only accepts devices with this vendor ID (or another included one).
+    pub fn vendor_id(self, vendor_id: impl Into<VendorId>) -> Self {
+        self.include(FilterRule::VendorId(vendor_id.into()))
+    }
+
+    /// Only accepts devices with this product ID (or another included one).
+    pub fn product_id(self, product_id: impl Into<ProductId>) -> Self {
+        self.include(FilterRule::ProductId(product_id.into()))
+    }
+
+    /// Only accepts devices with this serial number (or another included one).
+    pub fn serial(self, serial: impl Into<String>) -> Self {
+        self.include(FilterRule::Serial(serial.into()))
+    }
+
+    /// Only accepts devices whose name matches this regular expression (or another included one).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`UsbWatchError::InvalidConfig`] if the pattern is not a valid
+    /// regular expression.
+    pub fn name_regex(self, pattern: &str) -> crate::Result<Self> {
+        let regex = Regex::new(pattern)
+            .map_err(|e| invalid(format!("Invalid name pattern '{pattern}': {e}")))?;
+        Ok(self.include(FilterRule::NameRegex(regex)))
+    }
+
+    /// Only accepts devices of this class (or another included one).
+    pub fn class(self, class: u8) -> Self {
+        self.include(FilterRule::Class(class))
+    }
+
+    /// Only accepts devices with a network interface (or an included class).
+    pub fn network(self) -> Self {
+        self.include(FilterRule::Network)
+    }
+
+    /// Only reports events of this type (or another included one).
+    pub fn event_type(self, kind: EventKind) -> Self {
+        self.include(FilterRule::EventType(kind))
+    }
+
+    /// Returns true if the filter has no rules and so accepts every device.
+    pub fn is_empty(&self) -> bool {
+        self.include.is_empty() && self.exclude.is_empty()
+    }
+
+    /// Returns true if the device passes the filter.
+    pub fn matches(&self, device: &UsbDeviceInfo) -> bool {
+        if self.exclude.iter().any(|rule| rule.matches(device)) {
+            return false;
+        }
+
+        // Every property with include rules needs at least one of them to match
+        self.include.iter().all(|rule| {
+            self.include
+                .iter()
+                .filter(|other| other.same_kind(rule))
+                .any(|other| other.matches(device))
+        })
+    }
+}
+
+/// Parses a USB class given by name (e.g., "hid", "mass-storage", "storage")
+/// or as a hexadecimal code (e.g., "08" or "0x08").
+///
+/// Names are matched case-insensitively, ignoring spaces, dashes and underscores.
+///
+/// # Examples
+///
+/// ```
+/// use usbwatch_rs::filter::parse_class;
+///
+/// assert_eq!(parse_class("Mass Storage"), Some(0x08));
+/// assert_eq!(parse_class("mass-storage"), Some(0x08));
+/// assert_eq!(parse_class("0xe0"), Some(0xe0));
+/// assert_eq!(parse_class("bogus"), None);
+/// ```
+pub fn parse_class(value: &str) -> Option<u8> {
+    let wanted = normalize_class_name(value);
+    let alias = match wanted.as_str() {
+        "storage" => Some(0x08),
+        "cdc" => Some(0x02),
+        "vendor" => Some(0xff),
+        _ => None,
+    };
+    let by_name = || {
+        (0..=u8::MAX).find(|&code| {
+            let name = UsbClass::new(code, 0, 0).name();
+            name != "Unknown" && normalize_class_name(name) == wanted
+        })
+    };
+    let by_code = || {
+        let value = value.trim();
+        let digits = value
+            .strip_prefix("0x")
+            .or_else(|| value.strip_prefix("0X"))
+            .unwrap_or(value);
+        u8::from_str_radix(digits, 16).ok()
+    };
+
+    alias.or_else(by_name).or_else(by_code)
+}
+
+/// Returns true if a class value asks for devices with a network interface.
+fn is_network_class(value: &str) -> bool {
+    matches!(normalize_class_name(value).as_str(), "net" | "network")
+}
+
+fn normalize_class_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+fn invalid(message: String) -> UsbWatchError {
+    UsbWatchError::InvalidConfig(message)
+}
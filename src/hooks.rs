@@ -0,0 +1,271 @@
+//! External commands run on device events.
+//!
+//! [`EventHooks`] runs a shell command whenever a device connects or
+//! disconnects. The command gets the device's fields in environment
+//! variables and the event as a line of JSON on stdin:
+//!
+//! | Variable | Value |
+//! | -------- | ----- |
+//! | `USBWATCH_EVENT` | `connected` or `disconnected` |
+//! | `USBWATCH_VID` | Vendor ID in hex, e.g. `0483` |
+//! | `USBWATCH_PID` | Product ID in hex, e.g. `3748` |
+//! | `USBWATCH_SERIAL` | Serial number, or empty if the device has none |
+//! | `USBWATCH_SYSFS_PATH` | sysfs directory of the device on Linux, otherwise unset |
+//! | `USBWATCH_NET_INTERFACES` | Network interface names separated by spaces, e.g. `usb0`, or empty |
+//!
+//! Recordings don't keep the sysfs path, as it only means something on the
+//! machine that recorded them, so `USBWATCH_SYSFS_PATH` is unset for replayed
+//! events.
+//!
+//! Commands run through `sh -c` (`cmd /C` on Windows). Their output goes to
+//! usbwatch's stderr, so it never mixes with events printed on stdout.
+
+use crate::device_info::{DeviceEventType, DeviceHandle, UsbDeviceInfo};
+use crate::error::UsbWatchError;
+use std::io;
+use std::process::{ExitStatus, Stdio};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tokio::sync::Semaphore;
+use tokio::task::JoinHandle;
+
+/// Default number of hook commands that may run at the same time.
+pub const DEFAULT_HOOK_CONCURRENCY: usize = 4;
+
+/// Default time a hook command may run before it is killed.
+pub const DEFAULT_HOOK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Commands to run when devices connect or disconnect.
+///
+/// At most [`max_concurrent`](Self::max_concurrent) commands run at once;
+/// further commands wait for a slot. Clones share the same limit.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use usbwatch_rs::hooks::EventHooks;
+/// use usbwatch_rs::UsbWatcherBuilder;
+/// use std::time::Duration;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let hooks = EventHooks::new()
+///     .on_connect("notify-send \"USB device $USBWATCH_VID:$USBWATCH_PID connected\"")
+///     .timeout(Duration::from_secs(5));
+///
+/// let (watcher, mut rx) = UsbWatcherBuilder::new().build_with_channel()?;
+/// tokio::spawn(async move { watcher.start_monitoring().await });
+///
+/// while let Some(device_info) = rx.recv().await {
+///     hooks.spawn(&device_info);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct EventHooks {
+    on_connect: Option<String>,
+    on_disconnect: Option<String>,
+    timeout: Duration,
+    permits: Arc<Semaphore>,
+}
+
+impl Default for EventHooks {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventHooks {
+    /// Creates hooks that run no commands, with the default concurrency
+    /// limit and timeout.
+    pub fn new() -> Self {
+        Self {
+            on_connect: None,
+            on_disconnect: None,
+            timeout: DEFAULT_HOOK_TIMEOUT,
+            permits: Arc::new(Semaphore::new(DEFAULT_HOOK_CONCURRENCY)),
+        }
+    }
+
+    /// Sets the command to run when a device connects.
+    pub fn on_connect(mut self, command: impl Into<String>) -> Self {
+        self.on_connect = Some(command.into());
+        self
+    }
+
+    /// Sets the command to run when a device disconnects.
+    pub fn on_disconnect(mut self, command: impl Into<String>) -> Self {
+        self.on_disconnect = Some(command.into());
+        self
+    }
+
+    /// Sets how many commands may run at the same time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `limit` is zero.
+    pub fn max_concurrent(mut self, limit: usize) -> Self {
+        assert!(limit > 0, "Hook concurrency limit must be at least 1");
+        self.permits = Arc::new(Semaphore::new(limit));
+        self
+    }
+
+    /// Sets how long a command may run before it is killed.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Returns true if no commands are configured.
+    pub fn is_empty(&self) -> bool {
+        self.on_connect.is_none() && self.on_disconnect.is_none()
+    }
+
+    /// Returns the command to run for `device`'s event, if any.
+    pub fn command_for(&self, device: &UsbDeviceInfo) -> Option<&str> {
+        match device.event_type {
+            DeviceEventType::Connected => self.on_connect.as_deref(),
+            DeviceEventType::Disconnected => self.on_disconnect.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Runs the command for `device`'s event and waits for it to exit.
+    ///
+    /// Returns `Ok(None)` if there is no command for the event, and the
+    /// command's exit status otherwise, whether it succeeded or not.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the command cannot be started, or an
+    /// [`Io`](UsbWatchError::Io) error of kind
+    /// [`TimedOut`](io::ErrorKind::TimedOut) if it was killed for running
+    /// longer than the [`timeout`](Self::timeout).
+    pub async fn run(&self, device: &UsbDeviceInfo) -> crate::Result<Option<ExitStatus>> {
+        let Some(command) = self.command_for(device) else {
+            return Ok(None);
+        };
+        let input = serde_json::to_string(device)? + "\n";
+        let _permit = self
+            .permits
+            .acquire()
+            .await
+            .expect("The hook semaphore is never closed");
+
+        let mut child = shell(command)
+            .envs(environment(device))
+            .stdin(Stdio::piped())
+            .stdout(io::stderr())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| UsbWatchError::io(format!("Failed to run hook '{command}'"), e))?;
+
+        let finished = tokio::time::timeout(self.timeout, async {
+            if let Some(mut stdin) = child.stdin.take() {
+                // The command may exit without reading its input
+                let _ = stdin.write_all(input.as_bytes()).await;
+            }
+            child.wait().await
+        })
+        .await;
+
+        match finished {
+            Ok(status) => status
+                .map(Some)
+                .map_err(|e| UsbWatchError::io(format!("Failed to wait for hook '{command}'"), e)),
+            Err(_) => {
+                let _ = child.kill().await;
+                Err(UsbWatchError::io(
+                    format!(
+                        "Hook '{command}' killed after {}s",
+                        self.timeout.as_secs_f64()
+                    ),
+                    io::Error::from(io::ErrorKind::TimedOut),
+                ))
+            }
+        }
+    }
+
+    /// Runs the command for `device`'s event in the background.
+    ///
+    /// Failures, timeouts and non-zero exit statuses are reported on stderr.
+    /// Returns `None` without spawning a task if there is no command for the
+    /// event.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a Tokio runtime.
+    pub fn spawn(&self, device: &UsbDeviceInfo) -> Option<JoinHandle<()>> {
+        self.command_for(device)?;
+        let hooks = self.clone();
+        let device = device.clone();
+        Some(tokio::spawn(async move {
+            let failure = match hooks.run(&device).await {
+                Ok(Some(status)) if !status.success() => status.to_string(),
+                Ok(_) => return,
+                Err(e) => e.to_string(),
+            };
+            eprintln!(
+                "Hook for {} ({}:{}) failed: {failure}",
+                device.device_name, device.vendor_id, device.product_id
+            );
+        }))
+    }
+}
+
+/// Builds the environment variables describing `device` to a hook.
+fn environment(device: &UsbDeviceInfo) -> Vec<(&'static str, String)> {
+    let event = match device.event_type {
+        DeviceEventType::Connected => "connected",
+        DeviceEventType::Disconnected => "disconnected",
+        DeviceEventType::Changed(_) => "changed",
+        DeviceEventType::Flapping { .. } => "flapping",
+    };
+    let mut variables = vec![
+        ("USBWATCH_EVENT", event.to_string()),
+        ("USBWATCH_VID", device.vendor_id.to_string()),
+        ("USBWATCH_PID", device.product_id.to_string()),
+        (
+            "USBWATCH_SERIAL",
+            device.serial_number.clone().unwrap_or_default(),
+        ),
+        (
+            "USBWATCH_NET_INTERFACES",
+            device
+                .network_interfaces
+                .iter()
+                .map(|interface| interface.name.as_str())
+                .collect::<Vec<_>>()
+                .join(" "),
+        ),
+    ];
+    variables.extend(sysfs_path(device).map(|path| ("USBWATCH_SYSFS_PATH", path)));
+    variables
+}
+
+/// Returns the sysfs directory of a device seen on this machine, which
+/// replayed events don't have.
+fn sysfs_path(device: &UsbDeviceInfo) -> Option<String> {
+    match &device.device_handle {
+        #[cfg(target_os = "linux")]
+        DeviceHandle::Linux { sysfs_path, .. } => Some(sysfs_path.clone()),
+        _ => None,
+    }
+}
+
+#[cfg(windows)]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("cmd");
+    shell.arg("/C").arg(command);
+    shell
+}
+
+#[cfg(not(windows))]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("sh");
+    shell.arg("-c").arg(command);
+    shell
+}
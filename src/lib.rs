@@ -6,7 +6,7 @@
 //!
 //! ## Features
 //!
-//! - **Cross-platform**: Linux (sysfs), Windows (Win32 APIs), macOS (IOKit)
+//! - **Cross-platform**: Linux (netlink uevents + sysfs), Windows (Win32 APIs), macOS (IOKit)
 //! - **Real-time monitoring**: Detect USB events as they happen
 //! - **Multiple output formats**: Plain text and JSON
 //! - **File logging**: Save events to log files
@@ -29,6 +29,13 @@
 //! # Monitor and log to file
 //! usbwatch --logfile usb-events.log
 //!
+//! # List connected devices as a table or JSON array
+//! usbwatch list
+//! usbwatch list --json
+//!
+//! # Show the hub and port topology
+//! usbwatch tree
+//!
 //! # Monitor with coloured output (default if supported)
 //! usbwatch
 //!
@@ -61,9 +68,9 @@
 //!         // Access platform-specific device handle
 //!         match device_info.as_device_handle() {
 //!             #[cfg(target_os = "linux")]
-//!             DeviceHandle::Linux { sysfs_path, device_node } => {
+//!             DeviceHandle::Linux { sysfs_path, device_nodes } => {
 //!                 println!("Linux sysfs path: {}", sysfs_path);
-//!                 if let Some(node) = device_node {
+//!                 for node in device_nodes {
 //!                     println!("Device node: {}", node);
 //!                 }
 //!             }
@@ -91,35 +98,69 @@
 //! ## Library API Highlights
 //!
 //! - [`UsbWatcher`] - Cross-platform watcher for USB device events
+//! - [`UsbWatcherBuilder`] - Configure polling intervals, startup events and channel capacity
+//! - [`StopHandle`] - Stop a running watcher cleanly
+//! - [`DeviceEventStream`] - Consume device events as a `futures` [`Stream`](futures_core::Stream)
 //! - [`UsbDeviceInfo`] - Struct containing device metadata and event info
 //! - [`DeviceHandle`] - Enum for platform-specific device handles
 //! - [`AsDeviceHandle`] - Trait for accessing device handles from device info
 //! - [`create_watcher`] - Convenience function for watcher creation
 //! - [`monitor_with_callback`] - High-level async monitoring with callback
 //! - [`monitor_for_duration`] - Collect events for a fixed duration
+//! - [`list_devices`] - Snapshot of the devices connected right now
+//! - [`device_tree`] - Hub and port topology of the connected devices
+//! - [`usb_ids::UsbIds`] - Name devices from the `usb.ids` database when they don't name themselves
+//! - [`DeviceFilter`] - Report only the devices you care about
+//! - [`Logger`] - Write events to the console, log files or your own [`EventSink`]s
+//! - [`EventHooks`] - Run commands when devices connect or disconnect
+//! - [`config::Config`] - Load the TOML settings file used by the command-line tool
+//! - [`Debouncer`] - Drop short reconnects and collapse flapping devices into one event
+//! - [`UsbBackend`] - Plug in a custom source of devices, such as the scripted [`MockBackend`]
+//! - [`ReplayBackend`] - Re-emit a recorded session with its original timing
 //!
 //! ## Platform Support
 //!
-//! - **Linux**: Uses sysfs filesystem (`/sys/bus/usb/devices`)
+//! - **Linux**: Uses kernel uevents with the sysfs filesystem (`/sys/bus/usb/devices`),
+//!   falling back to polling sysfs when the netlink socket is unavailable
 //! - **Windows**: Uses Win32 Device Installation APIs
 //!
 //! ## Error Handling
 //!
-//! All public APIs use `Result` types for proper error handling. Platform-specific
-//! errors are wrapped in boxed `std::error::Error` for consistency.
+//! All public APIs return [`Result`], whose error type [`UsbWatchError`] names
+//! the cause of a failure (missing sysfs, permission denied, closed channel,
+//! unsupported platform, ...) so it can be matched on.
 
 #![warn(missing_docs)]
 #![warn(rust_2018_idioms)]
 #![deny(unsafe_op_in_unsafe_fn)]
 
+pub mod config;
 pub mod device_info;
+pub mod error;
+pub mod filter;
+pub mod hooks;
 pub mod logger;
+pub mod policy;
+pub mod topology;
+pub mod usb_ids;
 pub mod watcher;
 
 // Re-export commonly used types
-pub use device_info::{AsDeviceHandle, DeviceEventType, DeviceHandle, UsbDeviceInfo};
-pub use logger::{logger_task, Logger};
-pub use watcher::UsbWatcher;
+pub use device_info::{
+    AsDeviceHandle, BcdVersion, BlockDevice, DeviceEventType, DeviceHandle, DeviceId, EventKind,
+    FieldChange, NetworkInterface, ProductId, UsbClass, UsbDeviceInfo, UsbInterface, UsbSpeed,
+    VendorId,
+};
+pub use error::UsbWatchError;
+pub use filter::{DeviceFilter, FilterRule};
+pub use hooks::EventHooks;
+pub use logger::{logger_task, EventSink, Logger, RotationPolicy};
+pub use policy::{Policy, PolicyAction};
+pub use topology::UsbTreeNode;
+pub use watcher::{
+    Debouncer, DeviceEventStream, FlapDetection, MockBackend, ReplayBackend, StopHandle,
+    UsbBackend, UsbWatcher, UsbWatcherBuilder, WatcherConfig,
+};
 
 /// Library version information
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -131,7 +172,7 @@ pub const NAME: &str = env!("CARGO_PKG_NAME");
 pub const DESCRIPTION: &str = env!("CARGO_PKG_DESCRIPTION");
 
 /// A result type for USB monitoring operations
-pub type Result<T> = std::result::Result<T, String>;
+pub type Result<T> = std::result::Result<T, UsbWatchError>;
 
 /// Create a new USB watcher with the given channel sender.
 ///
@@ -162,7 +203,7 @@ pub type Result<T> = std::result::Result<T, String>;
 /// }
 /// ```
 pub fn create_watcher(sender: tokio::sync::mpsc::Sender<UsbDeviceInfo>) -> Result<UsbWatcher> {
-    UsbWatcher::new(sender).map_err(|e| e.to_string())
+    UsbWatcher::new(sender)
 }
 
 /// Start monitoring USB devices with a callback function.
@@ -196,8 +237,7 @@ pub async fn monitor_with_callback<F>(mut callback: F) -> Result<()>
 where
     F: FnMut(UsbDeviceInfo) + Send + 'static,
 {
-    let (tx, mut rx) = tokio::sync::mpsc::channel(100);
-    let watcher = create_watcher(tx)?;
+    let (watcher, mut rx) = UsbWatcherBuilder::new().build_with_channel()?;
 
     // Process events with callback in background
     let callback_handle = tokio::spawn(async move {
@@ -209,10 +249,11 @@ where
     // Start monitoring (this will block until monitoring completes)
     let monitoring_result = watcher.start_monitoring().await;
 
-    // Stop callback processing
-    callback_handle.abort();
+    // Dropping the watcher closes the channel, letting the callback drain pending events
+    drop(watcher);
+    let _ = callback_handle.await;
 
-    monitoring_result.map_err(|e| e.to_string())
+    monitoring_result
 }
 
 /// Start monitoring USB devices and collect events into a vector.
@@ -248,51 +289,92 @@ where
 /// }
 /// ```
 pub async fn monitor_for_duration(duration: std::time::Duration) -> Result<Vec<UsbDeviceInfo>> {
-    let (tx, mut rx) = tokio::sync::mpsc::channel(100);
-    let watcher = create_watcher(tx)?;
-    let mut events = Vec::new();
+    let (watcher, mut rx) = UsbWatcherBuilder::new().build_with_channel()?;
+    let stop = watcher.stop_handle();
 
-    // Collect events in background
-    let collection_handle = tokio::spawn(async move {
-        let mut collected = Vec::new();
-        let timeout = tokio::time::sleep(duration);
-        tokio::pin!(timeout);
-
-        loop {
-            tokio::select! {
-                device_info = rx.recv() => {
-                    if let Some(device_info) = device_info {
-                        collected.push(device_info);
-                    } else {
-                        break; // Channel closed
-                    }
-                }
-                _ = &mut timeout => {
-                    break; // Duration elapsed
-                }
-            }
-        }
-        collected
+    // Stop the watcher once the duration has elapsed
+    let timer = tokio::spawn(async move {
+        tokio::time::sleep(duration).await;
+        stop.stop();
     });
 
-    // Start monitoring task (but we'll collect separately)
+    // The watcher is dropped when monitoring returns, which closes the channel
+    // once the events it picked up before stopping have been collected
     let monitoring_task = async move { watcher.start_monitoring().await };
-
-    // Wait for collection to complete
-    tokio::select! {
-        collected = collection_handle => {
-            events = collected.map_err(|e| e.to_string())?;
-        }
-        result = monitoring_task => {
-            if let Err(e) = result {
-                return Err(e.to_string());
-            }
+    let collection_task = async move {
+        let mut collected = Vec::new();
+        while let Some(device_info) = rx.recv().await {
+            collected.push(device_info);
         }
-    }
+        collected
+    };
+
+    let (result, events) = tokio::join!(monitoring_task, collection_task);
+    timer.abort();
+    result?;
 
     Ok(events)
 }
 
+/// List the USB devices connected right now.
+///
+/// This takes a one-shot snapshot without starting a monitor. Every returned
+/// device has [`DeviceEventType::Connected`] as its event type.
+///
+/// # Returns
+///
+/// Returns a [`Result`] containing the currently connected devices, or an
+/// error if they cannot be enumerated (e.g., on unsupported platforms).
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use usbwatch_rs::list_devices;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     for device in list_devices().await? {
+///         println!("{}", device.device_name);
+///     }
+///
+///     Ok(())
+/// }
+/// ```
+pub async fn list_devices() -> Result<Vec<UsbDeviceInfo>> {
+    // The watcher requires a sender, but listing never sends anything
+    let (tx, _rx) = tokio::sync::mpsc::channel(1);
+    let watcher = create_watcher(tx)?;
+    watcher.list_devices().await
+}
+
+/// Build the USB bus topology of the devices connected right now.
+///
+/// Use [`topology::render_tree`] to print the result like `lsusb -t`.
+///
+/// # Returns
+///
+/// Returns a [`Result`] containing the root nodes of the topology, or an error
+/// if the devices cannot be enumerated.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use usbwatch_rs::{device_tree, topology::render_tree};
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     print!("{}", render_tree(&device_tree().await?));
+///
+///     Ok(())
+/// }
+/// ```
+pub async fn device_tree() -> Result<Vec<UsbTreeNode>> {
+    // The watcher requires a sender, but building the tree never sends anything
+    let (tx, _rx) = tokio::sync::mpsc::channel(1);
+    let watcher = create_watcher(tx)?;
+    watcher.device_tree().await
+}
+
 /// Check if USB monitoring is supported on the current platform.
 ///
 /// # Returns
@@ -330,7 +412,7 @@ pub fn is_supported() -> bool {
 /// ```
 pub fn platform_info() -> &'static str {
     if cfg!(target_os = "linux") {
-        "Linux sysfs (/sys/bus/usb/devices)"
+        "Linux netlink uevents with sysfs (/sys/bus/usb/devices)"
     } else if cfg!(target_os = "windows") {
         "Windows Win32 Device Installation APIs"
     } else {
@@ -1,47 +1,65 @@
 //! Event logging and output formatting for USB device monitoring.
 //!
 //! Provides modern, coloured, and structured output for USB device events in both plain text and JSON formats.
-//! Supports logging to files and the console, with automatic colour detection for terminals.
+//! Events fan out to pluggable sinks — the console, log files or any async writer — each with its own format.
 //!
 //! ## Features
 //!
 //! - Coloured output using the `colored` crate
 //! - JSON and plain text output
-//! - File logging
+//! - File logging, with size- and time-based rotation
+//! - Custom destinations through the [`EventSink`] trait
 //! - Configurable via CLI options
 //! - Robust error handling
 
 use crate::device_info::UsbDeviceInfo;
-use colored::*;
-use std::fs::OpenOptions;
-use std::io::Write;
 use tokio::sync::mpsc;
-/// Configuration and state for logging USB device events.
+
+mod rotation;
+mod sink;
+
+pub use rotation::{parse_size, RotationPolicy, DEFAULT_KEEP};
+pub use sink::{EventFormat, EventSink, FileSink, SinkFuture, StdoutSink, WriterSink};
+
+/// Writes USB device events to any number of [`EventSink`]s.
+///
+/// Each sink has its own format, so human-readable text can go to the
+/// terminal while a file receives one JSON object per line.
+///
+/// # Examples
 ///
-/// The logger handles output formatting and can write to both console
-/// and log files simultaneously.
+/// ```no_run
+/// use usbwatch_rs::logger::{EventFormat, FileSink, Logger, StdoutSink};
+///
+/// let logger = Logger::default()
+///     .with_sink(StdoutSink::new(EventFormat::Text { colourful: true }))
+///     .with_sink(FileSink::open("usb-events.json", EventFormat::Json)?);
+/// # Ok::<(), usbwatch_rs::UsbWatchError>(())
+/// ```
+#[derive(Default)]
 pub struct Logger {
-    output_json: bool,
-    log_file: Option<std::fs::File>,
-    colorful: bool,
+    sinks: Vec<Box<dyn EventSink>>,
 }
 
 impl Logger {
-    /// Creates a new logger instance.
+    /// Creates a logger printing to the console and, optionally, appending
+    /// to a log file in the same format.
     ///
     /// # Arguments
     ///
     /// * `output_json` - Whether to format output as JSON
     /// * `log_file_path` - Optional path to a log file
-    /// * `colorful` - Whether to use coloured output (ignored for JSON mode)
+    /// * `colorful` - Whether to use coloured console output (ignored for
+    ///   JSON mode; the log file is never coloured)
     ///
     /// # Errors
     ///
-    /// Returns an error if the log file cannot be created or opened.
+    /// Returns [`UsbWatchError::PermissionDenied`](crate::UsbWatchError::PermissionDenied) or [`UsbWatchError::Io`](crate::UsbWatchError::Io) if
+    /// the log file cannot be created or opened.
     ///
     /// # Examples
     ///
-    /// ```
+    /// ```no_run
     /// use usbwatch_rs::logger::Logger;
     ///
     /// // Console-only logger with plain text and colours
@@ -49,36 +67,52 @@ impl Logger {
     ///
     /// // JSON logger with file output
     /// let logger = Logger::new(true, Some("usb-events.json"), true)?;
-    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// # Ok::<(), usbwatch_rs::UsbWatchError>(())
     /// ```
     pub fn new(
         output_json: bool,
         log_file_path: Option<&str>,
         colorful: bool,
-    ) -> Result<Self, Box<dyn std::error::Error>> {
-        let log_file = if let Some(path) = log_file_path {
-            Some(
-                OpenOptions::new()
-                    .create(true)
-                    .append(true)
-                    .open(path)
-                    .map_err(|e| format!("Failed to open log file '{path}': {e}"))?,
-            )
-        } else {
-            None
+    ) -> crate::Result<Self> {
+        let format = |colourful| {
+            if output_json {
+                EventFormat::Json
+            } else {
+                EventFormat::Text { colourful }
+            }
         };
+        let mut logger = Self::default().with_sink(StdoutSink::new(format(colorful)));
+        if let Some(path) = log_file_path {
+            logger = logger.with_sink(FileSink::open(path, format(false))?);
+        }
+        Ok(logger)
+    }
 
-        Ok(Self {
-            output_json,
-            log_file,
-            colorful,
-        })
+    /// Adds a sink that receives every event after the existing ones.
+    pub fn with_sink(mut self, sink: impl EventSink + 'static) -> Self {
+        self.sinks.push(Box::new(sink));
+        self
     }
 
-    /// Logs a USB device event to console and file (if configured).
+    /// Reopens the files of all sinks by name.
+    ///
+    /// Call this after an external tool such as logrotate has moved a log
+    /// file away, typically on `SIGHUP`, so that logging continues in a new
+    /// file at the configured path.
     ///
-    /// The output format depends on the `output_json` setting configured
-    /// during logger creation.
+    /// # Errors
+    ///
+    /// Returns the first error from a sink that could not reopen its file;
+    /// the other sinks are still reopened, and logging to the failed file is
+    /// retried on the next event.
+    pub fn reopen(&mut self) -> crate::Result<()> {
+        self.sinks
+            .iter_mut()
+            .map(|sink| sink.reopen())
+            .fold(Ok(()), Result::and)
+    }
+
+    /// Writes a USB device event to every sink.
     ///
     /// # Arguments
     ///
@@ -86,59 +120,24 @@ impl Logger {
     ///
     /// # Errors
     ///
-    /// Returns an error if JSON serialisation fails or file writing fails.
-    pub fn log_device_event(
-        &mut self,
-        device_info: &UsbDeviceInfo,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        if self.output_json {
-            let json = serde_json::to_string(device_info)?;
-            println!("{json}");
-            if let Some(file) = &mut self.log_file {
-                writeln!(file, "{json}")?;
-                file.flush()?;
-            }
-        } else {
-            let event_icon = match device_info.event_type {
-                crate::device_info::DeviceEventType::Connected => "🔌",
-                crate::device_info::DeviceEventType::Disconnected => "❌",
-            };
-            let styled_name = if self.colorful {
-                match device_info.event_type {
-                    crate::device_info::DeviceEventType::Connected => {
-                        device_info.device_name.green().bold()
-                    }
-                    crate::device_info::DeviceEventType::Disconnected => {
-                        device_info.device_name.red().bold()
-                    }
-                }
-            } else {
-                device_info.device_name.normal()
-            };
-            let output = format!(
-                "{} {} | VID: {} PID: {} | Serial: {} | Event: {:?} | {}",
-                event_icon,
-                styled_name,
-                device_info.vendor_id,
-                device_info.product_id,
-                device_info.serial_number.as_deref().unwrap_or("-"),
-                device_info.event_type,
-                device_info.timestamp
-            );
-            println!("{output}");
-            if let Some(file) = &mut self.log_file {
-                writeln!(file, "{output}")?;
-                file.flush()?;
-            }
+    /// Returns the first error from a sink, such as
+    /// [`UsbWatchError::Serialization`](crate::UsbWatchError::Serialization) if JSON serialisation fails
+    /// or an I/O error if writing a file fails. A failing sink does not keep
+    /// the event from the others.
+    pub async fn log_device_event(&mut self, device_info: &UsbDeviceInfo) -> crate::Result<()> {
+        let mut result = Ok(());
+        for sink in &mut self.sinks {
+            result = result.and(sink.write_event(device_info).await);
         }
-        Ok(())
+        result
     }
 }
 
 /// Async task that processes USB device events from a channel.
 ///
-/// This function runs indefinitely, receiving device events and logging
-/// them using the provided logger instance.
+/// This function receives device events and logs them using the provided
+/// logger instance until every sender has been dropped, so awaiting it after
+/// stopping the watcher guarantees all pending events have been written.
 ///
 /// # Arguments
 ///
@@ -146,7 +145,7 @@ impl Logger {
 /// * `logger` - Logger instance for formatting and outputting events
 pub async fn logger_task(mut rx: mpsc::Receiver<UsbDeviceInfo>, mut logger: Logger) {
     while let Some(device_info) = rx.recv().await {
-        if let Err(e) = logger.log_device_event(&device_info) {
+        if let Err(e) = logger.log_device_event(&device_info).await {
             eprintln!("Error logging device event: {e}");
         }
     }
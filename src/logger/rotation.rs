@@ -0,0 +1,272 @@
+//! Log file rotation and retention.
+
+use crate::error::UsbWatchError;
+use chrono::{DateTime, Local, NaiveDate};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+/// Number of rotated files kept by default.
+pub const DEFAULT_KEEP: usize = 5;
+
+/// When a [`FileSink`](super::FileSink) rotates its log file and what it keeps.
+///
+/// On rotation `usb.log` is renamed to `usb.log.1`, an existing `usb.log.1`
+/// to `usb.log.2` and so on, and files beyond the number to keep are
+/// deleted. Compressed files get a `.gz` suffix (`usb.log.1.gz`).
+///
+/// # Examples
+///
+/// ```
+/// use usbwatch_rs::logger::RotationPolicy;
+///
+/// // Rotate at 10 MiB or at midnight, keeping a week of compressed logs
+/// let policy = RotationPolicy::new()
+///     .max_size(10 * 1024 * 1024)
+///     .daily()
+///     .keep(7)
+///     .compress(true);
+/// ```
+#[derive(Debug, Clone)]
+pub struct RotationPolicy {
+    max_size: Option<u64>,
+    daily: bool,
+    keep: usize,
+    compress: bool,
+}
+
+impl Default for RotationPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RotationPolicy {
+    /// Creates a policy that never rotates and keeps [`DEFAULT_KEEP`] files.
+    pub fn new() -> Self {
+        Self {
+            max_size: None,
+            daily: false,
+            keep: DEFAULT_KEEP,
+            compress: false,
+        }
+    }
+
+    /// Rotates before a line would make the file larger than `bytes`.
+    ///
+    /// A single line longer than the limit is still written to an empty file.
+    pub fn max_size(mut self, bytes: u64) -> Self {
+        self.max_size = Some(bytes);
+        self
+    }
+
+    /// Rotates on the first write of each day, in local time.
+    pub fn daily(mut self) -> Self {
+        self.daily = true;
+        self
+    }
+
+    /// Sets how many rotated files are kept; 0 deletes the log on rotation.
+    pub fn keep(mut self, count: usize) -> Self {
+        self.keep = count;
+        self
+    }
+
+    /// Sets whether rotated files are compressed with gzip.
+    pub fn compress(mut self, compress: bool) -> Self {
+        self.compress = compress;
+        self
+    }
+}
+
+/// Parses a file size such as `10M`, `512KiB`, `1G` or `4096`.
+///
+/// Units are powers of 1024 and case-insensitive.
+///
+/// # Examples
+///
+/// ```
+/// use usbwatch_rs::logger::parse_size;
+///
+/// assert_eq!(parse_size("10M"), Some(10 * 1024 * 1024));
+/// assert_eq!(parse_size("512kb"), Some(512 * 1024));
+/// assert_eq!(parse_size("4096"), Some(4096));
+/// assert_eq!(parse_size("lots"), None);
+/// ```
+pub fn parse_size(value: &str) -> Option<u64> {
+    let value = value.trim();
+    let digits_end = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(digits_end);
+    let multiplier: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kb" | "kib" => 1 << 10,
+        "m" | "mb" | "mib" => 1 << 20,
+        "g" | "gb" | "gib" => 1 << 30,
+        _ => return None,
+    };
+    number.parse::<u64>().ok()?.checked_mul(multiplier)
+}
+
+/// A log file that rotates itself according to a [`RotationPolicy`].
+#[derive(Debug)]
+pub(super) struct LogFile {
+    path: PathBuf,
+    file: Option<File>,
+    size: u64,
+    /// Local date the file holds lines for: that of its last write when it
+    /// was opened, or of the last rotation. Daily rotation starts a new file
+    /// once the date changes.
+    day: NaiveDate,
+    policy: Option<RotationPolicy>,
+}
+
+impl LogFile {
+    /// Opens `path` for appending, creating it if needed.
+    pub(super) fn open(path: &Path) -> crate::Result<Self> {
+        let mut log_file = Self {
+            path: path.to_path_buf(),
+            file: None,
+            size: 0,
+            day: Local::now().date_naive(),
+            policy: None,
+        };
+        log_file.reopen()?;
+        Ok(log_file)
+    }
+
+    pub(super) fn set_policy(&mut self, policy: RotationPolicy) {
+        self.policy = Some(policy);
+    }
+
+    /// Closes the file and opens whatever is at its path now, e.g. after an
+    /// external tool rotated it.
+    pub(super) fn reopen(&mut self) -> crate::Result<()> {
+        self.file = None;
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| {
+                UsbWatchError::io(
+                    format!("Failed to open log file '{}'", self.path.display()),
+                    e,
+                )
+            })?;
+        let metadata = file.metadata().ok();
+        self.size = metadata.as_ref().map_or(0, |m| m.len());
+        // A file left over from an earlier day is rotated on the first write
+        self.day = metadata.and_then(|m| m.modified().ok()).map_or_else(
+            || Local::now().date_naive(),
+            |modified| DateTime::<Local>::from(modified).date_naive(),
+        );
+        self.file = Some(file);
+        Ok(())
+    }
+
+    /// Appends a line, rotating the file first if the policy calls for it.
+    pub(super) fn write_line(&mut self, line: &str) -> crate::Result<()> {
+        let length = line.len() as u64 + 1;
+        // A failed rotation is reported after the line has been written
+        let rotated = if self.rotation_due(length) {
+            self.rotate()
+        } else {
+            Ok(())
+        };
+        if self.file.is_none() {
+            // Opening the file failed after the last rotation; try again
+            self.reopen()?;
+        }
+        if let Some(file) = &mut self.file {
+            writeln!(file, "{line}")
+                .and_then(|()| file.flush())
+                .map_err(|e| UsbWatchError::io("Failed to write log file", e))?;
+            self.size += length;
+        }
+        rotated
+    }
+
+    fn rotation_due(&self, incoming: u64) -> bool {
+        let Some(policy) = &self.policy else {
+            return false;
+        };
+        let too_big = policy
+            .max_size
+            .is_some_and(|max| self.size > 0 && self.size + incoming > max);
+        let new_day = policy.daily && self.size > 0 && Local::now().date_naive() != self.day;
+        too_big || new_day
+    }
+
+    fn rotate(&mut self) -> crate::Result<()> {
+        let Some(policy) = self.policy.clone() else {
+            return Ok(());
+        };
+        // The file has to be closed before it can be renamed on Windows
+        self.file = None;
+        let rotated = rotate_files(&self.path, &policy).map_err(|e| {
+            UsbWatchError::io(
+                format!("Failed to rotate log file '{}'", self.path.display()),
+                e,
+            )
+        });
+        // Keep logging even if rotation failed
+        self.reopen()?;
+        self.day = Local::now().date_naive();
+        rotated
+    }
+}
+
+/// Moves `path` to `path.1`, shifting and pruning older rotated files.
+fn rotate_files(path: &Path, policy: &RotationPolicy) -> io::Result<()> {
+    if policy.keep == 0 {
+        return remove_if_exists(path);
+    }
+    for index in (1..=policy.keep).rev() {
+        for compressed in [false, true] {
+            let from = rotated_path(path, index, compressed);
+            if !from.exists() {
+                continue;
+            }
+            if index == policy.keep {
+                fs::remove_file(&from)?;
+            } else {
+                fs::rename(&from, rotated_path(path, index + 1, compressed))?;
+            }
+        }
+    }
+
+    let first = rotated_path(path, 1, false);
+    fs::rename(path, &first)?;
+    if policy.compress {
+        gzip(&first, &rotated_path(path, 1, true))?;
+        fs::remove_file(&first)?;
+    }
+    Ok(())
+}
+
+/// Returns the path of the `index`th rotated file, e.g. `usb.log.2.gz`.
+fn rotated_path(path: &Path, index: usize, compressed: bool) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{index}"));
+    if compressed {
+        name.push(".gz");
+    }
+    PathBuf::from(name)
+}
+
+fn gzip(from: &Path, to: &Path) -> io::Result<()> {
+    let mut input = BufReader::new(File::open(from)?);
+    let mut encoder = GzEncoder::new(File::create(to)?, Compression::default());
+    io::copy(&mut input, &mut encoder)?;
+    encoder.finish()?.sync_all()
+}
+
+fn remove_if_exists(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
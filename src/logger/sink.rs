@@ -0,0 +1,297 @@
+//! Destinations for logged events and the formats they are written in.
+
+use super::rotation::{LogFile, RotationPolicy};
+use crate::device_info::{BlockDevice, DeviceEventType, UsbDeviceInfo};
+use crate::error::UsbWatchError;
+use colored::*;
+use std::future::{self, Future};
+use std::path::Path;
+use std::pin::Pin;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+/// The boxed future returned by [`EventSink::write_event`].
+pub type SinkFuture<'a> = Pin<Box<dyn Future<Output = crate::Result<()>> + Send + 'a>>;
+
+/// A destination for the events written by [`Logger`](super::Logger).
+///
+/// The crate provides [`StdoutSink`], [`FileSink`] and [`WriterSink`]; implement
+/// this trait to send events anywhere else.
+///
+/// # Examples
+///
+/// ```
+/// use usbwatch_rs::logger::{EventSink, SinkFuture};
+/// use usbwatch_rs::{DeviceEventType, UsbDeviceInfo};
+///
+/// /// A sink that counts connect events.
+/// #[derive(Default)]
+/// struct ConnectCounter {
+///     connects: usize,
+/// }
+///
+/// impl EventSink for ConnectCounter {
+///     fn write_event<'a>(&'a mut self, event: &'a UsbDeviceInfo) -> SinkFuture<'a> {
+///         Box::pin(async move {
+///             if event.event_type == DeviceEventType::Connected {
+///                 self.connects += 1;
+///             }
+///             Ok(())
+///         })
+///     }
+/// }
+/// ```
+pub trait EventSink: Send {
+    /// Writes one event.
+    fn write_event<'a>(&'a mut self, event: &'a UsbDeviceInfo) -> SinkFuture<'a>;
+
+    /// Closes and reopens the underlying file, if there is one, e.g. after
+    /// an external tool rotated it.
+    ///
+    /// The default implementation does nothing.
+    fn reopen(&mut self) -> crate::Result<()> {
+        Ok(())
+    }
+}
+
+/// How a sink turns an event into a line of text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventFormat {
+    /// Human-readable text, coloured by event type if `colourful` is set
+    Text {
+        /// Whether to colour the device name
+        colourful: bool,
+    },
+    /// One JSON object per event, as read back by
+    /// [`read_recording`](crate::watcher::read_recording)
+    Json,
+}
+
+impl EventFormat {
+    /// Formats `device_info` as a line, without the trailing newline.
+    ///
+    /// Text output for [`Changed`](DeviceEventType::Changed) and
+    /// [`Flapping`](DeviceEventType::Flapping) events, and for connected
+    /// storage and network devices, spans several lines.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`UsbWatchError::Serialization`] if JSON serialisation fails.
+    pub fn format(&self, device_info: &UsbDeviceInfo) -> crate::Result<String> {
+        match *self {
+            EventFormat::Json => Ok(serde_json::to_string(device_info)?),
+            EventFormat::Text { colourful } => Ok(format_text(device_info, colourful)),
+        }
+    }
+}
+
+fn format_text(device_info: &UsbDeviceInfo, colourful: bool) -> String {
+    let event_icon = match device_info.event_type {
+        DeviceEventType::Connected => "🔌",
+        DeviceEventType::Disconnected => "❌",
+        DeviceEventType::Changed(_) => "🔄",
+        DeviceEventType::Flapping { .. } => "⚡",
+    };
+    let styled_name = if colourful {
+        match device_info.event_type {
+            DeviceEventType::Connected => device_info.device_name.green().bold(),
+            DeviceEventType::Disconnected => device_info.device_name.red().bold(),
+            DeviceEventType::Changed(_) => device_info.device_name.yellow().bold(),
+            DeviceEventType::Flapping { .. } => device_info.device_name.magenta().bold(),
+        }
+    } else {
+        device_info.device_name.normal()
+    };
+    // Names from the USB ID database follow the IDs, e.g. "VID: 0483 (STMicroelectronics)"
+    let with_name = |id: String, name: Option<&str>| match name {
+        Some(name) => format!("{id} ({name})"),
+        None => id,
+    };
+    let class_names = device_info.class_names();
+    // e.g. "USB 3.20, 480 Mbit/s, 896 mA, config 1 with 1 interface, removable | "
+    let link = match link_details(device_info) {
+        details if details.is_empty() => String::new(),
+        details => format!("{} | ", details.join(", ")),
+    };
+    let mut output = format!(
+        "{} {} | VID: {} PID: {} | Serial: {} | Class: {} | {}Event: {} | {}",
+        event_icon,
+        styled_name,
+        with_name(
+            device_info.vendor_id.to_string(),
+            device_info.vendor_name.as_deref()
+        ),
+        with_name(
+            device_info.product_id.to_string(),
+            device_info.product_name.as_deref()
+        ),
+        device_info.serial_number.as_deref().unwrap_or("-"),
+        if class_names.is_empty() {
+            "-".to_string()
+        } else {
+            class_names.join(", ")
+        },
+        link,
+        device_info.event_type,
+        device_info.timestamp
+    );
+    // e.g. "/dev/sdb1 (vfat "DATA") on /media/data" for each volume of a flash
+    // drive, or "usb0 (02:11:22:33:44:55)" for a tethered phone
+    if device_info.event_type == DeviceEventType::Connected {
+        for volume in device_info.storage.iter().flat_map(BlockDevice::volumes) {
+            output.push_str(&format!("\n    {volume}"));
+        }
+        for interface in &device_info.network_interfaces {
+            output.push_str(&format!("\n    {interface}"));
+        }
+    }
+    if let DeviceEventType::Changed(changes) = &device_info.event_type {
+        for change in changes {
+            output.push_str(&format!("\n    {change}"));
+        }
+    }
+    if let DeviceEventType::Flapping { count } = device_info.event_type {
+        output.push_str(&format!("\n    {count} connects and disconnects"));
+    }
+    output
+}
+
+/// Describes the link, power draw and configuration of a device, leaving
+/// out what the platform didn't report.
+fn link_details(device_info: &UsbDeviceInfo) -> Vec<String> {
+    let configuration =
+        device_info
+            .configuration
+            .map(|configuration| match device_info.num_interfaces {
+                Some(1) => format!("config {configuration} with 1 interface"),
+                Some(count) => format!("config {configuration} with {count} interfaces"),
+                None => format!("config {configuration}"),
+            });
+    [
+        device_info
+            .usb_version
+            .map(|version| format!("USB {version}")),
+        device_info.speed.map(|speed| speed.to_string()),
+        device_info.max_power_ma.map(|power| format!("{power} mA")),
+        configuration,
+        device_info
+            .max_child
+            .filter(|&ports| ports > 0)
+            .map(|ports| format!("{ports} ports")),
+        device_info
+            .removable
+            .map(|removable| if removable { "removable" } else { "fixed" }.to_string()),
+    ]
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+/// Prints events to standard output.
+#[derive(Debug, Clone)]
+pub struct StdoutSink {
+    format: EventFormat,
+}
+
+impl StdoutSink {
+    /// Creates a sink printing events in `format`.
+    pub fn new(format: EventFormat) -> Self {
+        Self { format }
+    }
+}
+
+impl EventSink for StdoutSink {
+    fn write_event<'a>(&'a mut self, event: &'a UsbDeviceInfo) -> SinkFuture<'a> {
+        let result = self.format.format(event).map(|line| println!("{line}"));
+        Box::pin(future::ready(result))
+    }
+}
+
+/// Appends events to a log file, optionally rotating it.
+#[derive(Debug)]
+pub struct FileSink {
+    file: LogFile,
+    format: EventFormat,
+}
+
+impl FileSink {
+    /// Opens `path` for appending, creating it if needed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`UsbWatchError::PermissionDenied`] or [`UsbWatchError::Io`] if
+    /// the file cannot be created or opened.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use usbwatch_rs::logger::{EventFormat, FileSink, RotationPolicy};
+    ///
+    /// let sink = FileSink::open("usb-events.json", EventFormat::Json)?
+    ///     .with_rotation(RotationPolicy::new().daily().keep(7).compress(true));
+    /// # Ok::<(), usbwatch_rs::UsbWatchError>(())
+    /// ```
+    pub fn open(path: impl AsRef<Path>, format: EventFormat) -> crate::Result<Self> {
+        Ok(Self {
+            file: LogFile::open(path.as_ref())?,
+            format,
+        })
+    }
+
+    /// Rotates the file according to `policy`.
+    pub fn with_rotation(mut self, policy: RotationPolicy) -> Self {
+        self.file.set_policy(policy);
+        self
+    }
+}
+
+impl EventSink for FileSink {
+    fn write_event<'a>(&'a mut self, event: &'a UsbDeviceInfo) -> SinkFuture<'a> {
+        let result = self
+            .format
+            .format(event)
+            .and_then(|line| self.file.write_line(&line));
+        Box::pin(future::ready(result))
+    }
+
+    fn reopen(&mut self) -> crate::Result<()> {
+        self.file.reopen()
+    }
+}
+
+/// Writes events to any [`AsyncWrite`], such as a socket or a pipe to
+/// another process, one line per event.
+///
+/// # Examples
+///
+/// ```
+/// use usbwatch_rs::logger::{EventFormat, Logger, WriterSink};
+///
+/// let logger = Logger::default().with_sink(WriterSink::new(tokio::io::stderr(), EventFormat::Json));
+/// ```
+#[derive(Debug)]
+pub struct WriterSink<W> {
+    writer: W,
+    format: EventFormat,
+}
+
+impl<W: AsyncWrite + Unpin + Send> WriterSink<W> {
+    /// Creates a sink writing events to `writer` in `format`.
+    pub fn new(writer: W, format: EventFormat) -> Self {
+        Self { writer, format }
+    }
+}
+
+impl<W: AsyncWrite + Unpin + Send> EventSink for WriterSink<W> {
+    fn write_event<'a>(&'a mut self, event: &'a UsbDeviceInfo) -> SinkFuture<'a> {
+        Box::pin(async move {
+            let mut line = self.format.format(event)?;
+            line.push('\n');
+            let failed = |e| UsbWatchError::io("Failed to write device event", e);
+            self.writer
+                .write_all(line.as_bytes())
+                .await
+                .map_err(failed)?;
+            self.writer.flush().await.map_err(failed)
+        })
+    }
+}
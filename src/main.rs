@@ -4,25 +4,75 @@
 //!
 //! ## Subcommands
 //! - `monitor` (default): Monitor USB device events in real-time
+//! - `list`: List the USB devices connected right now
+//! - `tree`: Show the USB bus topology
+//! - `record <FILE>`: Monitor and save the events to FILE as JSON lines
+//! - `replay <FILE> [--speed 2x]`: Re-emit recorded events with their original timing
+//! - `config check`: Validate the configuration file
 //! - `install`: Install usbwatch to system PATH
 //! - `uninstall`: Uninstall usbwatch from system PATH
 //!
 //! ## Options
-//! - `--json`: Output events in JSON format
+//! - `--config <PATH>`: Read settings from this TOML file instead of
+//!   `$XDG_CONFIG_HOME/usbwatch/config.toml`; options on the command line
+//!   override the file, and `--no-json`, `--no-logfile-json`,
+//!   `--no-rotate-daily` and `--no-rotate-compress` turn off switches the file
+//!   turns on
+//! - `--json`: Output events (or the device list) in JSON format
 //! - `--logfile <PATH>`: Log events to the specified file
+//! - `--logfile-json`: Write the log file as JSON lines even when the console
+//!   shows text
+//! - `--rotate-size <SIZE>`, `--rotate-daily`: Rotate the log file by size or
+//!   every day, keeping `--rotate-keep <N>` (default 5) old files, gzipped with
+//!   `--rotate-compress`; SIGHUP reopens the log file
+//! - `--usb-ids <PATH>`: Name devices from this `usb.ids` file instead of the
+//!   system's (`/usr/share/hwdata/usb.ids`, ...)
+//! - `--vid`, `--pid`, `--class`, `--name-regex`: Only report matching devices
+//!   (each may be repeated; monitor, list, record and replay only)
+//! - `--exclude <KEY=VALUE>`: Never report devices matching the rule
+//! - `--debounce <MS>`: Drop disconnect/reconnect pairs shorter than MS milliseconds
+//! - `--flap-threshold <N>`: Report a device toggling more than N times within
+//!   `--flap-window <SECS>` (default 10) as flapping; like `--debounce`, for
+//!   monitor, record and replay only
+//! - `--on-connect <CMD>`, `--on-disconnect <CMD>`: Run a shell command for each
+//!   connect or disconnect, with the device in `USBWATCH_*` variables and as
+//!   JSON on stdin
+//! - `--hook-concurrency <N>` (default 4), `--hook-timeout <SECS>` (default 30):
+//!   Limit how many hook commands run at once and for how long; hook options
+//!   apply to monitor, record and replay only
+//!
+//! ## Exit Codes
+//! - `0`: Success
+//! - `1`: Any other error
+//! - `2`: Invalid arguments, options or configuration file
+//! - `3`: sysfs USB devices directory not found
+//! - `4`: Permission denied
+//! - `5`: Not supported on this platform
+//! - `6`: I/O error
+//! - `7`: JSON serialisation failed
+//! - `8`: Event channel closed
+//! - `9`: Platform API error
+//! - `10`: Watcher task failed
+//! - `11`: Invalid event in a recording
 //!
 //! For installation and troubleshooting, see INSTALL.md.
-mod device_info;
-mod logger;
-mod watcher;
-
-use clap::{Parser, Subcommand};
-use logger::{logger_task, Logger};
+use clap::{Args, Parser, Subcommand};
 use std::env;
-use std::fs;
-use std::path::Path;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Duration;
 use tokio::sync::mpsc;
-use watcher::UsbWatcher;
+use tokio::task::JoinHandle;
+use usbwatch_rs::config::{Config, FilterConfig, HooksConfig, OutputConfig, WatcherSettings};
+use usbwatch_rs::logger::{parse_size, EventFormat, FileSink, RotationPolicy, StdoutSink};
+use usbwatch_rs::topology::render_tree;
+use usbwatch_rs::usb_ids::UsbIds;
+use usbwatch_rs::watcher::{open_recording, DEFAULT_CHANNEL_CAPACITY};
+use usbwatch_rs::{
+    DeviceFilter, EventHooks, Logger, Policy, ReplayBackend, Result, UsbDeviceInfo, UsbWatchError,
+    UsbWatcher, UsbWatcherBuilder,
+};
 
 #[derive(Parser)]
 #[command(name = "usbwatch")]
@@ -35,85 +85,768 @@ struct Cli {
     #[command(subcommand)]
     command: Option<Commands>,
 
-    /// Output events in JSON format (monitor mode only)
-    #[arg(long, global = true)]
+    #[command(flatten)]
+    output: OutputArgs,
+
+    /// Read settings from this TOML file instead of $XDG_CONFIG_HOME/usbwatch/config.toml
+    #[arg(long, value_name = "PATH", global = true)]
+    config: Option<PathBuf>,
+
+    /// Name devices from this usb.ids file instead of the system's
+    #[arg(long, value_name = "PATH", global = true)]
+    usb_ids: Option<PathBuf>,
+
+    /// Options given before the subcommand, or for the default monitor command
+    #[command(flatten)]
+    watch: WatchArgs,
+}
+
+/// Options of the commands that watch devices: monitor, record and replay
+#[derive(Args, Default)]
+struct WatchArgs {
+    #[command(flatten)]
+    filter: FilterArgs,
+
+    #[command(flatten)]
+    debounce: DebounceArgs,
+
+    #[command(flatten)]
+    hooks: HookArgs,
+}
+
+impl WatchArgs {
+    /// Rejects options given before a subcommand that doesn't watch devices.
+    fn reject_for(&self, command: &str) -> Result<()> {
+        self.filter.reject_for(command)?;
+        self.debounce.reject_for(command)?;
+        self.hooks.reject_for(command)
+    }
+
+    /// Adds the options given before the subcommand, e.g. `usbwatch --vid 0483 record`.
+    fn merged(self, earlier: WatchArgs) -> Self {
+        Self {
+            filter: self.filter.merged(earlier.filter),
+            debounce: self.debounce.merged(earlier.debounce),
+            hooks: self.hooks.merged(earlier.hooks),
+        }
+    }
+}
+
+/// Output format and log file options
+#[derive(Args)]
+struct OutputArgs {
+    /// Output in JSON format (monitor, list and tree)
+    #[arg(long, global = true, overrides_with = "no_json")]
     json: bool,
 
-    /// Log events to file (monitor mode only)
+    /// Output plain text, even if the configuration file sets json
+    #[arg(long, global = true)]
+    no_json: bool,
+
+    /// Log events to file (monitor, record and replay)
     #[arg(long, value_name = "PATH", global = true)]
     logfile: Option<String>,
+
+    /// Write the log file as JSON lines, even when the console shows text
+    #[arg(long, global = true, overrides_with = "no_logfile_json")]
+    logfile_json: bool,
+
+    /// Write the log file as text, even when the console shows JSON
+    #[arg(long, global = true)]
+    no_logfile_json: bool,
+
+    /// Rotate the log file before it grows beyond SIZE (e.g. 10M, 512K)
+    #[arg(long, value_name = "SIZE", value_parser = parse_size_arg, global = true)]
+    rotate_size: Option<u64>,
+
+    /// Rotate the log file every day at midnight
+    #[arg(long, global = true, overrides_with = "no_rotate_daily")]
+    rotate_daily: bool,
+
+    /// Don't rotate the log file daily, even if the configuration file says to
+    #[arg(long, global = true)]
+    no_rotate_daily: bool,
+
+    /// Number of rotated log files to keep [default: 5]
+    #[arg(long, value_name = "N", global = true)]
+    rotate_keep: Option<usize>,
+
+    /// Compress rotated log files with gzip
+    #[arg(long, global = true, overrides_with = "no_rotate_compress")]
+    rotate_compress: bool,
+
+    /// Don't compress rotated log files, even if the configuration file says to
+    #[arg(long, global = true)]
+    no_rotate_compress: bool,
+}
+
+impl OutputArgs {
+    /// Collects the output options given on the command line.
+    fn output_config(&self) -> OutputConfig {
+        OutputConfig {
+            json: switch(self.json, self.no_json),
+            logfile: self.logfile.clone(),
+            logfile_json: switch(self.logfile_json, self.no_logfile_json),
+            rotate_size: self.rotate_size,
+            rotate_daily: switch(self.rotate_daily, self.no_rotate_daily),
+            rotate_keep: self.rotate_keep,
+            rotate_compress: switch(self.rotate_compress, self.no_rotate_compress),
+        }
+    }
+}
+
+/// Returns the value of a `--flag`/`--no-flag` pair, or `None` if neither was
+/// given so that the configuration file decides.
+fn switch(on: bool, off: bool) -> Option<bool> {
+    match (on, off) {
+        (true, _) => Some(true),
+        (_, true) => Some(false),
+        _ => None,
+    }
+}
+
+/// Device filter options of the commands that report devices
+#[derive(Args, Default)]
+struct FilterArgs {
+    /// Only show devices with this vendor ID, in hex (repeatable)
+    #[arg(long, value_name = "VID")]
+    vid: Vec<String>,
+
+    /// Only show devices with this product ID, in hex (repeatable)
+    #[arg(long, value_name = "PID")]
+    pid: Vec<String>,
+
+    /// Only show devices of this class, by name (e.g. hid, mass-storage, net) or hex code (repeatable)
+    #[arg(long, value_name = "CLASS")]
+    class: Vec<String>,
+
+    /// Only show devices whose name matches this regular expression (repeatable)
+    #[arg(long, value_name = "REGEX")]
+    name_regex: Vec<String>,
+
+    /// Hide devices matching KEY=VALUE, where KEY is vid, pid, serial, name, class or event (repeatable)
+    #[arg(long, value_name = "KEY=VALUE")]
+    exclude: Vec<String>,
+}
+
+impl FilterArgs {
+    /// Adds the options given before the subcommand, e.g. `usbwatch --vid 0483 list`.
+    fn merged(mut self, earlier: FilterArgs) -> Self {
+        self.vid.extend(earlier.vid);
+        self.pid.extend(earlier.pid);
+        self.class.extend(earlier.class);
+        self.name_regex.extend(earlier.name_regex);
+        self.exclude.extend(earlier.exclude);
+        self
+    }
+
+    /// Rejects filter options given before a subcommand that doesn't filter.
+    fn reject_for(&self, command: &str) -> Result<()> {
+        let given = [
+            &self.vid,
+            &self.pid,
+            &self.class,
+            &self.name_regex,
+            &self.exclude,
+        ];
+        if given.iter().any(|values| !values.is_empty()) {
+            return Err(UsbWatchError::InvalidConfig(format!(
+                "Filter options (--vid, --pid, --class, --name-regex, --exclude) don't apply to {command}"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Collects the filter options given on the command line.
+    fn filter_config(&self) -> FilterConfig {
+        FilterConfig {
+            vid: self.vid.clone(),
+            pid: self.pid.clone(),
+            class: self.class.clone(),
+            name_regex: self.name_regex.clone(),
+            include: Vec::new(),
+            exclude: self.exclude.clone(),
+        }
+    }
+}
+
+/// Debouncing and flap detection options of the commands that watch devices
+#[derive(Args, Default)]
+struct DebounceArgs {
+    /// Hold connect and disconnect events for MS milliseconds and drop pairs that cancel out
+    #[arg(long, value_name = "MS")]
+    debounce: Option<u64>,
+
+    /// Report a device that connects or disconnects more than N times within the flap window as flapping
+    #[arg(long, value_name = "N")]
+    flap_threshold: Option<usize>,
+
+    /// Length of the flap detection window in seconds [default: 10]
+    #[arg(long, value_name = "SECS")]
+    flap_window: Option<u64>,
+}
+
+impl DebounceArgs {
+    /// Adds the options given before the subcommand, e.g. `usbwatch --debounce 300 record`.
+    fn merged(self, earlier: DebounceArgs) -> Self {
+        Self {
+            debounce: self.debounce.or(earlier.debounce),
+            flap_threshold: self.flap_threshold.or(earlier.flap_threshold),
+            flap_window: self.flap_window.or(earlier.flap_window),
+        }
+    }
+
+    /// Rejects debounce options given before a subcommand that doesn't watch devices.
+    fn reject_for(&self, command: &str) -> Result<()> {
+        if self.debounce.is_some() || self.flap_threshold.is_some() || self.flap_window.is_some() {
+            return Err(UsbWatchError::InvalidConfig(format!(
+                "Debounce options (--debounce, --flap-threshold, --flap-window) don't apply to {command}"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Collects the debouncing options given on the command line.
+    fn watcher_settings(&self) -> WatcherSettings {
+        WatcherSettings {
+            debounce: self.debounce.map(Duration::from_millis),
+            flap_threshold: self.flap_threshold,
+            flap_window: self.flap_window.map(Duration::from_secs),
+            ..WatcherSettings::default()
+        }
+    }
+}
+
+/// Commands run on device events, for monitor, record and replay
+#[derive(Args, Default)]
+#[command(next_display_order = 100)]
+struct HookArgs {
+    /// Run this shell command whenever a device connects
+    #[arg(long, value_name = "CMD")]
+    on_connect: Option<String>,
+
+    /// Run this shell command whenever a device disconnects
+    #[arg(long, value_name = "CMD")]
+    on_disconnect: Option<String>,
+
+    /// Maximum number of hook commands running at the same time [default: 4]
+    #[arg(long, value_name = "N",
+          value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    hook_concurrency: Option<usize>,
+
+    /// Kill hook commands still running after SECS seconds [default: 30]
+    #[arg(long, value_name = "SECS")]
+    hook_timeout: Option<u64>,
+}
+
+impl HookArgs {
+    /// Adds the options given before the subcommand, e.g. `usbwatch --on-connect CMD record`.
+    fn merged(self, earlier: HookArgs) -> Self {
+        Self {
+            on_connect: self.on_connect.or(earlier.on_connect),
+            on_disconnect: self.on_disconnect.or(earlier.on_disconnect),
+            hook_concurrency: self.hook_concurrency.or(earlier.hook_concurrency),
+            hook_timeout: self.hook_timeout.or(earlier.hook_timeout),
+        }
+    }
+
+    /// Rejects hook options given before a subcommand that doesn't run hooks.
+    fn reject_for(&self, command: &str) -> Result<()> {
+        if self.on_connect.is_some()
+            || self.on_disconnect.is_some()
+            || self.hook_concurrency.is_some()
+            || self.hook_timeout.is_some()
+        {
+            return Err(UsbWatchError::InvalidConfig(format!(
+                "Hook options (--on-connect, --on-disconnect, --hook-concurrency, --hook-timeout) don't apply to {command}"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Collects the hook options given on the command line.
+    fn hooks_config(&self) -> HooksConfig {
+        HooksConfig {
+            on_connect: self.on_connect.clone(),
+            on_disconnect: self.on_disconnect.clone(),
+            concurrency: self.hook_concurrency,
+            timeout: self.hook_timeout.map(Duration::from_secs),
+        }
+    }
 }
 
 #[derive(Subcommand)]
 enum Commands {
     /// Monitor USB device events (default)
-    Monitor,
+    Monitor {
+        #[command(flatten)]
+        watch: WatchArgs,
+    },
+    /// List the USB devices connected right now
+    List {
+        #[command(flatten)]
+        filter: FilterArgs,
+    },
+    /// Show the USB hub and port topology
+    Tree,
+    /// Monitor USB device events and save them as JSON lines for replay
+    Record {
+        /// File to write the events to (overwritten if it exists)
+        #[arg(value_name = "FILE")]
+        file: String,
+
+        #[command(flatten)]
+        watch: WatchArgs,
+    },
+    /// Replay events saved by record (or monitor --json) with their original timing
+    Replay {
+        /// Recording to replay
+        #[arg(value_name = "FILE")]
+        file: String,
+
+        /// Replay speed, e.g. 2x for twice as fast or 0.5x for half speed
+        #[arg(long, value_name = "FACTOR", default_value = "1x", value_parser = parse_speed)]
+        speed: f64,
+
+        #[command(flatten)]
+        watch: WatchArgs,
+    },
+    /// Work with the configuration file
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
     /// Install usbwatch to system PATH
     Install,
     /// Uninstall usbwatch from system PATH
     Uninstall,
 }
 
+#[derive(Subcommand)]
+enum ConfigCommand {
+    /// Check the configuration file and report any errors with their line numbers
+    Check,
+}
+
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+async fn main() -> ExitCode {
     let cli = Cli::parse();
+    match run(cli).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {e}");
+            ExitCode::from(exit_code(&e))
+        }
+    }
+}
 
-    match cli.command.unwrap_or(Commands::Monitor) {
-        Commands::Monitor => run_monitor(cli.json, cli.logfile).await,
-        Commands::Install => install_binary(),
-        Commands::Uninstall => uninstall_binary(),
+/// Maps each kind of failure to its own exit code, so scripts can tell them apart.
+fn exit_code(error: &UsbWatchError) -> u8 {
+    match error {
+        UsbWatchError::InvalidConfig(_) | UsbWatchError::InvalidConfigFile { .. } => 2,
+        UsbWatchError::SysfsUnavailable { .. } => 3,
+        UsbWatchError::PermissionDenied { .. } => 4,
+        UsbWatchError::Unsupported(_) => 5,
+        UsbWatchError::Io { .. } => 6,
+        UsbWatchError::Serialization(_) => 7,
+        UsbWatchError::ChannelClosed => 8,
+        UsbWatchError::Platform(_) => 9,
+        UsbWatchError::TaskFailed(_) => 10,
+        UsbWatchError::InvalidRecording { .. } => 11,
+        _ => 1,
     }
 }
 
-async fn run_monitor(
+async fn run(mut cli: Cli) -> Result<()> {
+    let command = cli.command.take().unwrap_or(Commands::Monitor {
+        watch: WatchArgs::default(),
+    });
+    // Options given before the subcommand, e.g. `usbwatch --vid 0483 list`
+    let earlier = std::mem::take(&mut cli.watch);
+    match command {
+        Commands::Monitor { watch } => {
+            run_monitor(Settings::resolve(&cli, &watch.merged(earlier))?).await
+        }
+        Commands::Record { file, watch } => {
+            run_record(&file, Settings::resolve(&cli, &watch.merged(earlier))?).await
+        }
+        Commands::Replay { file, speed, watch } => {
+            run_replay(
+                &file,
+                speed,
+                Settings::resolve(&cli, &watch.merged(earlier))?,
+            )
+            .await
+        }
+        Commands::List { filter } => {
+            earlier.debounce.reject_for("list")?;
+            earlier.hooks.reject_for("list")?;
+            let watch = WatchArgs {
+                filter: filter.merged(earlier.filter),
+                ..WatchArgs::default()
+            };
+            let settings = Settings::resolve(&cli, &watch)?;
+            run_list(settings.json, settings.builder, &settings.policy).await
+        }
+        Commands::Tree => {
+            earlier.reject_for("tree")?;
+            let settings = Settings::resolve(&cli, &WatchArgs::default())?;
+            run_tree(settings.json, settings.builder).await
+        }
+        Commands::Config {
+            command: ConfigCommand::Check,
+        } => {
+            earlier.reject_for("config check")?;
+            check_config(cli.config)
+        }
+        Commands::Install => {
+            earlier.reject_for("install")?;
+            install_binary()
+        }
+        Commands::Uninstall => {
+            earlier.reject_for("uninstall")?;
+            uninstall_binary()
+        }
+    }
+}
+
+/// Options from the configuration file, overridden by those on the command line.
+struct Settings {
     json: bool,
     logfile: Option<String>,
-) -> Result<(), Box<dyn std::error::Error>> {
-    println!(
+    logfile_json: bool,
+    rotation: Option<RotationPolicy>,
+    builder: UsbWatcherBuilder,
+    hooks: EventHooks,
+    policy: Policy,
+}
+
+impl Settings {
+    fn resolve(cli: &Cli, watch: &WatchArgs) -> Result<Self> {
+        let config = load_config(cli.config.clone())?;
+        let output = config.output.overridden_by(cli.output.output_config());
+        let filter = config
+            .filter
+            .overridden_by(watch.filter.filter_config())
+            .device_filter()?;
+        let watcher = config.watcher.overridden_by(WatcherSettings {
+            usb_ids: cli.usb_ids.clone(),
+            ..watch.debounce.watcher_settings()
+        });
+        let mut builder = watcher.apply(UsbWatcherBuilder::new().filter(filter));
+        builder = match &watcher.usb_ids {
+            Some(path) => builder.usb_ids(Some(UsbIds::load(path)?)),
+            None => builder.system_usb_ids(),
+        };
+        let hooks = config
+            .hooks
+            .overridden_by(watch.hooks.hooks_config())
+            .event_hooks();
+
+        let json = output.json.unwrap_or(false);
+        Ok(Self {
+            json,
+            logfile_json: output.logfile_json.unwrap_or(json),
+            rotation: output.rotation_policy(),
+            logfile: output.logfile,
+            builder,
+            hooks,
+            policy: config.policy,
+        })
+    }
+
+    /// Creates the logger for the console and the log file.
+    fn logger(&self) -> Result<Logger> {
+        let logger = self.console_logger();
+        let Some(path) = &self.logfile else {
+            return Ok(logger);
+        };
+        let format = if self.logfile_json {
+            EventFormat::Json
+        } else {
+            EventFormat::Text { colourful: false }
+        };
+        let mut sink = FileSink::open(path, format)?;
+        if let Some(policy) = &self.rotation {
+            sink = sink.with_rotation(policy.clone());
+        }
+        Ok(logger.with_sink(sink))
+    }
+
+    /// Creates a logger printing events to the console only.
+    fn console_logger(&self) -> Logger {
+        let format = if self.json {
+            EventFormat::Json
+        } else {
+            // Detect if terminal supports colour
+            EventFormat::Text {
+                colourful: atty::is(atty::Stream::Stdout),
+            }
+        };
+        Logger::default().with_sink(StdoutSink::new(format))
+    }
+}
+
+/// Loads the configuration file given with --config, or the default one if it exists.
+fn load_config(path: Option<PathBuf>) -> Result<Config> {
+    match path.or_else(|| Config::default_path().filter(|path| path.exists())) {
+        Some(path) => Config::load(path),
+        None => Ok(Config::default()),
+    }
+}
+
+fn check_config(path: Option<PathBuf>) -> Result<()> {
+    let path = path.or_else(Config::default_path).ok_or_else(|| {
+        UsbWatchError::InvalidConfig("No configuration file given and no default location".into())
+    })?;
+    if !path.exists() {
+        return Err(UsbWatchError::InvalidConfig(format!(
+            "No configuration file at {}",
+            path.display()
+        )));
+    }
+    Config::load(&path)?;
+    println!("✅ {} is valid", path.display());
+    Ok(())
+}
+
+async fn run_monitor(settings: Settings) -> Result<()> {
+    // Status messages go to stderr, so that stdout carries only the events
+    eprintln!(
         "🔌 USB Device Monitor - usbwatch v{}",
         env!("CARGO_PKG_VERSION")
     );
-    println!("Press Ctrl+C to stop monitoring...");
-
-    // Create channel for device events
-    let (tx, rx) = mpsc::channel(100);
+    eprintln!("Press Ctrl+C to stop monitoring...");
 
-    // Detect if terminal supports colour
-    let colourful = atty::is(atty::Stream::Stdout);
     // Initialise logger
-    let logger = Logger::new(json, logfile.as_deref(), colourful)?;
+    let logger = settings.logger()?;
+    // Create USB watcher and the channel for its device events
+    let (watcher, rx) = settings.builder.build_with_channel()?;
+
+    watch_until_interrupted(watcher, rx, logger, settings.hooks, settings.policy).await
+}
 
-    // Start logger task
-    let logger_handle = tokio::spawn(logger_task(rx, logger));
+async fn run_record(file: &str, settings: Settings) -> Result<()> {
+    eprintln!("⏺️  Recording USB device events to {file}");
+    eprintln!("Press Ctrl+C to stop recording...");
 
-    // Create and start USB watcher
-    let watcher = UsbWatcher::new(tx)?;
+    // Start from an empty recording; the file sink then appends one event per line
+    File::create(file)
+        .map_err(|e| UsbWatchError::io(format!("Failed to create recording '{file}'"), e))?;
+    let logger = settings
+        .logger()?
+        .with_sink(FileSink::open(file, EventFormat::Json)?);
+
+    let (watcher, rx) = settings.builder.build_with_channel()?;
+
+    watch_until_interrupted(watcher, rx, logger, settings.hooks, settings.policy).await
+}
+
+async fn run_replay(file: &str, speed: f64, settings: Settings) -> Result<()> {
+    let events = open_recording(file)?;
+    eprintln!(
+        "▶️  Replaying {} events from {file} at {speed}x",
+        events.len()
+    );
+    eprintln!("Press Ctrl+C to stop replaying...");
+
+    let logger = settings.logger()?;
+    let (tx, rx) = mpsc::channel(DEFAULT_CHANNEL_CAPACITY);
+    let watcher = settings.builder.build_with_backend(tx, |tx, config| {
+        ReplayBackend::with_config(tx, config, events).speed(speed)
+    })?;
+
+    watch_until_interrupted(watcher, rx, logger, settings.hooks, settings.policy).await
+}
+
+/// Runs `watcher`, logging its events and running their hooks, until it
+/// finishes or Ctrl+C is pressed.
+async fn watch_until_interrupted(
+    watcher: UsbWatcher,
+    rx: mpsc::Receiver<UsbDeviceInfo>,
+    logger: Logger,
+    hooks: EventHooks,
+    policy: Policy,
+) -> Result<()> {
+    // Start the task handling events
+    let events_handle = tokio::spawn(handle_events(rx, logger, hooks, policy));
 
     // Handle Ctrl+C gracefully
-    let watcher_handle = tokio::spawn(async move {
-        if let Err(e) = watcher.start_monitoring().await {
-            eprintln!("USB monitoring error: {e}");
-        }
-    });
+    let stop = watcher.stop_handle();
+    let mut watcher_handle = tokio::spawn(async move { watcher.start_monitoring().await });
 
     // Wait for Ctrl+C
-    tokio::select! {
+    let result = tokio::select! {
         _ = tokio::signal::ctrl_c() => {
-            println!("\n📡 Shutting down USB monitor...");
+            eprintln!("\n📡 Shutting down USB monitor...");
+            stop.stop();
+            (&mut watcher_handle).await
         }
-        _ = watcher_handle => {
-            println!("📡 USB monitoring stopped");
+        result = &mut watcher_handle => {
+            eprintln!("📡 USB monitoring stopped");
+            result
         }
+    };
+
+    // The watcher has dropped its sender, so the remaining events are handled
+    // and the task exits once the channel is empty and the hooks are done
+    let _ = events_handle.await;
+
+    result.map_err(|e| UsbWatchError::TaskFailed(e.to_string()))?
+}
+
+async fn run_list(json: bool, builder: UsbWatcherBuilder, policy: &Policy) -> Result<()> {
+    let (watcher, _rx) = builder.build_with_channel()?;
+    let mut devices = watcher.list_devices().await?;
+    devices.retain(|device| policy.action_for(device).reports());
+    devices.sort_by(|a, b| {
+        (&a.vendor_id, &a.product_id, &a.device_name).cmp(&(
+            &b.vendor_id,
+            &b.product_id,
+            &b.device_name,
+        ))
+    });
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&devices)?);
+    } else if devices.is_empty() {
+        println!("No USB devices found");
+    } else {
+        print_device_table(&devices);
     }
 
-    // Cleanup
-    logger_handle.abort();
+    Ok(())
+}
+
+async fn run_tree(json: bool, builder: UsbWatcherBuilder) -> Result<()> {
+    // The tree shows every device, so that hubs stay connected to their children
+    let (watcher, _rx) = builder
+        .filter(DeviceFilter::default())
+        .build_with_channel()?;
+    let roots = watcher.device_tree().await?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&roots)?);
+    } else if roots.is_empty() {
+        println!("No USB devices found");
+    } else {
+        print!("{}", render_tree(&roots));
+    }
 
     Ok(())
 }
 
-fn install_binary() -> Result<(), Box<dyn std::error::Error>> {
-    let current_exe = env::current_exe()?;
+/// Logs each event and starts its hook as the policy allows, then waits for
+/// the hooks still running once the channel closes. The log file is reopened
+/// on SIGHUP.
+async fn handle_events(
+    mut rx: mpsc::Receiver<UsbDeviceInfo>,
+    mut logger: Logger,
+    hooks: EventHooks,
+    policy: Policy,
+) {
+    let mut hangups = Hangups::new();
+    let mut running: Vec<JoinHandle<()>> = Vec::new();
+    loop {
+        let device_info = tokio::select! {
+            device_info = rx.recv() => match device_info {
+                Some(device_info) => device_info,
+                None => break,
+            },
+            () = hangups.recv() => {
+                if let Err(e) = logger.reopen() {
+                    eprintln!("Error reopening log file: {e}");
+                }
+                continue;
+            }
+        };
+        let action = policy.action_for(&device_info);
+        if !action.reports() {
+            continue;
+        }
+        if let Err(e) = logger.log_device_event(&device_info).await {
+            eprintln!("Error logging device event: {e}");
+        }
+        running.retain(|hook| !hook.is_finished());
+        if action.runs_hooks() {
+            running.extend(hooks.spawn(&device_info));
+        }
+    }
+    for hook in running {
+        let _ = hook.await;
+    }
+}
+
+/// SIGHUP notifications, the conventional request to reopen log files.
+/// Never fires on platforms without signals.
+struct Hangups {
+    #[cfg(unix)]
+    signal: Option<tokio::signal::unix::Signal>,
+}
+
+impl Hangups {
+    fn new() -> Self {
+        Self {
+            #[cfg(unix)]
+            signal: tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup()).ok(),
+        }
+    }
+
+    async fn recv(&mut self) {
+        #[cfg(unix)]
+        if let Some(signal) = &mut self.signal {
+            if signal.recv().await.is_some() {
+                return;
+            }
+        }
+        std::future::pending().await
+    }
+}
+
+/// Parses a log file size such as `10M`.
+fn parse_size_arg(value: &str) -> std::result::Result<u64, String> {
+    parse_size(value)
+        .filter(|&bytes| bytes > 0)
+        .ok_or_else(|| format!("'{value}' is not a size such as 10M or 512K"))
+}
+
+/// Parses a replay speed such as `2x`, `0.5x` or `3`.
+fn parse_speed(value: &str) -> std::result::Result<f64, String> {
+    let factor = value.strip_suffix('x').unwrap_or(value);
+    match factor.parse::<f64>() {
+        Ok(speed) if speed > 0.0 && speed.is_finite() => Ok(speed),
+        _ => Err(format!(
+            "'{value}' is not a positive speed such as 2x or 0.5x"
+        )),
+    }
+}
+
+fn print_device_table(devices: &[UsbDeviceInfo]) {
+    let name_width = devices
+        .iter()
+        .map(|d| d.device_name.chars().count())
+        .chain(std::iter::once("NAME".len()))
+        .max()
+        .unwrap_or_default();
+
+    println!(
+        "{:<4}  {:<4}  {:<name_width$}  SERIAL",
+        "VID", "PID", "NAME"
+    );
+    for device in devices {
+        println!(
+            "{:<4}  {:<4}  {:<name_width$}  {}",
+            device.vendor_id,
+            device.product_id,
+            device.device_name,
+            device.serial_number.as_deref().unwrap_or("-")
+        );
+    }
+}
+
+fn install_binary() -> Result<()> {
+    let current_exe = env::current_exe()
+        .map_err(|e| UsbWatchError::io("Failed to locate the running executable", e))?;
     let exe_name = if cfg!(windows) {
         "usbwatch.exe"
     } else {
@@ -135,19 +868,34 @@ fn install_binary() -> Result<(), Box<dyn std::error::Error>> {
 
     // Create target directory if it doesn't exist (Windows only)
     if cfg!(windows) {
-        fs::create_dir_all(&target_dir)?;
+        fs::create_dir_all(&target_dir).map_err(|e| {
+            UsbWatchError::io(format!("Failed to create {}", target_dir.display()), e)
+        })?;
     }
 
     // Copy the binary
-    fs::copy(&current_exe, &target_path)?;
+    fs::copy(&current_exe, &target_path).map_err(|e| {
+        UsbWatchError::io(
+            format!("Failed to copy usbwatch to {}", target_path.display()),
+            e,
+        )
+    })?;
 
     // Set executable permissions on Unix
     #[cfg(unix)]
     {
         use std::os::unix::fs::PermissionsExt;
-        let mut perms = fs::metadata(&target_path)?.permissions();
-        perms.set_mode(0o755);
-        fs::set_permissions(&target_path, perms)?;
+        let make_executable = || {
+            let mut perms = fs::metadata(&target_path)?.permissions();
+            perms.set_mode(0o755);
+            fs::set_permissions(&target_path, perms)
+        };
+        make_executable().map_err(|e| {
+            UsbWatchError::io(
+                format!("Failed to make {} executable", target_path.display()),
+                e,
+            )
+        })?;
     }
 
     println!(
@@ -167,7 +915,7 @@ fn install_binary() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
-fn uninstall_binary() -> Result<(), Box<dyn std::error::Error>> {
+fn uninstall_binary() -> Result<()> {
     let exe_name = if cfg!(windows) {
         "usbwatch.exe"
     } else {
@@ -186,15 +934,21 @@ fn uninstall_binary() -> Result<(), Box<dyn std::error::Error>> {
     let target_path = target_dir.join(exe_name);
 
     if target_path.exists() {
-        fs::remove_file(&target_path)?;
+        fs::remove_file(&target_path).map_err(|e| {
+            UsbWatchError::io(format!("Failed to remove {}", target_path.display()), e)
+        })?;
         println!(
             "✅ Successfully uninstalled usbwatch from {}",
             target_path.display()
         );
 
         // Remove directory on Windows if empty
-        if cfg!(windows) && target_dir.read_dir()?.next().is_none() {
-            fs::remove_dir(&target_dir)?;
+        let remaining =
+            |e| UsbWatchError::io(format!("Failed to read {}", target_dir.display()), e);
+        if cfg!(windows) && target_dir.read_dir().map_err(remaining)?.next().is_none() {
+            fs::remove_dir(&target_dir).map_err(|e| {
+                UsbWatchError::io(format!("Failed to remove {}", target_dir.display()), e)
+            })?;
             println!("🗑️  Removed empty directory {}", target_dir.display());
         }
     } else {
@@ -0,0 +1,121 @@
+//! Policy rules deciding what happens to each reported event.
+//!
+//! A [`Policy`] is an ordered list of rules, each pairing a
+//! [`PolicyAction`] with [`FilterRule`]s. The first rule whose filter rules
+//! all match an event decides its action; events no rule matches are
+//! allowed. Unlike a [`DeviceFilter`](crate::DeviceFilter), which only says
+//! whether a device is reported, a policy can also report a device without
+//! running its hooks, and its rules are checked in the order they are given.
+
+use crate::device_info::UsbDeviceInfo;
+use crate::error::UsbWatchError;
+use crate::filter::FilterRule;
+use std::str::FromStr;
+
+/// What happens to an event matched by a policy rule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum PolicyAction {
+    /// Report the event and run its hooks
+    #[default]
+    Allow,
+    /// Report the event without running its hooks
+    NoHooks,
+    /// Neither report the event nor run its hooks
+    Deny,
+}
+
+impl PolicyAction {
+    /// Returns true if the event is reported.
+    pub fn reports(self) -> bool {
+        self != PolicyAction::Deny
+    }
+
+    /// Returns true if the event's hooks run.
+    pub fn runs_hooks(self) -> bool {
+        self == PolicyAction::Allow
+    }
+}
+
+/// Parses `allow`, `no-hooks` or `deny`.
+impl FromStr for PolicyAction {
+    type Err = UsbWatchError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "allow" => Ok(PolicyAction::Allow),
+            "no-hooks" => Ok(PolicyAction::NoHooks),
+            "deny" => Ok(PolicyAction::Deny),
+            _ => Err(UsbWatchError::InvalidConfig(format!(
+                "Unknown policy action '{s}': expected allow, no-hooks or deny"
+            ))),
+        }
+    }
+}
+
+/// Ordered rules deciding whether events are reported and run hooks.
+///
+/// # Examples
+///
+/// ```
+/// use usbwatch_rs::device_info::{DeviceEventType, ProductId, UsbDeviceInfo, VendorId};
+/// use usbwatch_rs::policy::{Policy, PolicyAction};
+///
+/// // Flash ST-Links, report other STMicroelectronics devices, and hide the rest
+/// let policy = Policy::new()
+///     .rule(PolicyAction::Allow, ["vid=0483".parse()?, "pid=3748".parse()?])
+///     .rule(PolicyAction::NoHooks, ["vid=0483".parse()?])
+///     .rule(PolicyAction::Deny, []);
+///
+/// let mut device = UsbDeviceInfo::new(
+///     "ST-Link V2".to_string(),
+///     VendorId(0x0483),
+///     ProductId(0x3748),
+///     None,
+///     DeviceEventType::Connected,
+/// );
+/// assert_eq!(policy.action_for(&device), PolicyAction::Allow);
+///
+/// device.product_id = ProductId(0x5740);
+/// assert_eq!(policy.action_for(&device), PolicyAction::NoHooks);
+///
+/// device.vendor_id = VendorId(0x1366);
+/// assert_eq!(policy.action_for(&device), PolicyAction::Deny);
+/// # Ok::<(), usbwatch_rs::UsbWatchError>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Policy {
+    rules: Vec<(PolicyAction, Vec<FilterRule>)>,
+}
+
+impl Policy {
+    /// Creates a policy that allows every event.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a rule applying `action` to events matching all of `rules`,
+    /// checked after the rules added before it. A rule with no filter rules
+    /// matches every event.
+    pub fn rule(
+        mut self,
+        action: PolicyAction,
+        rules: impl IntoIterator<Item = FilterRule>,
+    ) -> Self {
+        self.rules.push((action, rules.into_iter().collect()));
+        self
+    }
+
+    /// Returns true if the policy has no rules and so allows every event.
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Returns the action of the first rule matching `device`, or
+    /// [`PolicyAction::Allow`] if none does.
+    pub fn action_for(&self, device: &UsbDeviceInfo) -> PolicyAction {
+        self.rules
+            .iter()
+            .find(|(_, rules)| rules.iter().all(|rule| rule.matches(device)))
+            .map_or(PolicyAction::Allow, |(action, _)| *action)
+    }
+}
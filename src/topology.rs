@@ -0,0 +1,186 @@
+//! USB bus topology.
+//!
+//! Arranges devices into the hub hierarchy described by the bus number, port
+//! path and parent recorded on each [`UsbDeviceInfo`], and renders it as a
+//! tree similar to `lsusb -t`.
+
+use crate::device_info::UsbDeviceInfo;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+/// A device in the USB bus topology together with the devices plugged into it.
+#[derive(Debug, Clone, Serialize)]
+pub struct UsbTreeNode {
+    /// The device at this node
+    pub device: UsbDeviceInfo,
+    /// Kernel drivers bound to the device's interfaces, sorted and deduplicated
+    pub drivers: Vec<String>,
+    /// Devices plugged into this one, ordered by port
+    pub children: Vec<UsbTreeNode>,
+}
+
+impl UsbTreeNode {
+    /// Creates a leaf node.
+    ///
+    /// The drivers are taken from the device's interfaces.
+    pub fn new(device: UsbDeviceInfo) -> Self {
+        let mut drivers: Vec<String> = device
+            .interfaces
+            .iter()
+            .filter_map(|interface| interface.driver.clone())
+            .collect();
+        drivers.sort();
+        drivers.dedup();
+
+        Self {
+            device,
+            drivers,
+            children: Vec::new(),
+        }
+    }
+
+    /// Returns the name other devices use to refer to this one as their parent.
+    ///
+    /// This is the Linux sysfs naming scheme: `usb<bus>` for root hubs and
+    /// `<bus>-<port path>` for everything else (e.g., "1-1.4").
+    pub fn name(&self) -> Option<String> {
+        let bus = self.device.bus_number?;
+        Some(match &self.device.port_path {
+            Some(port_path) => format!("{bus}-{port_path}"),
+            None => format!("usb{bus}"),
+        })
+    }
+
+    /// Returns the port on the parent hub this device is plugged into.
+    pub fn port(&self) -> Option<&str> {
+        self.device
+            .port_path
+            .as_deref()
+            .and_then(|path| path.rsplit('.').next())
+    }
+
+    /// Sort key ordering nodes by bus, then numerically by port path.
+    fn sort_key(&self) -> (u8, Vec<u32>) {
+        let ports = self
+            .device
+            .port_path
+            .as_deref()
+            .map(|path| path.split('.').filter_map(|p| p.parse().ok()).collect())
+            .unwrap_or_default();
+        (self.device.bus_number.unwrap_or(u8::MAX), ports)
+    }
+}
+
+/// Arranges a flat list of nodes into trees using each device's parent.
+///
+/// Devices whose parent is unknown or not in the list become roots, so
+/// platforms without topology information produce a flat list.
+///
+/// # Examples
+///
+/// ```
+/// use usbwatch_rs::device_info::{DeviceEventType, ProductId, UsbDeviceInfo, VendorId};
+/// use usbwatch_rs::topology::{build_tree, UsbTreeNode};
+///
+/// let mut hub = UsbDeviceInfo::new(
+///     "Root hub".to_string(),
+///     VendorId(0x1d6b),
+///     ProductId(0x0002),
+///     None,
+///     DeviceEventType::Connected,
+/// );
+/// hub.bus_number = Some(1);
+///
+/// let mut drive = UsbDeviceInfo::new(
+///     "Flash drive".to_string(),
+///     VendorId(0x0781),
+///     ProductId(0x5583),
+///     None,
+///     DeviceEventType::Connected,
+/// );
+/// drive.bus_number = Some(1);
+/// drive.port_path = Some("2".to_string());
+/// drive.parent = Some("usb1".to_string());
+///
+/// let roots = build_tree(vec![UsbTreeNode::new(drive), UsbTreeNode::new(hub)]);
+/// assert_eq!(roots.len(), 1);
+/// assert_eq!(roots[0].children[0].port(), Some("2"));
+/// ```
+pub fn build_tree(nodes: Vec<UsbTreeNode>) -> Vec<UsbTreeNode> {
+    let names: HashSet<String> = nodes.iter().filter_map(UsbTreeNode::name).collect();
+
+    let mut roots = Vec::new();
+    let mut children_of: HashMap<String, Vec<UsbTreeNode>> = HashMap::new();
+    for node in nodes {
+        match node.device.parent.clone().filter(|p| names.contains(p)) {
+            Some(parent) => children_of.entry(parent).or_default().push(node),
+            None => roots.push(node),
+        }
+    }
+
+    for root in &mut roots {
+        attach_children(root, &mut children_of);
+    }
+    roots.sort_by_key(UsbTreeNode::sort_key);
+    roots
+}
+
+fn attach_children(node: &mut UsbTreeNode, children_of: &mut HashMap<String, Vec<UsbTreeNode>>) {
+    let Some(mut children) = node.name().and_then(|name| children_of.remove(&name)) else {
+        return;
+    };
+    for child in &mut children {
+        attach_children(child, children_of);
+    }
+    children.sort_by_key(UsbTreeNode::sort_key);
+    node.children = children;
+}
+
+/// Renders trees built by [`build_tree`] in a style similar to `lsusb -t`.
+///
+/// ```text
+/// Bus 001: Linux Foundation 2.0 root hub [1d6b:0002] Driver=hub, 480M
+///     |__ Port 1: Generic USB2.0 Hub [05e3:0608] Driver=hub, 480M
+///         |__ Port 4: SanDisk Ultra [0781:5583] Driver=usb-storage, 480M
+/// ```
+pub fn render_tree(roots: &[UsbTreeNode]) -> String {
+    let mut output = String::new();
+    for root in roots {
+        render_node(root, 0, &mut output);
+    }
+    output
+}
+
+fn render_node(node: &UsbTreeNode, depth: usize, output: &mut String) {
+    let device = &node.device;
+    let position = match (depth, device.bus_number, node.port()) {
+        (0, Some(bus), None) => format!("Bus {bus:03}"),
+        (_, _, Some(port)) => format!("Port {port}"),
+        _ => "Device".to_string(),
+    };
+
+    let mut line = format!(
+        "{position}: {} [{}:{}]",
+        device.device_name, device.vendor_id, device.product_id
+    );
+    let details: Vec<String> = (!node.drivers.is_empty())
+        .then(|| format!("Driver={}", node.drivers.join(",")))
+        .into_iter()
+        .chain(device.speed.map(|speed| format!("{}M", speed.mbps())))
+        .collect();
+    if !details.is_empty() {
+        line.push(' ');
+        line.push_str(&details.join(", "));
+    }
+
+    if depth > 0 {
+        output.push_str(&"    ".repeat(depth));
+        output.push_str("|__ ");
+    }
+    output.push_str(&line);
+    output.push('\n');
+
+    for child in &node.children {
+        render_node(child, depth + 1, output);
+    }
+}
@@ -0,0 +1,280 @@
+//! Vendor, product and class names from the USB ID database.
+//!
+//! The database is the `usb.ids` file maintained at <http://www.linux-usb.org/usb-ids.html>
+//! and installed by most Linux distributions (e.g., `/usr/share/hwdata/usb.ids`).
+//! It names devices whose descriptors carry no strings, which the watchers
+//! otherwise report as "Unknown Device".
+//!
+//! Watchers only use a database they are given, through
+//! [`UsbWatcherBuilder::usb_ids`](crate::UsbWatcherBuilder::usb_ids) or
+//! [`UsbWatcherBuilder::system_usb_ids`](crate::UsbWatcherBuilder::system_usb_ids),
+//! so library output doesn't depend on the host by default.
+//!
+//! With the `bundled-usb-ids` cargo feature, a snapshot of the file is
+//! compiled in and used when no system copy is found.
+
+use crate::device_info::{UsbClass, UsbDeviceInfo};
+use crate::error::UsbWatchError;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, OnceLock};
+
+/// Locations searched for the database by [`UsbIds::system`], in order.
+pub const DEFAULT_PATHS: &[&str] = &[
+    "/usr/share/hwdata/usb.ids",
+    "/usr/share/misc/usb.ids",
+    "/usr/share/usb.ids",
+    "/var/lib/usbutils/usb.ids",
+];
+
+/// Name the watchers give devices without a product string.
+const UNKNOWN_DEVICE: &str = "Unknown Device";
+
+/// A parsed USB ID database.
+///
+/// # Examples
+///
+/// ```
+/// use usbwatch_rs::usb_ids::UsbIds;
+///
+/// let ids = UsbIds::parse(
+///     "0483  STMicroelectronics\n\
+///      \t3748  ST-LINK/V2\n\
+///      C 08  Mass Storage\n\
+///      \t06  SCSI\n\
+///      \t\t50  Bulk-Only\n",
+/// );
+/// assert_eq!(ids.vendor(0x0483), Some("STMicroelectronics"));
+/// assert_eq!(ids.product(0x0483, 0x3748), Some("ST-LINK/V2"));
+/// assert_eq!(ids.protocol(0x08, 0x06, 0x50), Some("Bulk-Only"));
+/// ```
+#[derive(Default)]
+pub struct UsbIds {
+    vendors: HashMap<u16, String>,
+    products: HashMap<(u16, u16), String>,
+    classes: HashMap<u8, String>,
+    subclasses: HashMap<(u8, u8), String>,
+    protocols: HashMap<(u8, u8, u8), String>,
+}
+
+impl std::fmt::Debug for UsbIds {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("UsbIds")
+            .field("vendors", &self.vendors.len())
+            .field("products", &self.products.len())
+            .field("classes", &self.classes.len())
+            .finish()
+    }
+}
+
+/// The section of the file the parser is in.
+enum Section {
+    Vendor(u16),
+    Class(u8),
+    /// Sections this module doesn't use, such as HID usages and languages
+    Other,
+}
+
+impl UsbIds {
+    /// Parses the contents of a `usb.ids` file.
+    ///
+    /// Lines that don't fit the format are skipped, so a newer file with
+    /// additional sections still loads.
+    pub fn parse(text: &str) -> Self {
+        let mut ids = Self::default();
+        let mut section = Section::Other;
+        let mut subclass = None;
+        for line in text.lines() {
+            if line.starts_with('#') || line.trim().is_empty() {
+                continue;
+            }
+            let entry = line.trim_start_matches('\t');
+            match (line.len() - entry.len(), &section) {
+                (0, _) => {
+                    section = if let Some(entry) = entry.strip_prefix("C ") {
+                        parse_entry(entry, 2).map_or(Section::Other, |(class, name)| {
+                            ids.classes.insert(class as u8, name.to_string());
+                            Section::Class(class as u8)
+                        })
+                    } else {
+                        parse_entry(entry, 4).map_or(Section::Other, |(vendor, name)| {
+                            ids.vendors.insert(vendor, name.to_string());
+                            Section::Vendor(vendor)
+                        })
+                    };
+                    subclass = None;
+                }
+                (1, Section::Vendor(vendor)) => {
+                    if let Some((product, name)) = parse_entry(entry, 4) {
+                        ids.products.insert((*vendor, product), name.to_string());
+                    }
+                }
+                (1, Section::Class(class)) => {
+                    subclass = parse_entry(entry, 2).map(|(code, name)| {
+                        ids.subclasses
+                            .insert((*class, code as u8), name.to_string());
+                        code as u8
+                    });
+                }
+                (2, Section::Class(class)) => {
+                    if let (Some(subclass), Some((protocol, name))) =
+                        (subclass, parse_entry(entry, 2))
+                    {
+                        ids.protocols
+                            .insert((*class, subclass, protocol as u8), name.to_string());
+                    }
+                }
+                // Interfaces of a product and entries of other sections
+                _ => {}
+            }
+        }
+        ids
+    }
+
+    /// Loads a `usb.ids` file.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`UsbWatchError::PermissionDenied`] or [`UsbWatchError::Io`] if
+    /// the file cannot be read.
+    pub fn load(path: impl AsRef<Path>) -> crate::Result<Self> {
+        let path = path.as_ref();
+        // The file is mostly ASCII but not guaranteed to be valid UTF-8
+        let bytes = std::fs::read(path).map_err(|e| {
+            UsbWatchError::io(
+                format!("Failed to read USB ID database '{}'", path.display()),
+                e,
+            )
+        })?;
+        Ok(Self::parse(&String::from_utf8_lossy(&bytes)))
+    }
+
+    /// Returns the snapshot of the database compiled into the crate.
+    #[cfg(feature = "bundled-usb-ids")]
+    pub fn bundled() -> Self {
+        Self::parse(include_str!("../data/usb.ids"))
+    }
+
+    /// Returns the system database from the first of [`DEFAULT_PATHS`] that
+    /// can be read, or the bundled snapshot if there is none and the
+    /// `bundled-usb-ids` feature is enabled.
+    ///
+    /// The database is loaded once and shared by later calls.
+    pub fn system() -> Option<Arc<UsbIds>> {
+        static SYSTEM: OnceLock<Option<Arc<UsbIds>>> = OnceLock::new();
+        SYSTEM
+            .get_or_init(|| {
+                let found = DEFAULT_PATHS.iter().find_map(|path| Self::load(path).ok());
+                #[cfg(feature = "bundled-usb-ids")]
+                let found = found.or_else(|| Some(Self::bundled()));
+                found.map(Arc::new)
+            })
+            .clone()
+    }
+
+    /// Returns the name of a vendor.
+    pub fn vendor(&self, vendor_id: u16) -> Option<&str> {
+        self.vendors.get(&vendor_id).map(String::as_str)
+    }
+
+    /// Returns the name of a vendor's product.
+    pub fn product(&self, vendor_id: u16, product_id: u16) -> Option<&str> {
+        self.products
+            .get(&(vendor_id, product_id))
+            .map(String::as_str)
+    }
+
+    /// Returns the name of a base class.
+    pub fn class(&self, class: u8) -> Option<&str> {
+        self.classes.get(&class).map(String::as_str)
+    }
+
+    /// Returns the name of a subclass.
+    pub fn subclass(&self, class: u8, subclass: u8) -> Option<&str> {
+        self.subclasses.get(&(class, subclass)).map(String::as_str)
+    }
+
+    /// Returns the name of a protocol.
+    pub fn protocol(&self, class: u8, subclass: u8, protocol: u8) -> Option<&str> {
+        self.protocols
+            .get(&(class, subclass, protocol))
+            .map(String::as_str)
+    }
+
+    /// Describes a class triple with the names of its known parts, e.g.
+    /// "Mass Storage / SCSI / Bulk-Only".
+    pub fn class_name(&self, class: &UsbClass) -> Option<String> {
+        let names: Vec<&str> = [
+            self.class(class.class),
+            self.subclass(class.class, class.subclass),
+            self.protocol(class.class, class.subclass, class.protocol),
+        ]
+        .into_iter()
+        .map_while(|name| name)
+        .collect();
+        (!names.is_empty()).then(|| names.join(" / "))
+    }
+
+    /// Fills in the vendor, product and class names of `device`.
+    ///
+    /// A device reported as "Unknown Device" is also renamed after its vendor
+    /// and product. Names already set are kept.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use usbwatch_rs::usb_ids::UsbIds;
+    /// use usbwatch_rs::{DeviceEventType, ProductId, UsbDeviceInfo, VendorId};
+    ///
+    /// let ids = UsbIds::parse("046d  Logitech, Inc.\n\tc52b  Unifying Receiver\n");
+    /// let mut device = UsbDeviceInfo::new(
+    ///     "Unknown Device".to_string(),
+    ///     VendorId(0x046d),
+    ///     ProductId(0xc52b),
+    ///     None,
+    ///     DeviceEventType::Connected,
+    /// );
+    /// ids.annotate(&mut device);
+    ///
+    /// assert_eq!(device.vendor_name.as_deref(), Some("Logitech, Inc."));
+    /// assert_eq!(device.device_name, "Logitech, Inc. Unifying Receiver");
+    /// ```
+    pub fn annotate(&self, device: &mut UsbDeviceInfo) {
+        let (vendor_id, product_id) = (device.vendor_id.0, device.product_id.0);
+        if device.vendor_name.is_none() {
+            device.vendor_name = self.vendor(vendor_id).map(str::to_string);
+        }
+        if device.product_name.is_none() {
+            device.product_name = self.product(vendor_id, product_id).map(str::to_string);
+        }
+        if device.class_name.is_none() {
+            device.class_name = device
+                .device_class
+                .as_ref()
+                // Class 0x00 only says to look at the interfaces
+                .filter(|class| class.class != 0x00)
+                .and_then(|class| self.class_name(class));
+        }
+
+        if device.device_name == UNKNOWN_DEVICE {
+            let name = match (&device.vendor_name, &device.product_name) {
+                (Some(vendor), Some(product)) => Some(format!("{vendor} {product}")),
+                (Some(vendor), None) => Some(format!("{vendor} device")),
+                (None, _) => None,
+            };
+            if let Some(name) = name {
+                device.device_name = name;
+            }
+        }
+    }
+}
+
+/// Splits a `<hex id>  <name>` entry whose id has `digits` hex digits.
+fn parse_entry(entry: &str, digits: usize) -> Option<(u16, &str)> {
+    let (id, name) = entry.split_once("  ")?;
+    if id.len() != digits {
+        return None;
+    }
+    let id = u16::from_str_radix(id, 16).ok()?;
+    Some((id, name.trim()))
+}
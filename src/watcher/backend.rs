@@ -0,0 +1,79 @@
+//! Extension point for sources of USB devices and events.
+
+use super::StopHandle;
+use crate::device_info::UsbDeviceInfo;
+use crate::topology::{build_tree, UsbTreeNode};
+use std::future::Future;
+use std::pin::Pin;
+
+/// Boxed future returned by [`UsbBackend`] methods.
+pub type BackendFuture<'a, T> = Pin<Box<dyn Future<Output = crate::Result<T>> + Send + 'a>>;
+
+/// A source of USB devices and device events.
+///
+/// The Linux, Windows and macOS watchers implement this trait, and
+/// [`UsbWatcher::with_backend`](super::UsbWatcher::with_backend) wraps any
+/// other implementation, such as [`MockBackend`](super::MockBackend) or a
+/// backend for a platform the crate doesn't support. Like the built-in
+/// watchers, a backend is created with the channel sender it publishes
+/// events to.
+///
+/// # Examples
+///
+/// ```
+/// use tokio::sync::mpsc;
+/// use usbwatch_rs::watcher::{BackendFuture, UsbBackend};
+/// use usbwatch_rs::{StopHandle, UsbDeviceInfo, UsbWatcher};
+///
+/// /// A backend that never sees any devices.
+/// struct EmptyBackend {
+///     stop: StopHandle,
+/// }
+///
+/// impl UsbBackend for EmptyBackend {
+///     fn enumerate(&self) -> BackendFuture<'_, Vec<UsbDeviceInfo>> {
+///         Box::pin(async { Ok(Vec::new()) })
+///     }
+///
+///     fn watch(&self) -> BackendFuture<'_, ()> {
+///         Box::pin(async move {
+///             self.stop.stopped().await;
+///             Ok(())
+///         })
+///     }
+///
+///     fn stop_handle(&self) -> StopHandle {
+///         self.stop.clone()
+///     }
+/// }
+///
+/// let watcher = UsbWatcher::with_backend(EmptyBackend { stop: StopHandle::new() });
+/// ```
+pub trait UsbBackend: Send + Sync {
+    /// Enumerates the devices connected right now.
+    ///
+    /// Every returned device should have
+    /// [`DeviceEventType::Connected`](crate::DeviceEventType::Connected) as its
+    /// event type. Nothing is sent through the channel.
+    fn enumerate(&self) -> BackendFuture<'_, Vec<UsbDeviceInfo>>;
+
+    /// Publishes device events through the backend's channel until the handle
+    /// returned by [`stop_handle`](Self::stop_handle) is stopped.
+    fn watch(&self) -> BackendFuture<'_, ()>;
+
+    /// Returns a handle that makes [`watch`](Self::watch) return.
+    fn stop_handle(&self) -> StopHandle;
+
+    /// Builds the bus topology of the devices connected right now.
+    ///
+    /// The default implementation nests the devices from
+    /// [`enumerate`](Self::enumerate) by their [`parent`](UsbDeviceInfo::parent).
+    fn device_tree(&self) -> BackendFuture<'_, Vec<UsbTreeNode>> {
+        Box::pin(async move {
+            let devices = self.enumerate().await?;
+            Ok(build_tree(
+                devices.into_iter().map(UsbTreeNode::new).collect(),
+            ))
+        })
+    }
+}
@@ -0,0 +1,416 @@
+//! Configuration and builder for [`UsbWatcher`].
+//!
+//! The builder collects polling, startup and platform options into a
+//! [`WatcherConfig`] that is handed to the platform-specific watcher.
+
+use super::{DeviceEventStream, FlapDetection, UsbBackend, UsbWatcher};
+use crate::device_info::UsbDeviceInfo;
+use crate::error::UsbWatchError;
+use crate::filter::DeviceFilter;
+use crate::usb_ids::UsbIds;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// Default capacity of the event channel created by [`UsbWatcherBuilder::build_with_channel`].
+pub const DEFAULT_CHANNEL_CAPACITY: usize = 100;
+
+/// Options shared by all platform-specific watchers.
+///
+/// Backends that receive events from the operating system (such as the Linux
+/// uevent listener) ignore the polling options.
+#[derive(Debug, Clone)]
+pub struct WatcherConfig {
+    /// Shortest interval between scans, used right after device activity and
+    /// at startup (default 500 ms)
+    pub min_poll_interval: Duration,
+    /// Longest interval between scans when no devices change (default 5 s)
+    pub max_poll_interval: Duration,
+    /// Ceiling for the exponential backoff applied after failed scans (default 10 s)
+    pub error_backoff_max: Duration,
+    /// Whether to emit `Connected` events for devices present at startup
+    pub emit_initial: bool,
+    /// Directory treated as the sysfs mount point (Linux only)
+    pub sysfs_root: PathBuf,
+    /// Mount table used to find where storage devices are mounted (Linux only)
+    pub mountinfo_path: PathBuf,
+    /// Directory of the udev database, which holds filesystem labels (Linux only)
+    pub udev_data_dir: PathBuf,
+    /// Which devices and events are reported
+    pub filter: DeviceFilter,
+    /// How long connect and disconnect events are held back so that short
+    /// reconnects can be dropped; `None` reports them immediately
+    pub debounce_window: Option<Duration>,
+    /// Settings for collapsing flapping devices into a single event
+    pub flap_detection: Option<FlapDetection>,
+    /// Database used to name devices; `None` reports names as the devices give them
+    pub usb_ids: Option<Arc<UsbIds>>,
+}
+
+impl Default for WatcherConfig {
+    fn default() -> Self {
+        Self {
+            min_poll_interval: Duration::from_millis(500),
+            max_poll_interval: Duration::from_secs(5),
+            error_backoff_max: Duration::from_secs(10),
+            emit_initial: true,
+            sysfs_root: PathBuf::from("/sys"),
+            mountinfo_path: PathBuf::from("/proc/self/mountinfo"),
+            udev_data_dir: PathBuf::from("/run/udev/data"),
+            filter: DeviceFilter::default(),
+            debounce_window: None,
+            flap_detection: None,
+            usb_ids: None,
+        }
+    }
+}
+
+impl WatcherConfig {
+    /// Checks that the options are consistent.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`UsbWatchError::InvalidConfig`] if an interval is zero, the
+    /// minimum poll interval exceeds the maximum, or the flap detection
+    /// settings are empty.
+    pub fn validate(&self) -> crate::Result<()> {
+        if self.min_poll_interval.is_zero() {
+            return Err(UsbWatchError::InvalidConfig(
+                "Minimum poll interval must be greater than zero".to_string(),
+            ));
+        }
+        if self.min_poll_interval > self.max_poll_interval {
+            return Err(UsbWatchError::InvalidConfig(format!(
+                "Minimum poll interval ({:?}) exceeds maximum poll interval ({:?})",
+                self.min_poll_interval, self.max_poll_interval
+            )));
+        }
+        if self.error_backoff_max < self.min_poll_interval {
+            return Err(UsbWatchError::InvalidConfig(format!(
+                "Error backoff ceiling ({:?}) is shorter than the minimum poll interval ({:?})",
+                self.error_backoff_max, self.min_poll_interval
+            )));
+        }
+        if let Some(flap) = self.flap_detection {
+            if flap.threshold == 0 {
+                return Err(UsbWatchError::InvalidConfig(
+                    "Flap detection threshold must be greater than zero".to_string(),
+                ));
+            }
+            if flap.window.is_zero() {
+                return Err(UsbWatchError::InvalidConfig(
+                    "Flap detection window must be greater than zero".to_string(),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Builder for configuring a [`UsbWatcher`].
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use usbwatch_rs::UsbWatcherBuilder;
+/// use std::time::Duration;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// // Poll aggressively on a kiosk and skip devices that were already plugged in
+/// let (watcher, mut rx) = UsbWatcherBuilder::new()
+///     .min_poll_interval(Duration::from_millis(100))
+///     .max_poll_interval(Duration::from_secs(1))
+///     .emit_initial(false)
+///     .channel_capacity(32)
+///     .build_with_channel()?;
+///
+/// tokio::spawn(async move {
+///     if let Err(e) = watcher.start_monitoring().await {
+///         eprintln!("Monitoring error: {}", e);
+///     }
+/// });
+///
+/// while let Some(device_info) = rx.recv().await {
+///     println!("USB event: {}", device_info);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct UsbWatcherBuilder {
+    config: WatcherConfig,
+    channel_capacity: usize,
+}
+
+impl Default for UsbWatcherBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UsbWatcherBuilder {
+    /// Creates a builder with the default options.
+    pub fn new() -> Self {
+        Self {
+            config: WatcherConfig::default(),
+            channel_capacity: DEFAULT_CHANNEL_CAPACITY,
+        }
+    }
+
+    /// Sets the shortest interval between scans, used right after device activity.
+    pub fn min_poll_interval(mut self, interval: Duration) -> Self {
+        self.config.min_poll_interval = interval;
+        self
+    }
+
+    /// Sets the longest interval between scans when no devices change.
+    pub fn max_poll_interval(mut self, interval: Duration) -> Self {
+        self.config.max_poll_interval = interval;
+        self
+    }
+
+    /// Sets the ceiling for the exponential backoff applied after failed scans.
+    pub fn error_backoff_max(mut self, ceiling: Duration) -> Self {
+        self.config.error_backoff_max = ceiling;
+        self
+    }
+
+    /// Sets whether devices present at startup are reported as `Connected`.
+    pub fn emit_initial(mut self, emit: bool) -> Self {
+        self.config.emit_initial = emit;
+        self
+    }
+
+    /// Sets the directory treated as the sysfs mount point.
+    ///
+    /// Only used on Linux; other platforms ignore it.
+    pub fn sysfs_root(mut self, root: impl Into<PathBuf>) -> Self {
+        self.config.sysfs_root = root.into();
+        self
+    }
+
+    /// Sets the mount table read to find where storage devices are mounted,
+    /// `/proc/self/mountinfo` by default.
+    ///
+    /// The kernel signals changes of its mount tables; any other file is
+    /// reread every [`max_poll_interval`](Self::max_poll_interval) to notice
+    /// them. Only used on Linux; other platforms ignore it.
+    pub fn mountinfo_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config.mountinfo_path = path.into();
+        self
+    }
+
+    /// Sets the directory of the udev database, `/run/udev/data` by default,
+    /// from which filesystem types, labels and UUIDs are read.
+    ///
+    /// Only used on Linux; other platforms ignore it.
+    pub fn udev_data_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.config.udev_data_dir = dir.into();
+        self
+    }
+
+    /// Sets which devices and events the watcher reports.
+    ///
+    /// Devices rejected by the filter are still tracked, so they don't show up
+    /// as new if the filter matches a later event. Also applies to
+    /// [`UsbWatcher::list_devices`].
+    pub fn filter(mut self, filter: DeviceFilter) -> Self {
+        self.config.filter = filter;
+        self
+    }
+
+    /// Holds connect and disconnect events back for `window`, dropping both
+    /// when a device disconnects and reconnects (or the reverse) within it.
+    ///
+    /// Events are delivered `window` later than they happen. Changes to a
+    /// device with a held event are delivered after it.
+    pub fn debounce(mut self, window: Duration) -> Self {
+        self.config.debounce_window = Some(window);
+        self
+    }
+
+    /// Reports a device that connects or disconnects more than `threshold`
+    /// times within `window` with a single
+    /// [`Flapping`](crate::DeviceEventType::Flapping) event.
+    ///
+    /// Further events for the device are dropped until it has been quiet for
+    /// `window`, after which its final state is reported if it differs from
+    /// the last one delivered.
+    pub fn flap_detection(mut self, threshold: usize, window: Duration) -> Self {
+        self.config.flap_detection = Some(FlapDetection { threshold, window });
+        self
+    }
+
+    /// Sets the USB ID database used to fill in vendor, product and class
+    /// names.
+    ///
+    /// `None`, the default, leaves devices named as they report themselves.
+    pub fn usb_ids(mut self, ids: Option<UsbIds>) -> Self {
+        self.config.usb_ids = ids.map(Arc::new);
+        self
+    }
+
+    /// Names devices from the [system database](UsbIds::system), if the host
+    /// has one.
+    pub fn system_usb_ids(mut self) -> Self {
+        self.config.usb_ids = UsbIds::system();
+        self
+    }
+
+    /// Sets the capacity of the channel created by [`build_with_channel`](Self::build_with_channel).
+    pub fn channel_capacity(mut self, capacity: usize) -> Self {
+        self.channel_capacity = capacity;
+        self
+    }
+
+    /// Returns the watcher configuration collected so far.
+    pub fn config(&self) -> &WatcherConfig {
+        &self.config
+    }
+
+    /// Builds a watcher that publishes events to an existing channel.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the options are inconsistent (see
+    /// [`WatcherConfig::validate`]) or the platform watcher cannot be initialised.
+    ///
+    /// # Panics
+    ///
+    /// Panics if debouncing or flap detection is enabled and this is called
+    /// outside of a Tokio runtime.
+    pub fn build(self, sender: mpsc::Sender<UsbDeviceInfo>) -> crate::Result<UsbWatcher> {
+        self.config.validate()?;
+        UsbWatcher::with_config(sender, self.config)
+    }
+
+    /// Builds a watcher around a caller-supplied backend.
+    ///
+    /// `make_backend` receives the sender to publish events to and the
+    /// collected options, so the backend can honour them the way the platform
+    /// watchers do. Debouncing and flap detection are applied to its events.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the options are inconsistent (see
+    /// [`WatcherConfig::validate`]).
+    ///
+    /// # Panics
+    ///
+    /// Panics if debouncing or flap detection is enabled and this is called
+    /// outside of a Tokio runtime.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use usbwatch_rs::filter::DeviceFilter;
+    /// use usbwatch_rs::watcher::MockBackend;
+    /// use usbwatch_rs::UsbWatcherBuilder;
+    /// use tokio::sync::mpsc;
+    ///
+    /// let (tx, rx) = mpsc::channel(100);
+    /// let watcher = UsbWatcherBuilder::new()
+    ///     .filter(DeviceFilter::new().vendor_id(0x0483))
+    ///     .build_with_backend(tx, MockBackend::with_config)?;
+    /// # Ok::<(), usbwatch_rs::UsbWatchError>(())
+    /// ```
+    pub fn build_with_backend<B, F>(
+        self,
+        sender: mpsc::Sender<UsbDeviceInfo>,
+        make_backend: F,
+    ) -> crate::Result<UsbWatcher>
+    where
+        B: UsbBackend + 'static,
+        F: FnOnce(mpsc::Sender<UsbDeviceInfo>, WatcherConfig) -> B,
+    {
+        self.config.validate()?;
+        let sender = super::debounced(sender, &self.config);
+        Ok(UsbWatcher::with_backend(make_backend(sender, self.config)))
+    }
+
+    /// Builds a watcher together with a new event channel of the configured capacity.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the channel capacity is zero, the options are
+    /// inconsistent, or the platform watcher cannot be initialised.
+    ///
+    /// # Panics
+    ///
+    /// Panics if debouncing or flap detection is enabled and this is called
+    /// outside of a Tokio runtime.
+    pub fn build_with_channel(self) -> crate::Result<(UsbWatcher, mpsc::Receiver<UsbDeviceInfo>)> {
+        if self.channel_capacity == 0 {
+            return Err(UsbWatchError::InvalidConfig(
+                "Channel capacity must be greater than zero".to_string(),
+            ));
+        }
+        let (tx, rx) = mpsc::channel(self.channel_capacity);
+        Ok((self.build(tx)?, rx))
+    }
+
+    /// Builds a watcher, starts it in a background task and returns its events as a stream.
+    ///
+    /// The watcher is stopped when the returned [`DeviceEventStream`] is dropped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the channel capacity is zero, the options are
+    /// inconsistent, or the platform watcher cannot be initialised.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a Tokio runtime.
+    pub fn build_stream(self) -> crate::Result<DeviceEventStream> {
+        let (watcher, rx) = self.build_with_channel()?;
+        Ok(DeviceEventStream::spawn(watcher, rx))
+    }
+}
+
+/// Adaptive interval between scans for the polling backends.
+///
+/// Polls quickly after activity, slows down gradually while idle, and backs
+/// off exponentially after errors.
+#[cfg(any(target_os = "linux", target_os = "windows"))]
+pub(crate) struct PollInterval {
+    current: Duration,
+    min: Duration,
+    max: Duration,
+    error_max: Duration,
+}
+
+#[cfg(any(target_os = "linux", target_os = "windows"))]
+impl PollInterval {
+    /// How much the interval grows after each idle scan.
+    const IDLE_STEP: Duration = Duration::from_millis(100);
+
+    pub(crate) fn new(config: &WatcherConfig) -> Self {
+        Self {
+            current: config.min_poll_interval,
+            min: config.min_poll_interval,
+            max: config.max_poll_interval,
+            error_max: config.error_backoff_max,
+        }
+    }
+
+    /// Returns the interval to wait before the next scan.
+    pub(crate) fn current(&self) -> Duration {
+        self.current
+    }
+
+    /// Adjusts the interval after a successful scan that produced `events_sent` events.
+    pub(crate) fn on_scan(&mut self, events_sent: usize) {
+        self.current = if events_sent > 0 {
+            self.min
+        } else {
+            std::cmp::min(self.current + Self::IDLE_STEP, self.max)
+        };
+    }
+
+    /// Backs off after a failed scan.
+    pub(crate) fn on_error(&mut self) {
+        self.current = std::cmp::min(self.current * 2, self.error_max);
+    }
+}
@@ -0,0 +1,341 @@
+//! Debouncing and flap detection for device events.
+//!
+//! A [`Debouncer`] sits between a watcher and its consumer. It holds back
+//! connect and disconnect events for a short window so that pairs cancelling
+//! each other out are never delivered, and collapses bursts of reconnects
+//! from a flapping device into a single [`DeviceEventType::Flapping`] event.
+
+use crate::device_info::{DeviceEventType, DeviceId, UsbDeviceInfo};
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+
+/// Settings for detecting devices that keep connecting and disconnecting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FlapDetection {
+    /// A device is flapping once it toggles more than this many times within `window`
+    pub threshold: usize,
+    /// How far back toggles are counted, and how long a flapping device has to
+    /// stay quiet before it is reported normally again
+    pub window: Duration,
+}
+
+/// Filters a stream of device events, suppressing short-lived connect and
+/// disconnect pairs and reporting flapping devices.
+///
+/// Works on events from any source; [`UsbWatcherBuilder::debounce`] and
+/// [`UsbWatcherBuilder::flap_detection`] put one in front of a watcher's
+/// channel automatically.
+///
+/// [`UsbWatcherBuilder::debounce`]: super::UsbWatcherBuilder::debounce
+/// [`UsbWatcherBuilder::flap_detection`]: super::UsbWatcherBuilder::flap_detection
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use std::time::Duration;
+/// use tokio::sync::mpsc;
+/// use usbwatch_rs::watcher::{Debouncer, FlapDetection};
+/// use usbwatch_rs::UsbWatcher;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let (raw_tx, raw_rx) = mpsc::channel(100);
+/// let (tx, mut rx) = mpsc::channel(100);
+///
+/// let flap = FlapDetection {
+///     threshold: 6,
+///     window: Duration::from_secs(10),
+/// };
+/// Debouncer::new(Some(Duration::from_millis(300)), Some(flap)).spawn(raw_rx, tx);
+///
+/// let watcher = UsbWatcher::new(raw_tx)?;
+/// tokio::spawn(async move { watcher.start_monitoring().await.map_err(|e| e.to_string()) });
+///
+/// while let Some(device_info) = rx.recv().await {
+///     println!("USB event: {}", device_info);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Debouncer {
+    window: Option<Duration>,
+    flap: Option<FlapDetection>,
+    devices: HashMap<DeviceId, DeviceState>,
+}
+
+/// What the debouncer knows about one device.
+#[derive(Debug, Clone, Default)]
+struct DeviceState {
+    /// Held connect or disconnect events, each followed by any changes received after it
+    held: Vec<UsbDeviceInfo>,
+    /// When the held events are delivered
+    release_at: Option<Instant>,
+    /// Recent connects and disconnects, for flap detection
+    toggles: VecDeque<Instant>,
+    /// Set while the device is flapping
+    flapping: Option<Flapping>,
+    /// Whether the last delivered event left the device connected
+    connected: Option<bool>,
+}
+
+#[derive(Debug, Clone)]
+struct Flapping {
+    /// The most recent connect or disconnect, delivered once the device settles
+    latest: UsbDeviceInfo,
+    /// When the device counts as settled unless it toggles again
+    quiet_at: Instant,
+}
+
+impl Debouncer {
+    /// Creates a debouncer.
+    ///
+    /// # Arguments
+    ///
+    /// * `window` - Connect and disconnect events are held this long and
+    ///   dropped together if the opposite event arrives in the meantime
+    /// * `flap` - Optional flap detection settings
+    pub fn new(window: Option<Duration>, flap: Option<FlapDetection>) -> Self {
+        Self {
+            window: window.filter(|w| !w.is_zero()),
+            flap,
+            devices: HashMap::new(),
+        }
+    }
+
+    /// Returns true if the debouncer would pass every event through unchanged.
+    pub fn is_passthrough(&self) -> bool {
+        self.window.is_none() && self.flap.is_none()
+    }
+
+    /// Spawns a task that forwards events from `rx` to `tx`.
+    ///
+    /// The task ends once `rx` is closed and every held event has been
+    /// delivered, or once `tx` is closed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a Tokio runtime.
+    pub fn spawn(
+        self,
+        rx: mpsc::Receiver<UsbDeviceInfo>,
+        tx: mpsc::Sender<UsbDeviceInfo>,
+    ) -> JoinHandle<()> {
+        tokio::spawn(self.run(rx, tx))
+    }
+
+    /// Forwards events from `rx` to `tx` until `rx` is closed.
+    pub async fn run(
+        mut self,
+        mut rx: mpsc::Receiver<UsbDeviceInfo>,
+        tx: mpsc::Sender<UsbDeviceInfo>,
+    ) {
+        loop {
+            let deadline = self.next_deadline();
+            let delivered = tokio::select! {
+                event = rx.recv() => match event {
+                    Some(event) => {
+                        let delivered = self.handle(event, &tx).await;
+                        // Without a deadline `release_due` may never run, e.g. with flap detection only
+                        self.forget_settled(Instant::now());
+                        delivered
+                    }
+                    None => break,
+                },
+                _ = sleep_until(deadline), if deadline.is_some() => {
+                    self.release_due(Instant::now(), &tx).await
+                }
+            };
+            if !delivered {
+                return;
+            }
+        }
+
+        // The source is done, so nothing can cancel the held events any more
+        self.release_due(far_future(), &tx).await;
+    }
+
+    /// Processes one incoming event, returning false if the consumer has gone away.
+    async fn handle(&mut self, event: UsbDeviceInfo, tx: &mpsc::Sender<UsbDeviceInfo>) -> bool {
+        let now = Instant::now();
+        let state = self.devices.entry(event.device_id.clone()).or_default();
+
+        if !is_toggle(&event) {
+            // Changes are dropped while flapping and otherwise stay behind a held toggle
+            if state.flapping.is_some() {
+                return true;
+            }
+            if !state.held.is_empty() {
+                state.held.push(event);
+                return true;
+            }
+            return deliver(state, event, tx).await;
+        }
+
+        if let Some(flap) = self.flap {
+            state.toggles.push_back(now);
+            while state
+                .toggles
+                .front()
+                .is_some_and(|&t| now.duration_since(t) > flap.window)
+            {
+                state.toggles.pop_front();
+            }
+
+            if let Some(flapping) = &mut state.flapping {
+                flapping.latest = event;
+                flapping.quiet_at = now + flap.window;
+                return true;
+            }
+            if state.toggles.len() > flap.threshold {
+                state.held.clear();
+                state.release_at = None;
+
+                let mut report = event.clone();
+                report.event_type = DeviceEventType::Flapping {
+                    count: state.toggles.len(),
+                };
+                report.timestamp = chrono::Utc::now();
+                state.flapping = Some(Flapping {
+                    latest: event,
+                    quiet_at: now + flap.window,
+                });
+                return tx.send(report).await.is_ok();
+            }
+        }
+
+        let Some(window) = self.window else {
+            return deliver(state, event, tx).await;
+        };
+        match state.held.iter().rposition(is_toggle) {
+            // The opposite toggle arrived in time, so neither it nor the most
+            // recent held toggle is reported, along with the changes after it
+            Some(last) if state.held[last].event_type != event.event_type => {
+                state.held.truncate(last);
+                if state.held.is_empty() {
+                    state.release_at = None;
+                }
+            }
+            Some(_) => state.held.push(event),
+            None => {
+                state.held.push(event);
+                state.release_at = Some(now + window);
+            }
+        }
+        true
+    }
+
+    /// Delivers held events and settles flapping devices whose deadline is at
+    /// or before `now`, returning false if the consumer has gone away.
+    async fn release_due(&mut self, now: Instant, tx: &mpsc::Sender<UsbDeviceInfo>) -> bool {
+        let mut due: Vec<(Instant, DeviceId)> = self
+            .devices
+            .iter()
+            .filter_map(|(id, state)| {
+                let deadline = state
+                    .release_at
+                    .into_iter()
+                    .chain(state.flapping.as_ref().map(|f| f.quiet_at))
+                    .min()?;
+                (deadline <= now).then(|| (deadline, id.clone()))
+            })
+            .collect();
+        due.sort();
+
+        for (_, id) in due {
+            let Some(state) = self.devices.get_mut(&id) else {
+                continue;
+            };
+
+            if state.release_at.is_some_and(|t| t <= now) {
+                state.release_at = None;
+                for event in std::mem::take(&mut state.held) {
+                    if !deliver(state, event, tx).await {
+                        return false;
+                    }
+                }
+            }
+
+            if state.flapping.as_ref().is_some_and(|f| f.quiet_at <= now) {
+                let Some(flapping) = state.flapping.take() else {
+                    continue;
+                };
+                state.toggles.clear();
+
+                // Only report the final state if it differs from what the consumer last saw
+                let connected = flapping.latest.event_type == DeviceEventType::Connected;
+                if state.connected.unwrap_or(false) != connected {
+                    let mut event = flapping.latest;
+                    event.timestamp = chrono::Utc::now();
+                    if !deliver(state, event, tx).await {
+                        return false;
+                    }
+                }
+            }
+        }
+
+        self.forget_settled(now);
+        true
+    }
+
+    /// Forgets devices with no held events and no toggles recent enough to
+    /// count towards flapping at `now`.
+    fn forget_settled(&mut self, now: Instant) {
+        let flap_window = self.flap.map(|f| f.window).unwrap_or_default();
+        self.devices.retain(|_, state| {
+            state.release_at.is_some()
+                || state.flapping.is_some()
+                || state
+                    .toggles
+                    .back()
+                    .is_some_and(|&t| now.saturating_duration_since(t) <= flap_window)
+        });
+    }
+
+    /// Returns the earliest time at which held events are released or a flapping device settles.
+    fn next_deadline(&self) -> Option<Instant> {
+        self.devices
+            .values()
+            .flat_map(|state| {
+                state
+                    .release_at
+                    .into_iter()
+                    .chain(state.flapping.as_ref().map(|f| f.quiet_at))
+            })
+            .min()
+    }
+}
+
+/// Returns true for connect and disconnect events.
+fn is_toggle(event: &UsbDeviceInfo) -> bool {
+    matches!(
+        event.event_type,
+        DeviceEventType::Connected | DeviceEventType::Disconnected
+    )
+}
+
+/// Sends an event to the consumer, recording the connection state it implies.
+async fn deliver(
+    state: &mut DeviceState,
+    event: UsbDeviceInfo,
+    tx: &mpsc::Sender<UsbDeviceInfo>,
+) -> bool {
+    match event.event_type {
+        DeviceEventType::Connected => state.connected = Some(true),
+        DeviceEventType::Disconnected => state.connected = Some(false),
+        _ => {}
+    }
+    tx.send(event).await.is_ok()
+}
+
+async fn sleep_until(deadline: Option<Instant>) {
+    tokio::time::sleep_until(deadline.unwrap_or_else(far_future)).await;
+}
+
+/// An instant later than any deadline the debouncer sets.
+fn far_future() -> Instant {
+    Instant::now() + Duration::from_secs(86400 * 365)
+}
@@ -1,25 +1,423 @@
 #[cfg(target_os = "linux")]
-use crate::device_info::{DeviceEventType, DeviceHandle, UsbDeviceInfo};
+use super::{BackendFuture, PollInterval, StopHandle, UsbBackend, WatcherConfig};
+#[cfg(target_os = "linux")]
+use crate::device_info::{
+    BcdVersion, BlockDevice, DeviceEventType, DeviceHandle, DeviceId, NetworkInterface, ProductId,
+    UsbClass, UsbDeviceInfo, UsbInterface, VendorId,
+};
+#[cfg(target_os = "linux")]
+use crate::error::UsbWatchError;
+#[cfg(target_os = "linux")]
+use crate::topology::{build_tree, UsbTreeNode};
 #[cfg(target_os = "linux")]
 use std::collections::HashMap;
 #[cfg(target_os = "linux")]
-use std::path::Path;
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+#[cfg(target_os = "linux")]
+use std::path::{Path, PathBuf};
+#[cfg(target_os = "linux")]
+use std::str::FromStr;
+#[cfg(target_os = "linux")]
+use std::time::Duration;
 #[cfg(target_os = "linux")]
 use tokio::fs;
 #[cfg(target_os = "linux")]
+use tokio::io::unix::AsyncFd;
+#[cfg(target_os = "linux")]
+use tokio::io::Interest;
+#[cfg(target_os = "linux")]
 use tokio::sync::mpsc;
+#[cfg(target_os = "linux")]
+use tokio::time::Instant;
+
+/// Default mount point of the sysfs filesystem.
+#[cfg(target_os = "linux")]
+pub const DEFAULT_SYSFS_ROOT: &str = "/sys";
+
+/// Directory listing every USB device, relative to the sysfs root.
+#[cfg(target_os = "linux")]
+const USB_DEVICES_DIR: &str = "bus/usb/devices";
+
+/// Kernel device names (relative to `/dev`) reported as a device's nodes.
+#[cfg(target_os = "linux")]
+const DEVICE_NODE_PREFIXES: &[&str] = &["ttyUSB", "ttyACM", "sd", "hidraw", "video", "bus/usb/"];
+
+/// How far below an interface directory to look for device nodes.
+///
+/// Block devices are the deepest, e.g. `host2/target2:0:0/2:0:0:0/block/sda/sda1`.
+#[cfg(target_os = "linux")]
+const DEVICE_NODE_SEARCH_DEPTH: usize = 6;
+
+/// Steps from a USB device to the disks of a storage device: an interface, the
+/// SCSI host, target and logical unit, the `block` directory and finally the
+/// disks, e.g. `1-1:1.0/host2/target2:0:0/2:0:0:0/block/sdb`.
+#[cfg(target_os = "linux")]
+const BLOCK_DEVICE_CHAIN: &[fn(&str) -> bool] = &[
+    |name| name.contains(':'),
+    |name| name.starts_with("host"),
+    |name| name.starts_with("target"),
+    |name| name.split(':').count() == 4,
+    |name| name == "block",
+    |_| true,
+];
+
+/// Size of the sectors sysfs counts block device sizes in, whatever the
+/// device's own block size.
+#[cfg(target_os = "linux")]
+const SECTOR_SIZE: u64 = 512;
+
+/// Netlink multicast group on which the kernel broadcasts raw uevents.
+#[cfg(target_os = "linux")]
+const UEVENT_KERNEL_GROUP: u32 = 1;
+
+/// How long a device added without a `bind` uevent is held back before it is
+/// reported anyway; kernels before 4.14 never send `bind`.
+#[cfg(target_os = "linux")]
+const BIND_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Receive buffer size for a single uevent datagram.
+#[cfg(target_os = "linux")]
+const UEVENT_BUFFER_SIZE: usize = 8192;
 
 #[cfg(target_os = "linux")]
 /// Linux-specific USB device watcher implementation.
 ///
-/// This watcher uses the Linux sysfs filesystem (`/sys/bus/usb/devices`) to monitor
-/// USB device connections and disconnections. It polls the filesystem periodically
-/// to detect changes and sends events through the provided channel.
+/// This watcher listens for kernel uevents on a `NETLINK_KOBJECT_UEVENT` socket
+/// and reports USB devices as soon as the kernel announces them. Device details
+/// are read from the sysfs filesystem (`/sys/bus/usb/devices`). If the netlink
+/// socket cannot be opened (e.g., inside restricted containers), the watcher
+/// falls back to polling sysfs periodically.
+///
+/// The sysfs root can be changed with [`LinuxUsbWatcher::with_sysfs_root`] to run
+/// the watcher against a fake device tree. Kernel uevents only describe the real
+/// `/sys`, so a custom root is polled unless uevents are supplied through
+/// [`LinuxUsbWatcher::monitor_uevents`].
 ///
-/// Device handles are provided for each detected device, including sysfs path.
-/// Future versions may detect device nodes (e.g., `/dev/ttyUSB0`).
+/// Each device is reported with its sysfs path and `/dev` nodes (e.g.,
+/// `/dev/ttyUSB0`), the block devices and mount points of storage devices,
+/// and the network interfaces of USB network adapters.
 pub struct LinuxUsbWatcher {
     tx: mpsc::Sender<UsbDeviceInfo>,
+    // Boxed to keep the `UsbWatcher` variants about the same size
+    config: Box<WatcherConfig>,
+    stop: StopHandle,
+}
+
+/// Action carried by a kernel uevent.
+#[cfg(target_os = "linux")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UeventAction {
+    /// A device was added
+    Add,
+    /// A device was removed
+    Remove,
+    /// A device changed state
+    Change,
+    /// A driver was bound to a device
+    Bind,
+    /// A driver was unbound from a device
+    Unbind,
+    /// Any other action (e.g., `move`, `online`, `offline`)
+    Other(String),
+}
+
+#[cfg(target_os = "linux")]
+impl From<&str> for UeventAction {
+    fn from(action: &str) -> Self {
+        match action {
+            "add" => UeventAction::Add,
+            "remove" => UeventAction::Remove,
+            "change" => UeventAction::Change,
+            "bind" => UeventAction::Bind,
+            "unbind" => UeventAction::Unbind,
+            other => UeventAction::Other(other.to_string()),
+        }
+    }
+}
+
+/// A kernel uevent as broadcast on the `NETLINK_KOBJECT_UEVENT` socket.
+#[cfg(target_os = "linux")]
+#[derive(Debug, Clone)]
+pub struct Uevent {
+    /// The action that triggered the event
+    pub action: UeventAction,
+    /// Device path relative to `/sys` (e.g., "/devices/pci0000:00/0000:00:14.0/usb1/1-1")
+    pub devpath: String,
+    /// Kernel subsystem of the device (e.g., "usb")
+    pub subsystem: Option<String>,
+    /// Device type within the subsystem (e.g., "usb_device" or "usb_interface")
+    pub devtype: Option<String>,
+    /// All `KEY=VALUE` properties carried by the event
+    pub properties: HashMap<String, String>,
+}
+
+#[cfg(target_os = "linux")]
+impl Uevent {
+    /// Returns true if this event describes a USB device rather than one of its interfaces.
+    pub fn is_usb_device(&self) -> bool {
+        self.subsystem.as_deref() == Some("usb") && self.devtype.as_deref() == Some("usb_device")
+    }
+
+    /// Returns true if this event describes an interface of a USB device.
+    pub fn is_usb_interface(&self) -> bool {
+        self.subsystem.as_deref() == Some("usb") && self.devtype.as_deref() == Some("usb_interface")
+    }
+
+    /// Returns the sysfs entry name of the device's parent, which for an
+    /// interface (e.g., "1-1.4:1.0") is the device it belongs to ("1-1.4").
+    pub fn parent_sysfs_name(&self) -> Option<&str> {
+        self.devpath
+            .rsplit('/')
+            .nth(1)
+            .filter(|name| !name.is_empty())
+    }
+
+    /// Returns the sysfs entry name of the USB device an event below one of
+    /// its interfaces belongs to, e.g. "2-1" for the disk
+    /// `.../usb2/2-1/2-1:1.0/host2/target2:0:0/2:0:0:0/block/sdb`.
+    pub fn usb_device_sysfs_name(&self) -> Option<&str> {
+        self.devpath
+            .split('/')
+            .filter_map(|component| {
+                // Interfaces are named "<device>:<config>.<interface>"
+                let (device, interface) = component.split_once(':')?;
+                let is_interface = device.contains('-')
+                    && device.starts_with(|c: char| c.is_ascii_digit())
+                    && interface.contains('.');
+                is_interface.then_some(device)
+            })
+            .next_back()
+    }
+
+    /// Returns the sysfs entry name of the device (e.g., "1-1.4").
+    ///
+    /// This is the final component of the device path and matches the entry
+    /// name under `/sys/bus/usb/devices`.
+    pub fn sysfs_name(&self) -> Option<&str> {
+        self.devpath
+            .rsplit('/')
+            .next()
+            .filter(|name| !name.is_empty())
+    }
+}
+
+/// Parses a raw kernel uevent datagram.
+///
+/// Kernel uevents consist of an `action@devpath` header followed by
+/// NUL-separated `KEY=VALUE` properties. Messages re-broadcast by udev (which
+/// start with the `libudev` magic) and malformed buffers yield `None`.
+///
+/// # Examples
+///
+/// ```
+/// use usbwatch_rs::watcher::linux::{parse_uevent, UeventAction};
+///
+/// let buf = b"add@/devices/pci0000:00/0000:00:14.0/usb1/1-1\0\
+///             ACTION=add\0DEVPATH=/devices/pci0000:00/0000:00:14.0/usb1/1-1\0\
+///             SUBSYSTEM=usb\0DEVTYPE=usb_device\0PRODUCT=781/5583/100\0";
+///
+/// let uevent = parse_uevent(buf).expect("valid uevent");
+/// assert_eq!(uevent.action, UeventAction::Add);
+/// assert_eq!(uevent.sysfs_name(), Some("1-1"));
+/// assert!(uevent.is_usb_device());
+/// ```
+#[cfg(target_os = "linux")]
+pub fn parse_uevent(buf: &[u8]) -> Option<Uevent> {
+    let mut fields = buf
+        .split(|&b| b == 0)
+        .filter(|field| !field.is_empty())
+        .map(String::from_utf8_lossy);
+
+    let header = fields.next()?;
+    let (header_action, header_devpath) = header.split_once('@')?;
+
+    let properties: HashMap<String, String> = fields
+        .filter_map(|field| {
+            field
+                .split_once('=')
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+        })
+        .collect();
+
+    let action = properties
+        .get("ACTION")
+        .map(String::as_str)
+        .unwrap_or(header_action);
+    let devpath = properties
+        .get("DEVPATH")
+        .cloned()
+        .unwrap_or_else(|| header_devpath.to_string());
+
+    Some(Uevent {
+        action: UeventAction::from(action),
+        devpath,
+        subsystem: properties.get("SUBSYSTEM").cloned(),
+        devtype: properties.get("DEVTYPE").cloned(),
+        properties,
+    })
+}
+
+/// Non-blocking netlink socket subscribed to kernel uevents.
+#[cfg(target_os = "linux")]
+struct UeventSocket {
+    fd: AsyncFd<OwnedFd>,
+}
+
+#[cfg(target_os = "linux")]
+impl UeventSocket {
+    /// Opens a `NETLINK_KOBJECT_UEVENT` socket bound to the kernel uevent group.
+    fn open() -> std::io::Result<Self> {
+        // SAFETY: plain socket(2) call; the returned descriptor is checked before use.
+        let raw_fd = unsafe {
+            libc::socket(
+                libc::AF_NETLINK,
+                libc::SOCK_DGRAM | libc::SOCK_NONBLOCK | libc::SOCK_CLOEXEC,
+                libc::NETLINK_KOBJECT_UEVENT,
+            )
+        };
+        if raw_fd < 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        // SAFETY: `raw_fd` is a freshly created descriptor that nothing else owns.
+        let fd = unsafe { OwnedFd::from_raw_fd(raw_fd) };
+
+        // SAFETY: `sockaddr_nl` is plain old data, so all-zeroes is a valid value.
+        let mut addr: libc::sockaddr_nl = unsafe { std::mem::zeroed() };
+        addr.nl_family = libc::AF_NETLINK as libc::sa_family_t;
+        addr.nl_groups = UEVENT_KERNEL_GROUP;
+
+        // SAFETY: `addr` is a valid `sockaddr_nl` and the length matches its size.
+        let rc = unsafe {
+            libc::bind(
+                fd.as_raw_fd(),
+                &addr as *const libc::sockaddr_nl as *const libc::sockaddr,
+                std::mem::size_of::<libc::sockaddr_nl>() as libc::socklen_t,
+            )
+        };
+        if rc < 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+
+        Self::from_fd(fd)
+    }
+
+    /// Reads uevents from an already open datagram socket.
+    fn from_fd(fd: OwnedFd) -> std::io::Result<Self> {
+        Ok(Self {
+            fd: AsyncFd::new(fd)?,
+        })
+    }
+
+    /// Receives a single uevent datagram into `buf`, returning its length.
+    async fn recv(&self, buf: &mut [u8]) -> std::io::Result<usize> {
+        loop {
+            let mut guard = self.fd.readable().await?;
+            let result = guard.try_io(|fd| Self::recv_raw(fd.as_raw_fd(), buf));
+
+            match result {
+                Ok(result) => return result,
+                Err(_would_block) => continue,
+            }
+        }
+    }
+
+    /// Receives a queued uevent datagram without waiting.
+    ///
+    /// Fails with `WouldBlock` once the queue is empty.
+    fn try_recv(&self, buf: &mut [u8]) -> std::io::Result<usize> {
+        Self::recv_raw(self.fd.get_ref().as_raw_fd(), buf)
+    }
+
+    fn recv_raw(fd: libc::c_int, buf: &mut [u8]) -> std::io::Result<usize> {
+        // SAFETY: `buf` is valid for writes of `buf.len()` bytes.
+        let len = unsafe {
+            libc::recv(
+                fd,
+                buf.as_mut_ptr() as *mut libc::c_void,
+                buf.len(),
+                libc::MSG_DONTWAIT,
+            )
+        };
+        if len < 0 {
+            Err(std::io::Error::last_os_error())
+        } else {
+            Ok(len as usize)
+        }
+    }
+}
+
+/// Signals when filesystems are mounted or unmounted.
+#[cfg(target_os = "linux")]
+enum MountWatch {
+    /// The kernel marks its mount table as having priority data whenever it
+    /// changes, so it can be watched like a socket
+    Kernel(AsyncFd<std::fs::File>),
+    /// Any other file, such as a copy of the table, is reread every `interval`
+    Polled {
+        path: PathBuf,
+        table: String,
+        interval: Duration,
+    },
+}
+
+#[cfg(target_os = "linux")]
+impl MountWatch {
+    /// Starts watching a mount table such as `/proc/self/mountinfo`, rereading
+    /// it every `interval` if it is a file the kernel doesn't signal changes of.
+    fn open(path: &Path, interval: Duration) -> std::io::Result<Self> {
+        let file = std::fs::File::open(path)?;
+        // epoll rejects regular files with EPERM
+        match AsyncFd::with_interest(file, Interest::PRIORITY) {
+            Ok(fd) => Ok(Self::Kernel(fd)),
+            Err(e) if e.raw_os_error() == Some(libc::EPERM) => Ok(Self::Polled {
+                path: path.to_path_buf(),
+                table: std::fs::read_to_string(path)?,
+                interval,
+            }),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Waits for the next change of the mount table.
+    async fn changed(&mut self) -> std::io::Result<()> {
+        match self {
+            Self::Kernel(fd) => {
+                let mut guard = fd.ready(Interest::PRIORITY).await?;
+                guard.clear_ready();
+                Ok(())
+            }
+            Self::Polled {
+                path,
+                table,
+                interval,
+            } => loop {
+                tokio::time::sleep(*interval).await;
+                let current = fs::read_to_string(&path).await?;
+                if current != *table {
+                    *table = current;
+                    return Ok(());
+                }
+            },
+        }
+    }
+}
+
+/// Waits until `deadline`, or forever if there is none.
+#[cfg(target_os = "linux")]
+async fn sleep_until(deadline: Option<Instant>) {
+    match deadline {
+        Some(deadline) => tokio::time::sleep_until(deadline).await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Waits for the next change of the mount table, or forever if it isn't watched.
+#[cfg(target_os = "linux")]
+async fn mount_change(mounts: Option<&mut MountWatch>) -> std::io::Result<()> {
+    match mounts {
+        Some(mounts) => mounts.changed().await,
+        None => std::future::pending().await,
+    }
 }
 
 #[cfg(target_os = "linux")]
@@ -34,125 +432,511 @@ impl LinuxUsbWatcher {
     ///
     /// A new `LinuxUsbWatcher` instance
     pub fn new(tx: mpsc::Sender<UsbDeviceInfo>) -> Self {
-        Self { tx }
+        Self::with_config(tx, WatcherConfig::default())
+    }
+
+    /// Creates a new Linux USB watcher with the given options.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx` - Channel sender for broadcasting USB device events
+    /// * `config` - Polling, startup and sysfs root options
+    pub fn with_config(tx: mpsc::Sender<UsbDeviceInfo>, config: WatcherConfig) -> Self {
+        Self {
+            tx,
+            config: Box::new(config),
+            stop: StopHandle::new(),
+        }
+    }
+
+    /// Returns a handle that makes [`start_monitoring`](Self::start_monitoring) return.
+    pub fn stop_handle(&self) -> StopHandle {
+        self.stop.clone()
+    }
+
+    /// Creates a new Linux USB watcher reading devices from a custom sysfs root.
+    ///
+    /// Devices are looked up under `<sysfs_root>/bus/usb/devices`, so a test can
+    /// point the watcher at a temporary directory laid out like `/sys`.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx` - Channel sender for broadcasting USB device events
+    /// * `sysfs_root` - Directory to treat as the sysfs mount point
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use usbwatch_rs::watcher::linux::LinuxUsbWatcher;
+    /// use tokio::sync::mpsc;
+    ///
+    /// let (tx, _rx) = mpsc::channel(100);
+    /// let watcher = LinuxUsbWatcher::with_sysfs_root(tx, "/tmp/fake-sysfs");
+    /// assert_eq!(watcher.sysfs_root(), std::path::Path::new("/tmp/fake-sysfs"));
+    /// ```
+    pub fn with_sysfs_root(
+        tx: mpsc::Sender<UsbDeviceInfo>,
+        sysfs_root: impl Into<PathBuf>,
+    ) -> Self {
+        let config = WatcherConfig {
+            sysfs_root: sysfs_root.into(),
+            ..WatcherConfig::default()
+        };
+        Self::with_config(tx, config)
+    }
+
+    /// Returns the sysfs root this watcher reads devices from.
+    pub fn sysfs_root(&self) -> &Path {
+        &self.config.sysfs_root
+    }
+
+    /// Returns the directory listing every USB device under the sysfs root.
+    fn usb_devices_path(&self) -> PathBuf {
+        self.config.sysfs_root.join(USB_DEVICES_DIR)
     }
 
     /// Starts monitoring USB devices on Linux.
     ///
-    /// This method continuously polls the `/sys/bus/usb/devices` directory
-    /// to detect USB device connection and disconnection events. It maintains
-    /// a map of known devices and compares against current devices to identify
-    /// changes.
+    /// This method subscribes to kernel uevents and reports devices that are
+    /// already present, followed by every `add`/`remove` of a USB device as
+    /// it happens. If the netlink socket cannot be opened, or a custom sysfs
+    /// root is in use, it polls the `bus/usb/devices` directory instead.
     ///
     /// # Returns
     ///
-    /// Returns `Ok(())` if monitoring stops gracefully, or `Err(String)` if
-    /// an error occurs during monitoring.
+    /// Returns `Ok(())` once stopped through the watcher's [`StopHandle`], or
+    /// an error if monitoring fails. Uevents already
+    /// queued on the socket when the watcher is stopped are still reported.
     ///
     /// # Errors
     ///
     /// Returns an error if:
-    /// - The USB devices directory is not accessible
+    /// - The USB devices directory is missing ([`UsbWatchError::SysfsUnavailable`])
     /// - File system operations fail
-    /// - Device information parsing fails
-    pub async fn start_monitoring(&self) -> Result<(), String> {
+    /// - Reading from the uevent socket fails
+    /// - The event receiver is dropped ([`UsbWatchError::ChannelClosed`])
+    pub async fn start_monitoring(&self) -> crate::Result<()> {
         println!("Starting USB device monitoring on Linux...");
 
+        if self.sysfs_root() != Path::new(DEFAULT_SYSFS_ROOT) {
+            return self.poll_devices().await;
+        }
+
+        match UeventSocket::open() {
+            Ok(socket) => self.watch_uevents(socket).await,
+            Err(e) => {
+                eprintln!("Uevent socket unavailable ({e}), falling back to sysfs polling");
+                self.poll_devices().await
+            }
+        }
+    }
+
+    /// Monitors devices like [`start_monitoring`](Self::start_monitoring), but
+    /// reads uevents from `socket` instead of opening the kernel's netlink
+    /// socket, whatever the sysfs root.
+    ///
+    /// `socket` must deliver one uevent per datagram in the kernel's format,
+    /// e.g. a netlink socket set up by the caller or one end of a
+    /// [`UnixDatagram`](std::os::unix::net::UnixDatagram) pair replaying
+    /// captured uevents against a fake sysfs tree.
+    ///
+    /// # Errors
+    ///
+    /// As for [`start_monitoring`](Self::start_monitoring), and
+    /// [`UsbWatchError::Io`] if `socket` cannot be registered with the runtime.
+    pub async fn monitor_uevents(&self, socket: OwnedFd) -> crate::Result<()> {
+        let socket = UeventSocket::from_fd(socket)
+            .map_err(|e| UsbWatchError::io("Failed to watch uevent socket", e))?;
+        self.watch_uevents(socket).await
+    }
+
+    /// Enumerates the USB devices currently present in sysfs.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the USB devices directory is missing or cannot be read.
+    pub async fn list_devices(&self) -> crate::Result<Vec<UsbDeviceInfo>> {
+        let mut devices = self.scan_usb_devices().await?;
+        devices.retain(|device| self.config.filter.matches(device));
+        Ok(devices)
+    }
+
+    /// Builds the USB bus topology from the devices currently present in sysfs.
+    ///
+    /// Unlike [`list_devices`](Self::list_devices), this ignores the filter,
+    /// so the hubs leading to a device are always included.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the USB devices directory is missing or cannot be read.
+    pub async fn device_tree(&self) -> crate::Result<Vec<UsbTreeNode>> {
+        let devices = self.scan_usb_devices().await?;
+        Ok(build_tree(
+            devices.into_iter().map(UsbTreeNode::new).collect(),
+        ))
+    }
+
+    /// Reports devices as the kernel announces them on the uevent socket.
+    async fn watch_uevents(&self, socket: UeventSocket) -> crate::Result<()> {
+        let mut state = UeventState::default();
+        // Mounting doesn't produce a uevent, so the mount table is watched too
+        let mut mounts =
+            MountWatch::open(&self.config.mountinfo_path, self.config.max_poll_interval)
+                .map_err(|e| {
+                    eprintln!("Mount table unavailable ({e}), mount points won't be updated")
+                })
+                .ok();
+        self.resync_devices(&mut state.devices, self.config.emit_initial)
+            .await?;
+
+        let mut buf = vec![0u8; UEVENT_BUFFER_SIZE];
+        loop {
+            let bind_deadline = state.awaiting_bind.values().min().copied();
+            let received = tokio::select! {
+                _ = self.stop.stopped() => None,
+                _ = self.tx.closed() => return Err(UsbWatchError::ChannelClosed),
+                _ = sleep_until(bind_deadline) => {
+                    self.report_unbound(&mut state).await;
+                    continue;
+                }
+                changed = mount_change(mounts.as_mut()) => {
+                    match changed {
+                        Ok(()) => self.resync_devices(&mut state.devices, true).await?,
+                        Err(e) => {
+                            eprintln!("Failed to watch the mount table ({e}), mount points won't be updated");
+                            mounts = None;
+                        }
+                    }
+                    continue;
+                }
+                result = socket.recv(&mut buf) => Some(result),
+            };
+            let Some(received) = received else {
+                self.drain_uevents(&socket, &mut buf, &mut state).await;
+                return Ok(());
+            };
+
+            let len = match received {
+                Ok(len) => len,
+                Err(e) if e.raw_os_error() == Some(libc::ENOBUFS) => {
+                    // The kernel dropped events; rescan so we don't miss any changes
+                    eprintln!("Uevent socket overflowed, rescanning USB devices");
+                    self.resync_devices(&mut state.devices, true).await?;
+                    state.awaiting_bind.clear();
+                    continue;
+                }
+                Err(e) => return Err(UsbWatchError::io("Failed to read uevent", e)),
+            };
+
+            if let Some(uevent) = parse_uevent(&buf[..len]) {
+                self.handle_uevent(&uevent, &mut state).await;
+            }
+        }
+    }
+
+    /// Reports uevents that are still queued on the socket after a stop request.
+    async fn drain_uevents(&self, socket: &UeventSocket, buf: &mut [u8], state: &mut UeventState) {
+        while let Ok(len) = socket.try_recv(buf) {
+            if let Some(uevent) = parse_uevent(&buf[..len]) {
+                self.handle_uevent(&uevent, state).await;
+            }
+        }
+    }
+
+    /// Updates the known devices from a single USB device or interface uevent.
+    async fn handle_uevent(&self, uevent: &Uevent, state: &mut UeventState) {
+        // Binding a driver to an interface changes the device it belongs to
+        if uevent.is_usb_interface() {
+            let is_binding = matches!(
+                uevent.action,
+                UeventAction::Bind | UeventAction::Unbind | UeventAction::Change
+            );
+            if let Some(name) = uevent.parent_sysfs_name().filter(|_| is_binding) {
+                self.refresh_device(name, false, state).await;
+            }
+            return;
+        }
+        // Disks, partitions and network interfaces appear a while after the device is bound
+        if matches!(uevent.subsystem.as_deref(), Some("block" | "net")) {
+            if let Some(name) = uevent.usb_device_sysfs_name() {
+                self.refresh_device(name, false, state).await;
+            }
+            return;
+        }
+        if !uevent.is_usb_device() {
+            return;
+        }
+
+        let Some(name) = uevent.sysfs_name() else {
+            return;
+        };
+        let device_path = self.usb_devices_path().join(name);
+
+        match uevent.action {
+            UeventAction::Remove => {
+                // The device went away before it was reported, so there is nothing to undo
+                if state.awaiting_bind.remove(name).is_some() {
+                    return;
+                }
+
+                // A remove event only carries the sysfs name, so look the device up by it
+                let device = state
+                    .devices
+                    .iter()
+                    .find(|(_, d)| sysfs_name(d).as_deref() == Some(name))
+                    .map(|(id, _)| id.clone())
+                    .and_then(|id| state.devices.remove(&id))
+                    .or_else(|| {
+                        let mut device = device_from_uevent(uevent, &self.usb_devices_path())?;
+                        if let Some(ids) = &self.config.usb_ids {
+                            ids.annotate(&mut device);
+                        }
+                        Some(device)
+                    });
+                if let Some(device) = device {
+                    self.send_event(device, DeviceEventType::Disconnected).await;
+                }
+            }
+            // The interfaces and their /dev nodes only exist once the device is
+            // bound, so hold back the report until the bind event, or until
+            // BIND_TIMEOUT on kernels without one. Unauthorized devices are
+            // never bound and are reported straight away.
+            UeventAction::Add
+                if self
+                    .read_sys_file(&device_path, "authorized")
+                    .await
+                    .as_deref()
+                    != Some("0") =>
+            {
+                state
+                    .awaiting_bind
+                    .insert(name.to_string(), Instant::now() + BIND_TIMEOUT);
+            }
+            // add, change, bind and unbind all leave the device in sysfs
+            _ => {
+                self.refresh_device(name, uevent.action == UeventAction::Bind, state)
+                    .await;
+            }
+        }
+    }
+
+    /// Rereads a device from sysfs and reports it as connected if it is new, or
+    /// as changed if any of its attributes differ from the known copy.
+    async fn refresh_device(&self, name: &str, is_bind: bool, state: &mut UeventState) {
+        if !is_bind && state.awaiting_bind.contains_key(name) {
+            return;
+        }
+        state.awaiting_bind.remove(name);
+
+        let device_path = self.usb_devices_path().join(name);
+        let Some(device) = self.parse_usb_device(&device_path).await else {
+            return;
+        };
+        match state
+            .devices
+            .insert(device.device_id.clone(), device.clone())
+        {
+            None => {
+                self.send_event(device, DeviceEventType::Connected).await;
+            }
+            Some(previous) => {
+                self.send_change(&previous, device).await;
+            }
+        }
+    }
+
+    /// Reports the devices whose bind uevent is overdue as they are now.
+    async fn report_unbound(&self, state: &mut UeventState) {
+        let now = Instant::now();
+        let overdue: Vec<String> = state
+            .awaiting_bind
+            .iter()
+            .filter(|(_, deadline)| **deadline <= now)
+            .map(|(name, _)| name.clone())
+            .collect();
+        for name in overdue {
+            self.refresh_device(&name, true, state).await;
+        }
+    }
+
+    /// Rescans sysfs and, if `report` is set, reports any differences from the known devices.
+    async fn resync_devices(
+        &self,
+        known_devices: &mut HashMap<DeviceId, UsbDeviceInfo>,
+        report: bool,
+    ) -> crate::Result<()> {
+        let current_map: HashMap<DeviceId, UsbDeviceInfo> = self
+            .scan_usb_devices()
+            .await?
+            .into_iter()
+            .map(|d| (d.device_id.clone(), d))
+            .collect();
+
+        if report {
+            self.report_differences(known_devices, &current_map).await;
+        }
+
+        *known_devices = current_map;
+        Ok(())
+    }
+
+    /// Reports devices that appeared, disappeared or changed between two
+    /// scans, returning the number of events delivered.
+    async fn report_differences(
+        &self,
+        known_devices: &HashMap<DeviceId, UsbDeviceInfo>,
+        current_map: &HashMap<DeviceId, UsbDeviceInfo>,
+    ) -> usize {
+        let mut events_sent = 0;
+
+        // Check for new and changed devices
+        for (id, device) in current_map {
+            let sent = match known_devices.get(id) {
+                None => {
+                    self.send_event(device.clone(), DeviceEventType::Connected)
+                        .await
+                }
+                Some(previous) => self.send_change(previous, device.clone()).await,
+            };
+            if sent {
+                events_sent += 1;
+            }
+        }
+
+        // Check for removed devices (disconnected)
+        for (id, device) in known_devices {
+            if !current_map.contains_key(id)
+                && self
+                    .send_event(device.clone(), DeviceEventType::Disconnected)
+                    .await
+            {
+                events_sent += 1;
+            }
+        }
+
+        events_sent
+    }
+
+    /// Polls sysfs for device changes when the uevent socket is unavailable.
+    async fn poll_devices(&self) -> crate::Result<()> {
         // Simple polling approach - check /sys/bus/usb/devices periodically
-        let mut known_devices: HashMap<String, UsbDeviceInfo> = HashMap::new();
-        let mut poll_interval = tokio::time::Duration::from_secs(1); // Start with 1s, adapt based on activity
+        let mut known_devices: HashMap<DeviceId, UsbDeviceInfo> = HashMap::new();
+        let mut poll_interval = PollInterval::new(&self.config);
+        let mut initial_scan = true;
 
         loop {
+            if self.stop.is_stopped() {
+                return Ok(());
+            }
+            if self.tx.is_closed() {
+                return Err(UsbWatchError::ChannelClosed);
+            }
+
             let scan_start = std::time::Instant::now();
 
             match self.scan_usb_devices().await {
                 Ok(current_devices) => {
-                    let current_map: HashMap<String, UsbDeviceInfo> = current_devices
+                    let current_map: HashMap<DeviceId, UsbDeviceInfo> = current_devices
                         .into_iter()
-                        .map(|d| {
-                            let key = format!(
-                                "{}:{}:{}",
-                                d.vendor_id,
-                                d.product_id,
-                                d.serial_number.as_deref().unwrap_or("unknown")
-                            );
-                            (key, d)
-                        })
+                        .map(|d| (d.device_id.clone(), d))
                         .collect();
 
-                    let mut events_sent = 0;
-
-                    // Check for new devices (connected)
-                    for (key, device) in &current_map {
-                        if !known_devices.contains_key(key) {
-                            let mut device_clone = device.clone();
-                            device_clone.event_type = DeviceEventType::Connected;
-                            if let Err(e) = self.tx.send(device_clone).await {
-                                eprintln!("Failed to send device event: {e}");
-                            } else {
-                                events_sent += 1;
-                            }
-                        }
-                    }
-
-                    // Check for removed devices (disconnected)
-                    for (key, device) in &known_devices {
-                        if !current_map.contains_key(key) {
-                            let mut device_clone = device.clone();
-                            device_clone.event_type = DeviceEventType::Disconnected;
-                            if let Err(e) = self.tx.send(device_clone).await {
-                                eprintln!("Failed to send device event: {e}");
-                            } else {
-                                events_sent += 1;
-                            }
-                        }
+                    // Optionally treat devices present at startup as already known
+                    if initial_scan && !self.config.emit_initial {
+                        known_devices.clone_from(&current_map);
                     }
+                    initial_scan = false;
 
+                    let events_sent = self.report_differences(&known_devices, &current_map).await;
                     known_devices = current_map;
 
                     // Adaptive polling: reduce interval if there's activity, increase if idle
-                    if events_sent > 0 {
-                        poll_interval = tokio::time::Duration::from_millis(500);
-                    // Fast polling when active
-                    } else {
-                        poll_interval = std::cmp::min(
-                            poll_interval + tokio::time::Duration::from_millis(100),
-                            tokio::time::Duration::from_secs(5), // Max 5 seconds when idle
-                        );
-                    }
+                    poll_interval.on_scan(events_sent);
                 }
                 Err(e) => {
                     eprintln!("Error scanning USB devices: {e}");
                     // Use exponential backoff on errors
-                    poll_interval =
-                        std::cmp::min(poll_interval * 2, tokio::time::Duration::from_secs(10));
+                    poll_interval.on_error();
                 }
             }
 
             // Ensure we don't scan too frequently
             let scan_duration = scan_start.elapsed();
-            if scan_duration < poll_interval {
-                tokio::time::sleep(poll_interval - scan_duration).await;
+            if scan_duration < poll_interval.current() {
+                tokio::select! {
+                    _ = self.stop.stopped() => return Ok(()),
+                    _ = self.tx.closed() => return Err(UsbWatchError::ChannelClosed),
+                    _ = tokio::time::sleep(poll_interval.current() - scan_duration) => {}
+                }
             }
         }
     }
 
-    async fn scan_usb_devices(&self) -> Result<Vec<UsbDeviceInfo>, String> {
+    /// Reports the differences between two copies of a device, returning
+    /// whether an event was delivered.
+    ///
+    /// Network interfaces and storage appear after the device connects, so a
+    /// change can make a device pass or fail the filter. Consumers then see
+    /// it connect or disconnect instead, as if it had only just been plugged
+    /// in or unplugged.
+    async fn send_change(&self, previous: &UsbDeviceInfo, device: UsbDeviceInfo) -> bool {
+        let passes = |device: &UsbDeviceInfo, event_type: DeviceEventType| {
+            let mut device = device.clone();
+            device.event_type = event_type;
+            self.config.filter.matches(&device)
+        };
+        if !passes(previous, DeviceEventType::Connected)
+            && passes(&device, DeviceEventType::Connected)
+        {
+            return self.send_event(device, DeviceEventType::Connected).await;
+        }
+        if passes(previous, DeviceEventType::Disconnected)
+            && !passes(&device, DeviceEventType::Disconnected)
+        {
+            // Report the device as it was last shown, which passes the filter
+            let mut shown = previous.clone();
+            shown.timestamp = device.timestamp;
+            return self.send_event(shown, DeviceEventType::Disconnected).await;
+        }
+
+        let changes = device.changes_from(previous);
+        if changes.is_empty() {
+            return false;
+        }
+        self.send_event(device, DeviceEventType::Changed(changes))
+            .await
+    }
+
+    /// Sends a device event unless the filter rejects it, returning whether it was delivered.
+    async fn send_event(&self, mut device: UsbDeviceInfo, event_type: DeviceEventType) -> bool {
+        device.event_type = event_type;
+        if !self.config.filter.matches(&device) {
+            return false;
+        }
+        match self.tx.send(device).await {
+            Ok(()) => true,
+            Err(e) => {
+                eprintln!("Failed to send device event: {e}");
+                false
+            }
+        }
+    }
+
+    async fn scan_usb_devices(&self) -> crate::Result<Vec<UsbDeviceInfo>> {
         let mut devices = Vec::new();
-        let usb_devices_path = Path::new("/sys/bus/usb/devices");
+        let usb_devices_path = self.usb_devices_path();
 
         if !usb_devices_path.exists() {
-            return Err(
-                "USB devices path not found. Make sure you're running on Linux with USB support."
-                    .to_string(),
-            );
+            return Err(UsbWatchError::SysfsUnavailable {
+                path: usb_devices_path,
+            });
         }
 
-        let mut entries = fs::read_dir(usb_devices_path)
-            .await
-            .map_err(|e| e.to_string())?;
+        let read_failed =
+            |e| UsbWatchError::io(format!("Failed to read {}", usb_devices_path.display()), e);
+        let mut entries = fs::read_dir(&usb_devices_path).await.map_err(read_failed)?;
 
-        while let Some(entry) = entries.next_entry().await.map_err(|e| e.to_string())? {
+        while let Some(entry) = entries.next_entry().await.map_err(read_failed)? {
             let path = entry.path();
 
             // Skip entries that don't look like USB devices (e.g., usb1, usb2, etc.)
@@ -166,13 +950,11 @@ impl LinuxUsbWatcher {
                 let is_interface = name.contains(':');
 
                 if is_device && !is_interface {
-                    if let Ok(device_info) = self.parse_usb_device(&path).await {
+                    if let Some(device_info) = self.parse_usb_device(&path).await {
                         // Skip devices with all zero VID/PID (typically means no actual device info)
-                        if device_info.vendor_id != "0000" || device_info.product_id != "0000" {
+                        if device_info.vendor_id.0 != 0 || device_info.product_id.0 != 0 {
                             devices.push(device_info);
                         }
-                    } else {
-                        println!("Failed to parse device: {name}");
                     }
                 }
             }
@@ -181,15 +963,21 @@ impl LinuxUsbWatcher {
         Ok(devices)
     }
 
-    async fn parse_usb_device(&self, device_path: &Path) -> Result<UsbDeviceInfo, String> {
+    /// Reads a device from sysfs.
+    ///
+    /// Returns `None` if its vendor or product ID can't be read, e.g. because
+    /// it was unplugged while being read.
+    async fn parse_usb_device(&self, device_path: &Path) -> Option<UsbDeviceInfo> {
         let vendor_id = self
             .read_sys_file(device_path, "idVendor")
-            .await
-            .unwrap_or_else(|| "0000".to_string());
+            .await?
+            .parse()
+            .ok()?;
         let product_id = self
             .read_sys_file(device_path, "idProduct")
-            .await
-            .unwrap_or_else(|| "0000".to_string());
+            .await?
+            .parse()
+            .ok()?;
 
         let product_name = self
             .read_sys_file(device_path, "product")
@@ -209,20 +997,268 @@ impl LinuxUsbWatcher {
             "Unknown Device".to_string()
         };
 
+        let device_nodes = self.find_device_nodes(device_path).await;
         let device_handle = DeviceHandle::Linux {
             sysfs_path: device_path.to_string_lossy().to_string(),
-            device_node: None, // Could be enhanced to detect device nodes
+            device_nodes: device_nodes.clone(),
         };
 
-        Ok(UsbDeviceInfo::with_handle(
+        let mut device_info = UsbDeviceInfo::with_handle(
             device_name,
             vendor_id,
             product_id,
             serial_number,
             DeviceEventType::Connected, // Will be updated by caller
             device_handle,
+        );
+        if let Some(name) = device_path.file_name().and_then(|n| n.to_str()) {
+            device_info.set_location(name);
+            apply_topology(&mut device_info, name);
+        }
+        device_info.device_class = self.read_class(device_path, "bDevice").await;
+        // sysfs shows bcdUSB already formatted, e.g. " 2.10"
+        device_info.usb_version = self.read_sys_value(device_path, "version").await;
+        device_info.device_version = self
+            .read_sys_file(device_path, "bcdDevice")
+            .await
+            .and_then(|bcd| u16::from_str_radix(&bcd, 16).ok())
+            .map(BcdVersion::from_bcd);
+        device_info.speed = self.read_sys_value(device_path, "speed").await;
+        // e.g. "500mA", already scaled by the 2 or 8 mA unit of the descriptor
+        device_info.max_power_ma = self
+            .read_sys_file(device_path, "bMaxPower")
+            .await
+            .and_then(|power| power.trim_end_matches("mA").parse().ok());
+        // Empty while the device is unconfigured
+        device_info.configuration = self
+            .read_sys_value(device_path, "bConfigurationValue")
+            .await;
+        device_info.num_interfaces = self.read_sys_value(device_path, "bNumInterfaces").await;
+        device_info.max_child = self.read_sys_value(device_path, "maxchild").await;
+        device_info.removable = match self.read_sys_file(device_path, "removable").await {
+            Some(value) if value == "removable" => Some(true),
+            Some(value) if value == "fixed" => Some(false),
+            _ => None,
+        };
+        device_info.authorized = self
+            .read_sys_file(device_path, "authorized")
+            .await
+            .map(|value| value != "0");
+        device_info.interfaces = self.read_interfaces(device_path).await;
+        device_info.device_nodes = device_nodes;
+        device_info.storage = self.read_storage(device_path).await;
+        device_info.network_interfaces = self.read_network_interfaces(device_path).await;
+        if let Some(ids) = &self.config.usb_ids {
+            ids.annotate(&mut device_info);
+        }
+
+        Some(device_info)
+    }
+
+    /// Returns the `/dev` nodes created for a device and its interfaces, sorted.
+    ///
+    /// Every sysfs directory backing a device node has a `uevent` file naming
+    /// the node, so this reads the device's own and then walks the interface
+    /// directories looking for the kinds of nodes listed in
+    /// [`DEVICE_NODE_PREFIXES`].
+    async fn find_device_nodes(&self, device_path: &Path) -> Vec<String> {
+        let mut nodes = Vec::new();
+        if let Some(node) = read_device_node(device_path).await {
+            nodes.push(node);
+        }
+
+        let mut pending: Vec<(PathBuf, usize)> = Vec::new();
+        if let Ok(mut entries) = fs::read_dir(device_path).await {
+            while let Ok(Some(entry)) = entries.next_entry().await {
+                if entry.file_name().to_string_lossy().contains(':') {
+                    pending.push((entry.path(), 0));
+                }
+            }
+        }
+
+        while let Some((dir, depth)) = pending.pop() {
+            if let Some(node) = read_device_node(&dir).await {
+                nodes.push(node);
+            }
+            if depth == DEVICE_NODE_SEARCH_DEPTH {
+                continue;
+            }
+
+            let Ok(mut entries) = fs::read_dir(&dir).await else {
+                continue;
+            };
+            while let Ok(Some(entry)) = entries.next_entry().await {
+                // Don't follow symlinks such as "driver" and "subsystem", which lead
+                // back up the tree
+                if entry.file_type().await.is_ok_and(|t| t.is_dir()) {
+                    pending.push((entry.path(), depth + 1));
+                }
+            }
+        }
+
+        nodes.sort();
+        nodes.dedup();
+        nodes
+    }
+
+    /// Reads the disks of a storage device by following the SCSI host chain
+    /// under its interfaces, see [`BLOCK_DEVICE_CHAIN`].
+    async fn read_storage(&self, device_path: &Path) -> Vec<BlockDevice> {
+        let mut dirs = vec![device_path.to_path_buf()];
+        for step in BLOCK_DEVICE_CHAIN {
+            let mut next = Vec::new();
+            for dir in &dirs {
+                next.extend(subdirectories(dir, step).await);
+            }
+            dirs = next;
+        }
+        if dirs.is_empty() {
+            return Vec::new();
+        }
+
+        let mounts = read_mounts(&self.config.mountinfo_path).await;
+        let mut disks = Vec::new();
+        for disk_path in dirs {
+            let Some(mut disk) = self.read_block_device(&disk_path, &mounts).await else {
+                continue;
+            };
+            let mut partitions = Vec::new();
+            // Partitions are the subdirectories with a partition number
+            for path in subdirectories(&disk_path, |_| true).await {
+                let Some(number) = self.read_sys_value::<u32>(&path, "partition").await else {
+                    continue;
+                };
+                if let Some(partition) = self.read_block_device(&path, &mounts).await {
+                    partitions.push((number, partition));
+                }
+            }
+            partitions.sort_by_key(|(number, _)| *number);
+            disk.partitions = partitions
+                .into_iter()
+                .map(|(_, partition)| partition)
+                .collect();
+            disks.push(disk);
+        }
+        disks.sort_by(|a, b| a.path.cmp(&b.path));
+        disks
+    }
+
+    /// Reads a disk or partition, leaving out any partitions.
+    async fn read_block_device(&self, path: &Path, mounts: &[Mount]) -> Option<BlockDevice> {
+        let name = path.file_name()?.to_string_lossy();
+        // "major:minor", which names the device in the udev database and mount table
+        let dev = self.read_sys_file(path, "dev").await?;
+        let udev = read_udev_properties(&self.config.udev_data_dir.join(format!("b{dev}"))).await;
+        let mounted: Vec<&Mount> = mounts.iter().filter(|mount| mount.dev == dev).collect();
+
+        Some(BlockDevice {
+            path: format!("/dev/{name}"),
+            size_bytes: self
+                .read_sys_value::<u64>(path, "size")
+                .await
+                .map(|sectors| sectors * SECTOR_SIZE),
+            filesystem: udev
+                .get("ID_FS_TYPE")
+                .cloned()
+                .or_else(|| Some(mounted.first()?.filesystem.clone())),
+            // The plain label has spaces and other special characters replaced
+            label: udev
+                .get("ID_FS_LABEL_ENC")
+                .map(|label| unescape(label, "\\x", 2, 16))
+                .or_else(|| udev.get("ID_FS_LABEL").cloned()),
+            uuid: udev.get("ID_FS_UUID").cloned(),
+            mount_points: mounted
+                .iter()
+                .map(|mount| mount.mount_point.clone())
+                .collect(),
+            partitions: Vec::new(),
+        })
+    }
+
+    /// Reads the interfaces of the active configuration, ordered by interface number.
+    async fn read_interfaces(&self, device_path: &Path) -> Vec<UsbInterface> {
+        let mut interfaces = Vec::new();
+        let Ok(mut entries) = fs::read_dir(device_path).await else {
+            return interfaces;
+        };
+
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            // Interfaces are named "<device>:<config>.<interface>", e.g. "1-1:1.0"
+            if !entry.file_name().to_string_lossy().contains(':') {
+                continue;
+            }
+            let path = entry.path();
+            let Some(number) = self.read_hex_u8(&path, "bInterfaceNumber").await else {
+                continue;
+            };
+            let Some(class) = self.read_class(&path, "bInterface").await else {
+                continue;
+            };
+            let driver = fs::read_link(path.join("driver"))
+                .await
+                .ok()
+                .and_then(|target| Some(target.file_name()?.to_string_lossy().to_string()));
+
+            interfaces.push(UsbInterface {
+                number,
+                class,
+                driver,
+            });
+        }
+
+        interfaces.sort_by_key(|interface| interface.number);
+        interfaces
+    }
+
+    /// Reads the network interfaces registered below the device's interfaces,
+    /// e.g. `1-1:1.0/net/usb0`, ordered by USB interface.
+    async fn read_network_interfaces(&self, device_path: &Path) -> Vec<NetworkInterface> {
+        let mut network_interfaces = Vec::new();
+        for path in subdirectories(device_path, |name| name.contains(':')).await {
+            let Some(usb_interface) = self.read_hex_u8(&path, "bInterfaceNumber").await else {
+                continue;
+            };
+            for net_path in subdirectories(&path.join("net"), |_| true).await {
+                let Some(name) = net_path.file_name() else {
+                    continue;
+                };
+                network_interfaces.push(NetworkInterface {
+                    name: name.to_string_lossy().to_string(),
+                    // Raw IP interfaces of modems report an all-zero address
+                    mac_address: self
+                        .read_sys_file(&net_path, "address")
+                        .await
+                        .filter(|address| address.chars().any(|c| c != '0' && c != ':')),
+                    usb_interface,
+                });
+            }
+        }
+        network_interfaces.sort_by_key(|interface| interface.usb_interface);
+        network_interfaces
+    }
+
+    /// Reads a class triple from the `<prefix>Class`, `<prefix>SubClass` and
+    /// `<prefix>Protocol` attributes.
+    async fn read_class(&self, path: &Path, prefix: &str) -> Option<UsbClass> {
+        Some(UsbClass::new(
+            self.read_hex_u8(path, &format!("{prefix}Class")).await?,
+            self.read_hex_u8(path, &format!("{prefix}SubClass")).await?,
+            self.read_hex_u8(path, &format!("{prefix}Protocol")).await?,
         ))
     }
+
+    /// Reads a descriptor field that sysfs prints as two hex digits, e.g. "08".
+    async fn read_hex_u8(&self, path: &Path, filename: &str) -> Option<u8> {
+        let value = self.read_sys_file(path, filename).await?;
+        u8::from_str_radix(&value, 16).ok()
+    }
+
+    /// Reads an attribute that sysfs prints in decimal or another form
+    /// [`FromStr`] understands.
+    async fn read_sys_value<T: FromStr>(&self, path: &Path, filename: &str) -> Option<T> {
+        self.read_sys_file(path, filename).await?.parse().ok()
+    }
+
     async fn read_sys_file(&self, device_path: &Path, filename: &str) -> Option<String> {
         let file_path = device_path.join(filename);
         fs::read_to_string(file_path)
@@ -233,6 +1269,231 @@ impl LinuxUsbWatcher {
     }
 }
 
+/// Devices tracked while listening for uevents.
+#[cfg(target_os = "linux")]
+#[derive(Default)]
+struct UeventState {
+    /// Devices that have been reported as connected
+    devices: HashMap<DeviceId, UsbDeviceInfo>,
+    /// Sysfs names of devices that were added but not yet bound to a driver,
+    /// with when to report them if no bind uevent arrives
+    awaiting_bind: HashMap<String, Instant>,
+}
+
+/// Returns the `/dev` node named in a sysfs directory's `uevent` file, if it is
+/// one of the kinds reported for USB devices.
+#[cfg(target_os = "linux")]
+async fn read_device_node(sysfs_dir: &Path) -> Option<String> {
+    let contents = fs::read_to_string(sysfs_dir.join("uevent")).await.ok()?;
+    let devname = contents
+        .lines()
+        .find_map(|line| line.strip_prefix("DEVNAME="))?;
+    DEVICE_NODE_PREFIXES
+        .iter()
+        .any(|prefix| devname.starts_with(prefix))
+        .then(|| format!("/dev/{devname}"))
+}
+
+/// Returns the subdirectories of `dir` whose names `accept` approves, sorted.
+///
+/// Symlinks such as "driver" and "subsystem" are not followed.
+#[cfg(target_os = "linux")]
+async fn subdirectories(dir: &Path, accept: impl Fn(&str) -> bool) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    let Ok(mut entries) = fs::read_dir(dir).await else {
+        return dirs;
+    };
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        if accept(&entry.file_name().to_string_lossy())
+            && entry.file_type().await.is_ok_and(|t| t.is_dir())
+        {
+            dirs.push(entry.path());
+        }
+    }
+    dirs.sort();
+    dirs
+}
+
+/// An entry of the mount table.
+#[cfg(target_os = "linux")]
+struct Mount {
+    /// "major:minor" of the mounted device
+    dev: String,
+    mount_point: String,
+    filesystem: String,
+}
+
+/// Reads a mount table in the format of `/proc/self/mountinfo`, e.g.
+/// `36 25 8:17 / /media/usb rw,nosuid shared:1 - vfat /dev/sdb1 rw`.
+///
+/// A missing or unreadable table counts as empty.
+#[cfg(target_os = "linux")]
+async fn read_mounts(path: &Path) -> Vec<Mount> {
+    let Ok(table) = fs::read_to_string(path).await else {
+        return Vec::new();
+    };
+    table
+        .lines()
+        .filter_map(|line| {
+            // Optional fields precede the separator, so count from both ends
+            let (fields, rest) = line.split_once(" - ")?;
+            let mut fields = fields.split(' ');
+            let dev = fields.nth(2)?;
+            let mount_point = fields.nth(1)?;
+            Some(Mount {
+                dev: dev.to_string(),
+                mount_point: unescape(mount_point, "\\", 3, 8),
+                filesystem: rest.split(' ').next()?.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Reads the `E:KEY=VALUE` properties of a udev database entry.
+///
+/// A missing entry, e.g. without udev, has no properties.
+#[cfg(target_os = "linux")]
+async fn read_udev_properties(path: &Path) -> HashMap<String, String> {
+    let Ok(entry) = fs::read_to_string(path).await else {
+        return HashMap::new();
+    };
+    entry
+        .lines()
+        .filter_map(|line| line.strip_prefix("E:")?.split_once('='))
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
+
+/// Decodes the byte escapes in a string, such as the octal `\040` for a space
+/// in mount points or udev's hex `\x20`.
+#[cfg(target_os = "linux")]
+fn unescape(value: &str, marker: &str, digits: usize, radix: u32) -> String {
+    let mut bytes = Vec::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find(marker) {
+        bytes.extend_from_slice(&rest.as_bytes()[..start]);
+        let code = &rest[start + marker.len()..];
+        match code
+            .get(..digits)
+            .and_then(|code| u8::from_str_radix(code, radix).ok())
+        {
+            Some(byte) => {
+                bytes.push(byte);
+                rest = &code[digits..];
+            }
+            None => {
+                bytes.extend_from_slice(marker.as_bytes());
+                rest = code;
+            }
+        }
+    }
+    bytes.extend_from_slice(rest.as_bytes());
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// Returns the sysfs entry name (e.g., "1-1") a scanned device was read from.
+#[cfg(target_os = "linux")]
+fn sysfs_name(device: &UsbDeviceInfo) -> Option<String> {
+    match &device.device_handle {
+        DeviceHandle::Linux { sysfs_path, .. } => Path::new(sysfs_path)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string()),
+        _ => None,
+    }
+}
+
+/// Builds device information for a removed device from its uevent alone.
+///
+/// Used when a device disappears before we managed to read it from sysfs. The
+/// `PRODUCT` property has the form `vid/pid/bcdDevice` in unpadded hex.
+#[cfg(target_os = "linux")]
+fn device_from_uevent(uevent: &Uevent, usb_devices_path: &Path) -> Option<UsbDeviceInfo> {
+    let mut product = uevent.properties.get("PRODUCT")?.split('/');
+    let vendor_id = VendorId(u16::from_str_radix(product.next()?, 16).ok()?);
+    let product_id = ProductId(u16::from_str_radix(product.next()?, 16).ok()?);
+    let device_version = product
+        .next()
+        .and_then(|bcd| u16::from_str_radix(bcd, 16).ok())
+        .map(BcdVersion::from_bcd);
+
+    let device_nodes: Vec<String> = uevent
+        .properties
+        .get("DEVNAME")
+        .map(|name| format!("/dev/{name}"))
+        .into_iter()
+        .collect();
+    let device_handle = DeviceHandle::Linux {
+        sysfs_path: usb_devices_path
+            .join(uevent.sysfs_name()?)
+            .to_string_lossy()
+            .to_string(),
+        device_nodes: device_nodes.clone(),
+    };
+
+    let mut device_info = UsbDeviceInfo::with_handle(
+        "Unknown Device".to_string(),
+        vendor_id,
+        product_id,
+        None,
+        DeviceEventType::Disconnected,
+        device_handle,
+    );
+    device_info.device_nodes = device_nodes;
+    device_info.device_version = device_version;
+    let name = uevent.sysfs_name()?;
+    device_info.set_location(name);
+    apply_topology(&mut device_info, name);
+    Some(device_info)
+}
+
+/// Fills in the bus number, port path and parent encoded in a sysfs device name.
+///
+/// Root hubs are named `usb<bus>`; other devices `<bus>-<port>[.<port>...]`,
+/// where the ports lead from the root hub through any intermediate hubs.
+#[cfg(target_os = "linux")]
+fn apply_topology(device_info: &mut UsbDeviceInfo, name: &str) {
+    if let Some(bus) = name.strip_prefix("usb") {
+        device_info.bus_number = bus.parse().ok();
+        return;
+    }
+
+    let Some((bus, port_path)) = name.split_once('-') else {
+        return;
+    };
+    let Ok(bus) = bus.parse::<u8>() else {
+        return;
+    };
+    if port_path.contains(':') {
+        return;
+    }
+
+    device_info.bus_number = Some(bus);
+    device_info.port_path = Some(port_path.to_string());
+    device_info.parent = Some(match port_path.rsplit_once('.') {
+        Some((upstream, _)) => format!("{bus}-{upstream}"),
+        None => format!("usb{bus}"),
+    });
+}
+
+#[cfg(target_os = "linux")]
+impl UsbBackend for LinuxUsbWatcher {
+    fn enumerate(&self) -> BackendFuture<'_, Vec<UsbDeviceInfo>> {
+        Box::pin(self.list_devices())
+    }
+
+    fn watch(&self) -> BackendFuture<'_, ()> {
+        Box::pin(self.start_monitoring())
+    }
+
+    fn stop_handle(&self) -> StopHandle {
+        self.stop.clone()
+    }
+
+    fn device_tree(&self) -> BackendFuture<'_, Vec<UsbTreeNode>> {
+        Box::pin(LinuxUsbWatcher::device_tree(self))
+    }
+}
+
 #[cfg(not(target_os = "linux"))]
 pub struct LinuxUsbWatcher;
 
@@ -242,7 +1503,9 @@ impl LinuxUsbWatcher {
         Self
     }
 
-    pub async fn start_monitoring(&self) -> Result<(), String> {
-        Err("Linux USB monitoring not available on this platform".to_string())
+    pub async fn start_monitoring(&self) -> crate::Result<()> {
+        Err(crate::error::UsbWatchError::Unsupported(
+            "Linux USB monitoring not available on this platform".to_string(),
+        ))
     }
 }
@@ -3,7 +3,13 @@
 //! Uses IOKit FFI to detect USB device events in real time. Supports coloured output and modern CLI integration.
 
 #[cfg(target_os = "macos")]
-use crate::device_info::{DeviceEventType, DeviceHandle, UsbDeviceInfo};
+use super::{BackendFuture, StopHandle, UsbBackend, WatcherConfig};
+#[cfg(target_os = "macos")]
+use crate::device_info::{
+    BcdVersion, DeviceEventType, DeviceHandle, ProductId, UsbClass, UsbDeviceInfo, VendorId,
+};
+#[cfg(target_os = "macos")]
+use crate::error::UsbWatchError;
 #[cfg(target_os = "macos")]
 use core_foundation::base::CFRelease;
 #[cfg(target_os = "macos")]
@@ -26,6 +32,9 @@ use tokio::sync::mpsc;
 /// on macOS, sending events through a Tokio channel.
 pub struct MacosUsbWatcher {
     tx: mpsc::Sender<UsbDeviceInfo>,
+    // Boxed to keep the `UsbWatcher` variants about the same size
+    config: Box<WatcherConfig>,
+    stop: StopHandle,
 }
 
 #[cfg(target_os = "macos")]
@@ -36,7 +45,26 @@ impl MacosUsbWatcher {
     ///
     /// * `tx` - Tokio channel sender for publishing USB device events.
     pub fn new(tx: mpsc::Sender<UsbDeviceInfo>) -> Self {
-        Self { tx }
+        Self::with_config(tx, WatcherConfig::default())
+    }
+
+    /// Creates a new `MacosUsbWatcher` with the given options.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx` - Tokio channel sender for publishing USB device events.
+    /// * `config` - Watcher options; only `emit_initial` and `filter` apply to the current enumeration.
+    pub fn with_config(tx: mpsc::Sender<UsbDeviceInfo>, config: WatcherConfig) -> Self {
+        Self {
+            tx,
+            config: Box::new(config),
+            stop: StopHandle::new(),
+        }
+    }
+
+    /// Returns a handle that stops the enumeration early.
+    pub fn stop_handle(&self) -> StopHandle {
+        self.stop.clone()
     }
 
     /// Starts monitoring USB devices on macOS.
@@ -47,19 +75,46 @@ impl MacosUsbWatcher {
     /// # Errors
     ///
     /// Returns an error if IOKit FFI calls fail or device enumeration cannot be performed.
-    pub async fn start_monitoring(&self) -> Result<(), String> {
+    pub async fn start_monitoring(&self) -> crate::Result<()> {
         println!("Starting USB device monitoring on macOS...");
+        let devices = self.list_devices().await?;
+
+        if self.config.emit_initial {
+            for info in devices {
+                if self.stop.is_stopped() {
+                    break;
+                }
+                if self.tx.send(info).await.is_err() {
+                    return Err(UsbWatchError::ChannelClosed);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Enumerates the USB devices currently connected.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if IOKit FFI calls fail or device enumeration cannot be performed.
+    pub async fn list_devices(&self) -> crate::Result<Vec<UsbDeviceInfo>> {
+        let mut devices = Vec::new();
+
         // SAFETY: FFI calls to IOKit
         unsafe {
             let matching_dict = IOServiceMatching(b"IOUSBDevice\0".as_ptr() as *const i8);
             if matching_dict.is_null() {
-                return Err("Failed to create matching dictionary for IOUSBDevice".to_string());
+                return Err(UsbWatchError::Platform(
+                    "Failed to create matching dictionary for IOUSBDevice".to_string(),
+                ));
             }
 
             let mut iter: io_iterator_t = 0;
             let kr = IOServiceGetMatchingServices(kIOMasterPortDefault, matching_dict, &mut iter);
             if kr != 0 {
-                return Err(format!("IOServiceGetMatchingServices failed: {kr}"));
+                return Err(UsbWatchError::Platform(format!(
+                    "IOServiceGetMatchingServices failed: {kr}"
+                )));
             }
 
             loop {
@@ -76,40 +131,58 @@ impl MacosUsbWatcher {
                         .to_string_lossy()
                         .into_owned()
                 } else {
-                    "Unknown USB Device".to_string()
+                    "Unknown Device".to_string()
                 };
 
                 // Extract vendor and product IDs from device properties
-                let vendor_id = self
-                    .get_device_property_u16(device, b"idVendor\0")
-                    .map(|id| format!("{:04x}", id))
-                    .unwrap_or_else(|| "0000".to_string());
-
-                let product_id = self
-                    .get_device_property_u16(device, b"idProduct\0")
-                    .map(|id| format!("{:04x}", id))
-                    .unwrap_or_else(|| "0000".to_string());
+                let (Some(vendor_id), Some(product_id)) = (
+                    self.get_device_property_u16(device, b"idVendor\0"),
+                    self.get_device_property_u16(device, b"idProduct\0"),
+                ) else {
+                    IOObjectRelease(device);
+                    continue;
+                };
 
                 // Try to get serial number
                 let serial_number = self.get_device_property_string(device, b"USB Serial Number\0");
 
-                let info = UsbDeviceInfo {
+                let mut device_info = UsbDeviceInfo::with_handle(
                     device_name,
-                    vendor_id,
-                    product_id,
+                    VendorId(vendor_id),
+                    ProductId(product_id),
                     serial_number,
-                    timestamp: chrono::Utc::now(),
-                    event_type: DeviceEventType::Connected,
-                    device_handle: DeviceHandle::Macos {
+                    DeviceEventType::Connected,
+                    DeviceHandle::Macos {
                         device_id: format!("{device}"),
                     },
-                };
-                let _ = self.tx.send(info).await;
+                );
+                let class = self.get_device_property_u16(device, b"bDeviceClass\0");
+                let subclass = self.get_device_property_u16(device, b"bDeviceSubClass\0");
+                let protocol = self.get_device_property_u16(device, b"bDeviceProtocol\0");
+                if let (Some(class), Some(subclass), Some(protocol)) = (class, subclass, protocol) {
+                    device_info.device_class =
+                        Some(UsbClass::new(class as u8, subclass as u8, protocol as u8));
+                }
+                device_info.usb_version = self
+                    .get_device_property_u16(device, b"bcdUSB\0")
+                    .map(BcdVersion::from_bcd);
+                device_info.device_version = self
+                    .get_device_property_u16(device, b"bcdDevice\0")
+                    .map(BcdVersion::from_bcd);
+                // The location ID encodes the bus and port path, e.g. 0x14100000
+                if let Some(location_id) = self.get_device_property_u32(device, b"locationID\0") {
+                    device_info.set_location(&format!("{location_id:#010x}"));
+                }
+                if let Some(ids) = &self.config.usb_ids {
+                    ids.annotate(&mut device_info);
+                }
+                devices.push(device_info);
                 IOObjectRelease(device);
             }
             IOObjectRelease(iter);
         }
-        Ok(())
+        devices.retain(|device| self.config.filter.matches(device));
+        Ok(devices)
     }
 
     /// Helper function to get a 16-bit integer property from an IOKit device
@@ -150,6 +223,40 @@ impl MacosUsbWatcher {
         }
     }
 
+    /// Helper function to get a 32-bit integer property from an IOKit device
+    unsafe fn get_device_property_u32(
+        &self,
+        device: io_object_t,
+        property_name: &[u8],
+    ) -> Option<u32> {
+        let prop_name = core_foundation::string::CFString::from_static_string(
+            std::str::from_utf8(property_name)
+                .ok()?
+                .trim_end_matches('\0'),
+        );
+        let prop = IORegistryEntryCreateCFProperty(
+            device,
+            prop_name.as_concrete_TypeRef(),
+            std::ptr::null_mut(),
+            0,
+        );
+
+        if prop.is_null() {
+            return None;
+        }
+
+        // Convert CFNumber to u32
+        let cf_number = prop as core_foundation::number::CFNumberRef;
+        let mut value: u32 = 0;
+        let converted = core_foundation::number::CFNumberGetValue(
+            cf_number,
+            core_foundation::number::kCFNumberSInt32Type,
+            &mut value as *mut u32 as *mut std::ffi::c_void,
+        );
+        core_foundation::base::CFRelease(prop);
+        converted.then_some(value)
+    }
+
     /// Helper function to get a string property from an IOKit device
     unsafe fn get_device_property_string(
         &self,
@@ -184,3 +291,18 @@ impl MacosUsbWatcher {
         }
     }
 }
+
+#[cfg(target_os = "macos")]
+impl UsbBackend for MacosUsbWatcher {
+    fn enumerate(&self) -> BackendFuture<'_, Vec<UsbDeviceInfo>> {
+        Box::pin(self.list_devices())
+    }
+
+    fn watch(&self) -> BackendFuture<'_, ()> {
+        Box::pin(self.start_monitoring())
+    }
+
+    fn stop_handle(&self) -> StopHandle {
+        self.stop.clone()
+    }
+}
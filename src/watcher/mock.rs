@@ -0,0 +1,206 @@
+//! Scripted backend for running applications without USB hardware.
+
+use super::{BackendFuture, StopHandle, UsbBackend, WatcherConfig};
+use crate::device_info::{DeviceEventType, UsbDeviceInfo};
+use crate::error::UsbWatchError;
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// A [`UsbBackend`] that replays a scripted sequence of events.
+///
+/// Devices added with [`device`](Self::device) are connected from the start:
+/// they are returned by [`enumerate`](UsbBackend::enumerate) and, unless
+/// [`emit_initial`](WatcherConfig::emit_initial) is off, reported as
+/// `Connected` when watching starts. [`watch`](UsbBackend::watch) then sends
+/// the events added with [`event`](Self::event) in order, waiting wherever a
+/// [`delay`](Self::delay) was scripted, and returns once the script is done
+/// or the watcher is stopped. The watcher's [filter](WatcherConfig::filter)
+/// applies as it does for the platform backends.
+///
+/// # Examples
+///
+/// ```
+/// use usbwatch_rs::watcher::MockBackend;
+/// use usbwatch_rs::{DeviceEventType, ProductId, UsbDeviceInfo, UsbWatcher, VendorId};
+/// use std::time::Duration;
+/// use tokio::sync::mpsc;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let keyboard = UsbDeviceInfo::new(
+///     "Logitech Keyboard".to_string(),
+///     VendorId(0x046d),
+///     ProductId(0xc31c),
+///     None,
+///     DeviceEventType::Connected,
+/// );
+/// let mut unplugged = keyboard.clone();
+/// unplugged.event_type = DeviceEventType::Disconnected;
+///
+/// let (tx, mut rx) = mpsc::channel(10);
+/// let backend = MockBackend::new(tx)
+///     .device(keyboard)
+///     .delay(Duration::from_millis(10))
+///     .event(unplugged);
+/// let watcher = UsbWatcher::with_backend(backend);
+///
+/// assert_eq!(watcher.list_devices().await?.len(), 1);
+/// watcher.start_monitoring().await?;
+///
+/// assert_eq!(rx.recv().await.unwrap().event_type, DeviceEventType::Connected);
+/// assert_eq!(rx.recv().await.unwrap().event_type, DeviceEventType::Disconnected);
+/// assert!(watcher.list_devices().await?.is_empty());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct MockBackend {
+    tx: mpsc::Sender<UsbDeviceInfo>,
+    config: WatcherConfig,
+    connected: Mutex<Vec<UsbDeviceInfo>>,
+    script: Vec<MockStep>,
+    stop: StopHandle,
+}
+
+#[derive(Debug, Clone)]
+enum MockStep {
+    Event(Box<UsbDeviceInfo>),
+    Delay(Duration),
+}
+
+impl MockBackend {
+    /// Creates a mock backend without devices or events.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx` - Channel sender for publishing the scripted events
+    pub fn new(tx: mpsc::Sender<UsbDeviceInfo>) -> Self {
+        Self::with_config(tx, WatcherConfig::default())
+    }
+
+    /// Creates a mock backend with the given options.
+    ///
+    /// Only [`emit_initial`](WatcherConfig::emit_initial) and
+    /// [`filter`](WatcherConfig::filter) are used.
+    pub fn with_config(tx: mpsc::Sender<UsbDeviceInfo>, config: WatcherConfig) -> Self {
+        Self {
+            tx,
+            config,
+            connected: Mutex::new(Vec::new()),
+            script: Vec::new(),
+            stop: StopHandle::new(),
+        }
+    }
+
+    /// Adds a device that is connected from the start.
+    pub fn device(self, mut device: UsbDeviceInfo) -> Self {
+        device.event_type = DeviceEventType::Connected;
+        lock_connected(&self.connected).push(device);
+        self
+    }
+
+    /// Appends an event to the script.
+    ///
+    /// `Connected` and `Disconnected` events also update the devices returned
+    /// by [`enumerate`](UsbBackend::enumerate) once they have been sent.
+    pub fn event(mut self, event: UsbDeviceInfo) -> Self {
+        self.script.push(MockStep::Event(Box::new(event)));
+        self
+    }
+
+    /// Appends a pause to the script.
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.script.push(MockStep::Delay(delay));
+        self
+    }
+
+    async fn send(&self, device: UsbDeviceInfo) -> crate::Result<()> {
+        if !self.config.filter.matches(&device) {
+            return Ok(());
+        }
+        self.tx
+            .send(device)
+            .await
+            .map_err(|_| UsbWatchError::ChannelClosed)
+    }
+}
+
+impl UsbBackend for MockBackend {
+    fn enumerate(&self) -> BackendFuture<'_, Vec<UsbDeviceInfo>> {
+        Box::pin(async move {
+            let mut devices = lock_connected(&self.connected).clone();
+            devices.retain(|device| self.config.filter.matches(device));
+            Ok(devices)
+        })
+    }
+
+    fn watch(&self) -> BackendFuture<'_, ()> {
+        Box::pin(async move {
+            if self.config.emit_initial {
+                let initial = lock_connected(&self.connected).clone();
+                for device in initial {
+                    if self.stop.is_stopped() {
+                        return Ok(());
+                    }
+                    self.send(device).await?;
+                }
+            }
+
+            for step in &self.script {
+                if self.stop.is_stopped() {
+                    return Ok(());
+                }
+                match step {
+                    MockStep::Delay(delay) => {
+                        tokio::select! {
+                            _ = self.stop.stopped() => return Ok(()),
+                            _ = tokio::time::sleep(*delay) => {}
+                        }
+                    }
+                    MockStep::Event(event) => {
+                        track_connected(&mut lock_connected(&self.connected), event);
+                        self.send(UsbDeviceInfo::clone(event)).await?;
+                    }
+                }
+            }
+            Ok(())
+        })
+    }
+
+    fn stop_handle(&self) -> StopHandle {
+        self.stop.clone()
+    }
+}
+
+/// Locks a list of connected devices.
+pub(super) fn lock_connected(
+    connected: &Mutex<Vec<UsbDeviceInfo>>,
+) -> MutexGuard<'_, Vec<UsbDeviceInfo>> {
+    // The list is always left consistent, so a panic elsewhere doesn't invalidate it
+    connected
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Records the effect of an event on a list of connected devices.
+pub(super) fn track_connected(connected: &mut Vec<UsbDeviceInfo>, event: &UsbDeviceInfo) {
+    let existing = connected
+        .iter()
+        .position(|device| device.device_id == event.device_id);
+    match (&event.event_type, existing) {
+        (DeviceEventType::Connected | DeviceEventType::Changed(_), Some(index)) => {
+            connected[index] = event.clone();
+            connected[index].event_type = DeviceEventType::Connected;
+        }
+        (DeviceEventType::Connected, None) => {
+            let mut device = event.clone();
+            device.event_type = DeviceEventType::Connected;
+            connected.push(device);
+        }
+        (DeviceEventType::Disconnected, Some(index)) => {
+            connected.remove(index);
+        }
+        _ => {}
+    }
+}
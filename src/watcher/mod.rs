@@ -1,9 +1,9 @@
 //! Cross-platform USB device monitoring implementations.
 //!
 //! Platform-specific USB monitoring implementations for Linux, Windows, and macOS, abstracted behind a common `UsbWatcher` interface.
-//! Uses sysfs (Linux), Win32 APIs (Windows), and IOKit (macOS).
+//! Uses kernel uevents and sysfs (Linux), Win32 APIs (Windows), and IOKit (macOS).
 
-/// Linux-specific USB monitoring implementation using sysfs.
+/// Linux-specific USB monitoring implementation using kernel uevents and sysfs.
 #[cfg(target_os = "linux")]
 pub mod linux;
 
@@ -15,14 +15,34 @@ pub mod windows;
 #[cfg(target_os = "macos")]
 pub mod macos;
 
+mod backend;
+mod builder;
+mod debounce;
+mod mock;
+mod replay;
+mod stop;
+mod stream;
+
+pub use backend::{BackendFuture, UsbBackend};
+#[cfg(any(target_os = "linux", target_os = "windows"))]
+pub(crate) use builder::PollInterval;
+pub use builder::{UsbWatcherBuilder, WatcherConfig, DEFAULT_CHANNEL_CAPACITY};
+pub use debounce::{Debouncer, FlapDetection};
+pub use mock::MockBackend;
+pub use replay::{open_recording, read_recording, ReplayBackend};
+pub use stop::StopHandle;
+pub use stream::DeviceEventStream;
+
 use crate::device_info::UsbDeviceInfo;
+use crate::topology::UsbTreeNode;
 use tokio::sync::mpsc;
 
 /// Cross-platform USB device watcher.
 ///
 /// This enum provides a unified interface for USB monitoring across
 /// different operating systems. The appropriate implementation is
-/// selected at compile time based on the target platform.
+/// selected at compile time based on the target platform, or supplied by
+/// the caller through [`UsbWatcher::with_backend`].
 pub enum UsbWatcher {
     /// Windows implementation using Win32 APIs
     #[cfg(target_os = "windows")]
@@ -33,6 +53,8 @@ pub enum UsbWatcher {
     /// macOS implementation using IOKit or polling
     #[cfg(target_os = "macos")]
     Macos(macos::MacosUsbWatcher),
+    /// Caller-supplied backend, e.g. a [`MockBackend`]
+    Custom(Box<dyn UsbBackend>),
     /// Placeholder for unsupported platforms
     #[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
     Unsupported,
@@ -62,36 +84,174 @@ impl UsbWatcher {
     /// # Ok(())
     /// # }
     /// ```
-    pub fn new(sender: mpsc::Sender<UsbDeviceInfo>) -> Result<Self, Box<dyn std::error::Error>> {
+    pub fn new(sender: mpsc::Sender<UsbDeviceInfo>) -> crate::Result<Self> {
+        Self::with_config(sender, WatcherConfig::default())
+    }
+
+    /// Returns a [`UsbWatcherBuilder`] for configuring polling and startup options.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use usbwatch_rs::UsbWatcher;
+    /// use std::time::Duration;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let (watcher, rx) = UsbWatcher::builder()
+    ///     .max_poll_interval(Duration::from_secs(30))
+    ///     .build_with_channel()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn builder() -> UsbWatcherBuilder {
+        UsbWatcherBuilder::new()
+    }
+
+    /// Starts a watcher with the default options and returns its events as a stream.
+    ///
+    /// The watcher runs in a background task owned by the returned
+    /// [`DeviceEventStream`] and is stopped when the stream is dropped. Use
+    /// [`UsbWatcherBuilder::build_stream`] to configure the watcher first.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the platform-specific watcher cannot be initialised.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a Tokio runtime.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use futures_util::StreamExt;
+    /// use usbwatch_rs::UsbWatcher;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut events = UsbWatcher::stream()?;
+    /// while let Some(event) = events.next().await {
+    ///     println!("USB event: {}", event?);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn stream() -> crate::Result<DeviceEventStream> {
+        UsbWatcherBuilder::new().build_stream()
+    }
+
+    /// Creates a new USB watcher for the current platform with the given options.
+    ///
+    /// Prefer [`UsbWatcher::builder`], which validates the options first.
+    ///
+    /// # Arguments
+    ///
+    /// * `sender` - Channel sender for publishing device events
+    /// * `config` - Polling, startup and platform options
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the platform-specific watcher cannot be initialised.
+    ///
+    /// # Panics
+    ///
+    /// Panics if debouncing or flap detection is enabled and this is called
+    /// outside of a Tokio runtime, since the [`Debouncer`] runs in its own task.
+    pub fn with_config(
+        sender: mpsc::Sender<UsbDeviceInfo>,
+        config: WatcherConfig,
+    ) -> crate::Result<Self> {
+        let sender = debounced(sender, &config);
+
         #[cfg(target_os = "windows")]
         {
-            let watcher = windows::WindowsUsbWatcher::new(sender);
+            let watcher = windows::WindowsUsbWatcher::with_config(sender, config);
             Ok(UsbWatcher::Windows(watcher))
         }
 
         #[cfg(target_os = "linux")]
         {
-            let watcher = linux::LinuxUsbWatcher::new(sender);
+            let watcher = linux::LinuxUsbWatcher::with_config(sender, config);
             Ok(UsbWatcher::Linux(watcher))
         }
 
         #[cfg(target_os = "macos")]
         {
-            let watcher = macos::MacosUsbWatcher::new(sender);
+            let watcher = macos::MacosUsbWatcher::with_config(sender, config);
             Ok(UsbWatcher::Macos(watcher))
         }
 
         #[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
         {
+            let _ = (sender, config);
             Ok(UsbWatcher::Unsupported)
         }
     }
 
+    /// Creates a watcher around a caller-supplied backend.
+    ///
+    /// The backend publishes events to the sender it was created with. Use
+    /// [`UsbWatcherBuilder::build_with_backend`] to apply the builder's
+    /// filter and debouncing options to it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use usbwatch_rs::watcher::MockBackend;
+    /// use usbwatch_rs::UsbWatcher;
+    /// use tokio::sync::mpsc;
+    ///
+    /// let (tx, rx) = mpsc::channel(100);
+    /// let watcher = UsbWatcher::with_backend(MockBackend::new(tx));
+    /// ```
+    pub fn with_backend(backend: impl UsbBackend + 'static) -> Self {
+        UsbWatcher::Custom(Box::new(backend))
+    }
+
+    /// Creates a new USB watcher that reads devices from a custom sysfs root.
+    ///
+    /// This lets the Linux watcher run against a fake device tree, e.g. in
+    /// tests without real USB hardware. Devices are read from
+    /// `<sysfs_root>/bus/usb/devices`.
+    ///
+    /// # Arguments
+    ///
+    /// * `sender` - Channel sender for publishing device events
+    /// * `sysfs_root` - Directory to treat as the sysfs mount point
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the watcher cannot be initialised.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use usbwatch_rs::UsbWatcher;
+    /// use tokio::sync::mpsc;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let (tx, rx) = mpsc::channel(100);
+    /// let watcher = UsbWatcher::with_sysfs_root(tx, "/tmp/fake-sysfs")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(target_os = "linux")]
+    pub fn with_sysfs_root(
+        sender: mpsc::Sender<UsbDeviceInfo>,
+        sysfs_root: impl Into<std::path::PathBuf>,
+    ) -> crate::Result<Self> {
+        UsbWatcherBuilder::new()
+            .sysfs_root(sysfs_root)
+            .build(sender)
+    }
+
     /// Starts monitoring USB devices.
     ///
-    /// This method runs indefinitely, monitoring for USB device connection
-    /// and disconnection events. Events are sent through the channel provided
-    /// during construction.
+    /// This method runs until stopped through a [`StopHandle`] obtained from
+    /// [`UsbWatcher::stop_handle`], monitoring for USB device connection and
+    /// disconnection events. Events are sent through the channel provided
+    /// during construction. When stopped, events the watcher has already
+    /// picked up are delivered before it returns `Ok(())`.
     ///
     /// # Errors
     ///
@@ -123,25 +283,117 @@ impl UsbWatcher {
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn start_monitoring(&self) -> Result<(), Box<dyn std::error::Error>> {
+    pub async fn start_monitoring(&self) -> crate::Result<()> {
+        self.backend()?.watch().await
+    }
+
+    /// Enumerates the USB devices connected right now.
+    ///
+    /// Unlike [`start_monitoring`](Self::start_monitoring), this takes a single
+    /// snapshot and sends nothing through the channel. Every returned device
+    /// has [`DeviceEventType::Connected`](crate::DeviceEventType::Connected) as
+    /// its event type. Devices rejected by the watcher's
+    /// [filter](super::UsbWatcherBuilder::filter) are left out.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the devices cannot be enumerated.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use usbwatch_rs::UsbWatcher;
+    /// use tokio::sync::mpsc;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let (tx, _rx) = mpsc::channel(1);
+    /// let watcher = UsbWatcher::new(tx)?;
+    /// for device in watcher.list_devices().await? {
+    ///     println!("{} ({}:{})", device.device_name, device.vendor_id, device.product_id);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn list_devices(&self) -> crate::Result<Vec<UsbDeviceInfo>> {
+        self.backend()?.enumerate().await
+    }
+
+    /// Builds the USB bus topology of the devices connected right now.
+    ///
+    /// On Linux, devices are nested under the hubs they are plugged into. Other platforms don't
+    /// report topology yet, so every device is returned as a root.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the devices cannot be enumerated.
+    pub async fn device_tree(&self) -> crate::Result<Vec<UsbTreeNode>> {
+        self.backend()?.device_tree().await
+    }
+
+    /// Returns a handle that stops [`start_monitoring`](Self::start_monitoring).
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use usbwatch_rs::UsbWatcher;
+    /// use tokio::sync::mpsc;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let (tx, rx) = mpsc::channel(100);
+    /// let watcher = UsbWatcher::new(tx)?;
+    /// let stop = watcher.stop_handle();
+    ///
+    /// tokio::spawn(async move {
+    ///     tokio::time::sleep(std::time::Duration::from_secs(10)).await;
+    ///     stop.stop();
+    /// });
+    ///
+    /// // Returns Ok(()) after ten seconds
+    /// watcher.start_monitoring().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn stop_handle(&self) -> StopHandle {
+        self.backend()
+            .map(UsbBackend::stop_handle)
+            .unwrap_or_default()
+    }
+
+    /// Returns the backend doing the work, or an error on unsupported platforms.
+    fn backend(&self) -> crate::Result<&dyn UsbBackend> {
         match self {
             #[cfg(target_os = "windows")]
-            UsbWatcher::Windows(watcher) => Ok(watcher
-                .start_monitoring()
-                .await
-                .map_err(|e| Box::new(std::io::Error::new(std::io::ErrorKind::Other, e)))?),
+            UsbWatcher::Windows(watcher) => Ok(watcher),
             #[cfg(target_os = "linux")]
-            UsbWatcher::Linux(watcher) => Ok(watcher
-                .start_monitoring()
-                .await
-                .map_err(|e| Box::new(std::io::Error::other(e)))?),
+            UsbWatcher::Linux(watcher) => Ok(watcher),
             #[cfg(target_os = "macos")]
-            UsbWatcher::Macos(watcher) => Ok(watcher
-                .start_monitoring()
-                .await
-                .map_err(|e| Box::new(std::io::Error::other(e)))?),
+            UsbWatcher::Macos(watcher) => Ok(watcher),
+            UsbWatcher::Custom(backend) => Ok(backend.as_ref()),
             #[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
-            UsbWatcher::Unsupported => Err("USB monitoring not supported on this platform".into()),
+            UsbWatcher::Unsupported => Err(crate::error::UsbWatchError::Unsupported(
+                "USB monitoring not supported on this platform".to_string(),
+            )),
         }
     }
 }
+
+/// Puts a [`Debouncer`] in front of `sender` if the configuration asks for one.
+///
+/// # Panics
+///
+/// Panics if debouncing or flap detection is enabled and this is called
+/// outside of a Tokio runtime.
+pub(crate) fn debounced(
+    sender: mpsc::Sender<UsbDeviceInfo>,
+    config: &WatcherConfig,
+) -> mpsc::Sender<UsbDeviceInfo> {
+    let debouncer = Debouncer::new(config.debounce_window, config.flap_detection);
+    if debouncer.is_passthrough() {
+        return sender;
+    }
+    let (debounce_tx, debounce_rx) = mpsc::channel(sender.max_capacity());
+    debouncer.spawn(debounce_rx, sender);
+    debounce_tx
+}
@@ -0,0 +1,204 @@
+//! Replaying recorded event sessions.
+//!
+//! A recording is what the JSON logger writes: one [`UsbDeviceInfo`] object
+//! per line, e.g. the output of `usbwatch record` or `usbwatch --json`.
+
+use super::mock::{lock_connected, track_connected};
+use super::{BackendFuture, StopHandle, UsbBackend, WatcherConfig};
+use crate::device_info::UsbDeviceInfo;
+use crate::error::UsbWatchError;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::time::Instant;
+
+/// Reads a recorded session, one JSON event per line.
+///
+/// Blank lines are skipped.
+///
+/// # Errors
+///
+/// Returns [`UsbWatchError::InvalidRecording`] with the line number if a line
+/// is not a valid event, or an I/O error if reading fails.
+///
+/// # Examples
+///
+/// ```
+/// use usbwatch_rs::watcher::read_recording;
+///
+/// let recording = r#"{"device_name":"ST-Link V2","vendor_id":"0483","product_id":"3748","serial_number":null,"timestamp":"2025-07-27T10:30:15Z","event_type":"Connected"}"#;
+/// let events = read_recording(recording.as_bytes())?;
+/// assert_eq!(events[0].vendor_id.to_string(), "0483");
+///
+/// assert!(read_recording("not json".as_bytes()).is_err());
+/// # Ok::<(), usbwatch_rs::UsbWatchError>(())
+/// ```
+pub fn read_recording(reader: impl BufRead) -> crate::Result<Vec<UsbDeviceInfo>> {
+    let mut events = Vec::new();
+    for (index, line) in reader.lines().enumerate() {
+        let line = line.map_err(|e| UsbWatchError::io("Failed to read recording", e))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let event =
+            serde_json::from_str(&line).map_err(|source| UsbWatchError::InvalidRecording {
+                line: index + 1,
+                source,
+            })?;
+        events.push(event);
+    }
+    Ok(events)
+}
+
+/// Reads a recorded session from a file.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be opened or read, or contains an
+/// invalid event (see [`read_recording`]).
+pub fn open_recording(path: impl AsRef<Path>) -> crate::Result<Vec<UsbDeviceInfo>> {
+    let path = path.as_ref();
+    let file = File::open(path).map_err(|e| {
+        UsbWatchError::io(format!("Failed to open recording '{}'", path.display()), e)
+    })?;
+    read_recording(BufReader::new(file))
+}
+
+/// A [`UsbBackend`] that re-emits recorded events with their original timing.
+///
+/// The first event is sent as soon as watching starts, and every later one
+/// after the time that separated it from the first in the recording, divided
+/// by the [`speed`](Self::speed). Events keep their recorded timestamps.
+/// [`enumerate`](UsbBackend::enumerate) returns the devices connected at the
+/// current point of the replay. [`watch`](UsbBackend::watch) returns once
+/// every event has been sent or the watcher is stopped.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use usbwatch_rs::watcher::{open_recording, ReplayBackend};
+/// use usbwatch_rs::UsbWatcher;
+/// use tokio::sync::mpsc;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let events = open_recording("incident.jsonl")?;
+/// let (tx, mut rx) = mpsc::channel(100);
+/// let watcher = UsbWatcher::with_backend(ReplayBackend::new(tx, events).speed(10.0));
+///
+/// tokio::spawn(async move { watcher.start_monitoring().await });
+/// while let Some(device_info) = rx.recv().await {
+///     println!("Replayed: {}", device_info);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct ReplayBackend {
+    tx: mpsc::Sender<UsbDeviceInfo>,
+    config: WatcherConfig,
+    events: Vec<UsbDeviceInfo>,
+    speed: f64,
+    connected: Mutex<Vec<UsbDeviceInfo>>,
+    stop: StopHandle,
+}
+
+impl ReplayBackend {
+    /// Creates a backend replaying `events` in real time.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx` - Channel sender for publishing the replayed events
+    /// * `events` - Recorded events, oldest first
+    pub fn new(tx: mpsc::Sender<UsbDeviceInfo>, events: Vec<UsbDeviceInfo>) -> Self {
+        Self::with_config(tx, WatcherConfig::default(), events)
+    }
+
+    /// Creates a backend replaying `events` with the given options.
+    ///
+    /// Only the [`filter`](WatcherConfig::filter) is used.
+    pub fn with_config(
+        tx: mpsc::Sender<UsbDeviceInfo>,
+        config: WatcherConfig,
+        events: Vec<UsbDeviceInfo>,
+    ) -> Self {
+        Self {
+            tx,
+            config,
+            events,
+            speed: 1.0,
+            connected: Mutex::new(Vec::new()),
+            stop: StopHandle::new(),
+        }
+    }
+
+    /// Sets how much faster than recorded the events are replayed, e.g. 2.0
+    /// for twice as fast. `f64::INFINITY` sends them without pauses.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `speed` is not greater than zero.
+    pub fn speed(mut self, speed: f64) -> Self {
+        assert!(speed > 0.0, "Replay speed must be greater than zero");
+        self.speed = speed;
+        self
+    }
+
+    /// Returns when `event` is due for a replay started at `start`, or `None`
+    /// if that is too far in the future to represent, e.g. at a tiny speed.
+    fn due(&self, start: Instant, event: &UsbDeviceInfo) -> Option<Instant> {
+        let Some(first) = self.events.first() else {
+            return Some(start);
+        };
+        let recorded = (event.timestamp - first.timestamp)
+            .to_std()
+            .unwrap_or_default();
+        let offset = Duration::try_from_secs_f64(recorded.as_secs_f64() / self.speed).ok()?;
+        start.checked_add(offset)
+    }
+}
+
+impl UsbBackend for ReplayBackend {
+    fn enumerate(&self) -> BackendFuture<'_, Vec<UsbDeviceInfo>> {
+        Box::pin(async move {
+            let mut devices = lock_connected(&self.connected).clone();
+            devices.retain(|device| self.config.filter.matches(device));
+            Ok(devices)
+        })
+    }
+
+    fn watch(&self) -> BackendFuture<'_, ()> {
+        Box::pin(async move {
+            let start = Instant::now();
+            lock_connected(&self.connected).clear();
+
+            for event in &self.events {
+                // An event that is never due waits for the replay to be stopped
+                let Some(due) = self.due(start, event) else {
+                    self.stop.stopped().await;
+                    return Ok(());
+                };
+                tokio::select! {
+                    _ = self.stop.stopped() => return Ok(()),
+                    _ = tokio::time::sleep_until(due) => {}
+                }
+
+                track_connected(&mut lock_connected(&self.connected), event);
+                if self.config.filter.matches(event) {
+                    self.tx
+                        .send(event.clone())
+                        .await
+                        .map_err(|_| UsbWatchError::ChannelClosed)?;
+                }
+            }
+            Ok(())
+        })
+    }
+
+    fn stop_handle(&self) -> StopHandle {
+        self.stop.clone()
+    }
+}
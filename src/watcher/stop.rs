@@ -0,0 +1,76 @@
+//! Cancellation for running watchers.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::sync::Notify;
+
+/// Handle for stopping a running [`UsbWatcher`](super::UsbWatcher).
+///
+/// Obtained from [`UsbWatcher::stop_handle`](super::UsbWatcher::stop_handle).
+/// Calling [`stop`](Self::stop) makes `start_monitoring` finish delivering the
+/// events it has already picked up and return `Ok(())`. Once stopped, a
+/// watcher stays stopped. Handles are cheap to clone and can be moved to
+/// other tasks, e.g. a Ctrl+C handler.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use usbwatch_rs::UsbWatcherBuilder;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let (watcher, mut rx) = UsbWatcherBuilder::new().build_with_channel()?;
+/// let stop = watcher.stop_handle();
+///
+/// let monitor = tokio::spawn(async move { watcher.start_monitoring().await.map_err(|e| e.to_string()) });
+///
+/// tokio::signal::ctrl_c().await?;
+/// stop.stop();
+/// monitor.await??;
+///
+/// // The watcher has dropped its sender, so this drains the remaining events
+/// while let Some(device_info) = rx.recv().await {
+///     println!("USB event: {}", device_info);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct StopHandle {
+    inner: Arc<StopState>,
+}
+
+#[derive(Debug, Default)]
+struct StopState {
+    stopped: AtomicBool,
+    notify: Notify,
+}
+
+impl StopHandle {
+    /// Creates a handle that has not been stopped.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Asks the watcher to stop.
+    pub fn stop(&self) {
+        self.inner.stopped.store(true, Ordering::SeqCst);
+        self.inner.notify.notify_waiters();
+    }
+
+    /// Returns true once [`stop`](Self::stop) has been called.
+    pub fn is_stopped(&self) -> bool {
+        self.inner.stopped.load(Ordering::SeqCst)
+    }
+
+    /// Completes once [`stop`](Self::stop) has been called.
+    pub async fn stopped(&self) {
+        // Register for the notification before checking the flag so a
+        // concurrent `stop` cannot slip in between
+        let notified = self.inner.notify.notified();
+        if self.is_stopped() {
+            return;
+        }
+        notified.await;
+    }
+}
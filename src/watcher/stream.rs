@@ -0,0 +1,100 @@
+//! A [`Stream`] of device events backed by a watcher task.
+
+use super::{StopHandle, UsbWatcher};
+use crate::device_info::UsbDeviceInfo;
+use crate::error::UsbWatchError;
+use futures_core::Stream;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+/// Stream of USB device events that owns its watcher.
+///
+/// Created by [`UsbWatcher::stream`] or
+/// [`UsbWatcherBuilder::build_stream`](super::UsbWatcherBuilder::build_stream).
+/// The watcher runs in a background Tokio task for as long as the stream is
+/// alive and is stopped when the stream is dropped. If the watcher fails, the
+/// stream yields its error as the last item.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use futures_util::{future, StreamExt};
+/// use usbwatch_rs::{DeviceEventType, UsbWatcher};
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut connected = UsbWatcher::stream()?
+///     .filter_map(|event| future::ready(event.ok()))
+///     .filter(|device| future::ready(device.event_type == DeviceEventType::Connected));
+///
+/// while let Some(device_info) = connected.next().await {
+///     println!("Connected: {}", device_info);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct DeviceEventStream {
+    rx: mpsc::Receiver<UsbDeviceInfo>,
+    task: Option<JoinHandle<crate::Result<()>>>,
+    stop: StopHandle,
+}
+
+impl DeviceEventStream {
+    /// Spawns `watcher` on the current Tokio runtime and streams events from `rx`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a Tokio runtime.
+    pub(crate) fn spawn(watcher: UsbWatcher, rx: mpsc::Receiver<UsbDeviceInfo>) -> Self {
+        let stop = watcher.stop_handle();
+        let task = tokio::spawn(async move { watcher.start_monitoring().await });
+
+        Self {
+            rx,
+            task: Some(task),
+            stop,
+        }
+    }
+
+    /// Asks the watcher to stop.
+    ///
+    /// The stream keeps yielding the events the watcher had already picked up
+    /// and ends once they have been delivered.
+    pub fn stop(&self) {
+        self.stop.stop();
+    }
+}
+
+impl Stream for DeviceEventStream {
+    type Item = crate::Result<UsbDeviceInfo>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        // The channel only closes once the watcher task has dropped its sender
+        if let Some(device_info) = ready!(this.rx.poll_recv(cx)) {
+            return Poll::Ready(Some(Ok(device_info)));
+        }
+
+        let Some(task) = this.task.as_mut() else {
+            return Poll::Ready(None);
+        };
+        let result = ready!(Pin::new(task).poll(cx));
+        this.task = None;
+
+        match result {
+            Ok(Ok(())) => Poll::Ready(None),
+            Ok(Err(e)) => Poll::Ready(Some(Err(e))),
+            Err(e) => Poll::Ready(Some(Err(UsbWatchError::TaskFailed(e.to_string())))),
+        }
+    }
+}
+
+impl Drop for DeviceEventStream {
+    fn drop(&mut self) {
+        self.stop.stop();
+    }
+}
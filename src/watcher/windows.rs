@@ -7,9 +7,15 @@
 //! are displayed correctly instead of showing garbled text.
 //!
 #[cfg(target_os = "windows")]
-use crate::device_info::{DeviceEventType, DeviceHandle, UsbDeviceInfo};
+use super::{BackendFuture, PollInterval, StopHandle, UsbBackend, WatcherConfig};
 #[cfg(target_os = "windows")]
-use std::collections::HashSet;
+use crate::device_info::{
+    BcdVersion, DeviceEventType, DeviceHandle, DeviceId, ProductId, UsbDeviceInfo, VendorId,
+};
+#[cfg(target_os = "windows")]
+use crate::error::UsbWatchError;
+#[cfg(target_os = "windows")]
+use std::collections::HashMap;
 #[cfg(target_os = "windows")]
 use tokio::sync::mpsc;
 #[cfg(target_os = "windows")]
@@ -18,73 +24,116 @@ use windows::{core::*, Win32::Devices::DeviceAndDriverInstallation::*, Win32::Fo
 #[cfg(target_os = "windows")]
 pub struct WindowsUsbWatcher {
     tx: mpsc::Sender<UsbDeviceInfo>,
+    // Boxed to keep the `UsbWatcher` variants about the same size
+    config: Box<WatcherConfig>,
+    stop: StopHandle,
 }
 
 #[cfg(target_os = "windows")]
 impl WindowsUsbWatcher {
     pub fn new(tx: mpsc::Sender<UsbDeviceInfo>) -> Self {
-        Self { tx }
+        Self::with_config(tx, WatcherConfig::default())
+    }
+
+    pub fn with_config(tx: mpsc::Sender<UsbDeviceInfo>, config: WatcherConfig) -> Self {
+        Self {
+            tx,
+            config: Box::new(config),
+            stop: StopHandle::new(),
+        }
+    }
+
+    pub fn stop_handle(&self) -> StopHandle {
+        self.stop.clone()
     }
 
-    pub async fn start_monitoring(&self) -> std::result::Result<(), String> {
+    pub async fn start_monitoring(&self) -> crate::Result<()> {
         println!("Starting USB device monitoring on Windows...");
 
         // For this implementation, we'll use a simple polling approach
         // In a production environment, you'd want to use proper Windows notifications
-        let mut known_devices = HashSet::new();
+        let mut known_devices: HashMap<DeviceId, UsbDeviceInfo> = HashMap::new();
+        let mut poll_interval = PollInterval::new(&self.config);
+        let mut initial_scan = true;
 
         loop {
+            if self.stop.is_stopped() {
+                return Ok(());
+            }
+            if self.tx.is_closed() {
+                return Err(UsbWatchError::ChannelClosed);
+            }
+
             match self.scan_usb_devices().await {
                 Ok(current_devices) => {
+                    let mut events_sent = 0;
+                    let current_map: HashMap<DeviceId, UsbDeviceInfo> = current_devices
+                        .into_iter()
+                        .map(|d| (d.device_id.clone(), d))
+                        .collect();
+
+                    // Optionally treat devices present at startup as already known
+                    if initial_scan && !self.config.emit_initial {
+                        known_devices.clone_from(&current_map);
+                    }
+                    initial_scan = false;
+
                     // Check for new devices (connected)
-                    for device in &current_devices {
-                        let device_key = format!("{}:{}", device.vendor_id, device.product_id);
-                        if !known_devices.contains(&device_key) {
-                            known_devices.insert(device_key.clone());
+                    for (id, device) in &current_map {
+                        if !known_devices.contains_key(id) {
                             let mut device_clone = device.clone();
                             device_clone.event_type = DeviceEventType::Connected;
+                            if !self.config.filter.matches(&device_clone) {
+                                continue;
+                            }
                             if let Err(e) = self.tx.send(device_clone).await {
                                 eprintln!("Failed to send device event: {}", e);
+                            } else {
+                                events_sent += 1;
                             }
                         }
                     }
 
                     // Check for removed devices (disconnected)
-                    let current_keys: HashSet<String> = current_devices
-                        .iter()
-                        .map(|d| format!("{}:{}", d.vendor_id, d.product_id))
-                        .collect();
-
-                    let removed_keys: Vec<String> =
-                        known_devices.difference(&current_keys).cloned().collect();
-
-                    for key in removed_keys {
-                        known_devices.remove(&key);
-                        let parts: Vec<&str> = key.split(':').collect();
-                        if parts.len() == 2 {
-                            let device_info = UsbDeviceInfo::new(
-                                "Unknown Device".to_string(),
-                                parts[0].to_string(),
-                                parts[1].to_string(),
-                                None,
-                                DeviceEventType::Disconnected,
-                            );
-                            if let Err(e) = self.tx.send(device_info).await {
+                    for (id, device) in &known_devices {
+                        if !current_map.contains_key(id) {
+                            let mut device_clone = device.clone();
+                            device_clone.event_type = DeviceEventType::Disconnected;
+                            if !self.config.filter.matches(&device_clone) {
+                                continue;
+                            }
+                            if let Err(e) = self.tx.send(device_clone).await {
                                 eprintln!("Failed to send device event: {}", e);
+                            } else {
+                                events_sent += 1;
                             }
                         }
                     }
+
+                    known_devices = current_map;
+                    poll_interval.on_scan(events_sent);
                 }
                 Err(e) => {
                     eprintln!("Error scanning USB devices: {}", e);
+                    poll_interval.on_error();
                 }
             }
 
-            tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+            tokio::select! {
+                _ = self.stop.stopped() => return Ok(()),
+                _ = self.tx.closed() => return Err(UsbWatchError::ChannelClosed),
+                _ = tokio::time::sleep(poll_interval.current()) => {}
+            }
         }
     }
 
-    async fn scan_usb_devices(&self) -> std::result::Result<Vec<UsbDeviceInfo>, String> {
+    pub async fn list_devices(&self) -> crate::Result<Vec<UsbDeviceInfo>> {
+        let mut devices = self.scan_usb_devices().await?;
+        devices.retain(|device| self.config.filter.matches(device));
+        Ok(devices)
+    }
+
+    async fn scan_usb_devices(&self) -> crate::Result<Vec<UsbDeviceInfo>> {
         let mut devices = Vec::new();
 
         unsafe {
@@ -98,17 +147,23 @@ impl WindowsUsbWatcher {
             )
             .is_err()
             {
-                return Err("Failed to get USB class GUID".to_string());
+                return Err(UsbWatchError::Platform(
+                    "Failed to get USB class GUID".to_string(),
+                ));
             }
             let class_guid = class_guid_buffer[0];
 
             // Get device information set
             let device_info_set =
                 SetupDiGetClassDevsW(Some(&class_guid), PCWSTR::null(), None, DIGCF_PRESENT)
-                    .map_err(|e| format!("Failed to get device info set: {}", e))?;
+                    .map_err(|e| {
+                        UsbWatchError::Platform(format!("Failed to get device info set: {}", e))
+                    })?;
 
             if device_info_set.is_invalid() {
-                return Err("Failed to get device information set".to_string());
+                return Err(UsbWatchError::Platform(
+                    "Failed to get device information set".to_string(),
+                ));
             }
 
             let mut device_index = 0u32;
@@ -129,8 +184,9 @@ impl WindowsUsbWatcher {
                 device_index += 1;
             }
 
-            SetupDiDestroyDeviceInfoList(device_info_set)
-                .map_err(|e| format!("Failed to destroy device info list: {}", e))?;
+            SetupDiDestroyDeviceInfoList(device_info_set).map_err(|e| {
+                UsbWatchError::Platform(format!("Failed to destroy device info list: {}", e))
+            })?;
         }
 
         Ok(devices)
@@ -140,7 +196,7 @@ impl WindowsUsbWatcher {
         &self,
         device_info_set: HDEVINFO,
         device_info_data: &SP_DEVINFO_DATA,
-    ) -> std::result::Result<UsbDeviceInfo, String> {
+    ) -> crate::Result<UsbDeviceInfo> {
         // Get device description
         let device_name = self
             .get_device_property(device_info_set, device_info_data, SPDRP_DEVICEDESC)
@@ -151,7 +207,15 @@ impl WindowsUsbWatcher {
             .get_device_property(device_info_set, device_info_data, SPDRP_HARDWAREID)
             .unwrap_or_default();
 
-        let (vendor_id, product_id) = self.parse_vid_pid(&hardware_id);
+        // Root hubs and other devices without a VID and PID are skipped
+        let (Some(vendor_id), Some(product_id)) = (
+            self.hardware_id_field(&hardware_id, "VID_"),
+            self.hardware_id_field(&hardware_id, "PID_"),
+        ) else {
+            return Err(UsbWatchError::Platform(format!(
+                "Hardware ID '{hardware_id}' has no VID and PID"
+            )));
+        };
 
         // Try to get serial number
         let serial_number = self.get_device_property(
@@ -160,13 +224,31 @@ impl WindowsUsbWatcher {
             SPDRP_PHYSICAL_DEVICE_OBJECT_NAME,
         );
 
-        Ok(UsbDeviceInfo::new(
+        let mut device_info = UsbDeviceInfo::new(
             device_name,
-            vendor_id,
-            product_id,
+            VendorId(vendor_id),
+            ProductId(product_id),
             serial_number,
             DeviceEventType::Connected, // Will be updated by caller
-        ))
+        );
+        device_info.device_version = self
+            .hardware_id_field(&hardware_id, "REV_")
+            .map(BcdVersion::from_bcd);
+
+        // Port and hub, e.g. "Port_#0002.Hub_#0001", to tell identical devices apart
+        if let Some(location) = self.get_device_property(
+            device_info_set,
+            device_info_data,
+            SPDRP_LOCATION_INFORMATION,
+        ) {
+            device_info.set_location(&location);
+        }
+        // The friendly name stays the device name; the database adds vendor and product
+        if let Some(ids) = &self.config.usb_ids {
+            ids.annotate(&mut device_info);
+        }
+
+        Ok(device_info)
     }
 
     /// Gets a device property from the Windows registry.
@@ -236,36 +318,27 @@ impl WindowsUsbWatcher {
         }
     }
 
-    fn parse_vid_pid(&self, hardware_id: &str) -> (String, String) {
-        // Parse hardware ID like "USB\VID_046D&PID_C52B&REV_1200"
-        let mut vendor_id = "0000".to_string();
-        let mut product_id = "0000".to_string();
-
-        // Find VID
-        if let Some(vid_start) = hardware_id.find("VID_") {
-            let vid_str = &hardware_id[vid_start + 4..];
-            if let Some(vid_end) = vid_str.find('&').or_else(|| vid_str.find('\0')) {
-                if vid_end >= 4 {
-                    vendor_id = vid_str[..4].to_string();
-                }
-            } else if vid_str.len() >= 4 {
-                vendor_id = vid_str[..4].to_string();
-            }
-        }
+    /// Reads the four hex digits following `key` in a hardware ID like
+    /// "USB\VID_046D&PID_C52B&REV_1200".
+    fn hardware_id_field(&self, hardware_id: &str, key: &str) -> Option<u16> {
+        let start = hardware_id.find(key)? + key.len();
+        let digits = hardware_id.get(start..start + 4)?;
+        u16::from_str_radix(digits, 16).ok()
+    }
+}
 
-        // Find PID
-        if let Some(pid_start) = hardware_id.find("PID_") {
-            let pid_str = &hardware_id[pid_start + 4..];
-            if let Some(pid_end) = pid_str.find('&').or_else(|| pid_str.find('\0')) {
-                if pid_end >= 4 {
-                    product_id = pid_str[..4].to_string();
-                }
-            } else if pid_str.len() >= 4 {
-                product_id = pid_str[..4].to_string();
-            }
-        }
+#[cfg(target_os = "windows")]
+impl UsbBackend for WindowsUsbWatcher {
+    fn enumerate(&self) -> BackendFuture<'_, Vec<UsbDeviceInfo>> {
+        Box::pin(self.list_devices())
+    }
 
-        (vendor_id, product_id)
+    fn watch(&self) -> BackendFuture<'_, ()> {
+        Box::pin(self.start_monitoring())
+    }
+
+    fn stop_handle(&self) -> StopHandle {
+        self.stop.clone()
     }
 }
 
@@ -278,7 +351,9 @@ impl WindowsUsbWatcher {
         Self
     }
 
-    pub async fn start_monitoring(&self) -> std::result::Result<(), String> {
-        Err("Windows USB monitoring not available on this platform".to_string())
+    pub async fn start_monitoring(&self) -> crate::Result<()> {
+        Err(crate::error::UsbWatchError::Unsupported(
+            "Windows USB monitoring not available on this platform".to_string(),
+        ))
     }
 }
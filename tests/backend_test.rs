@@ -0,0 +1,133 @@
+// Tests for custom backends
+// These use the scripted MockBackend, so they run on every platform without hardware
+
+use std::time::Duration;
+use tokio::sync::mpsc;
+use usbwatch_rs::filter::DeviceFilter;
+use usbwatch_rs::{
+    DeviceEventType, MockBackend, ProductId, UsbDeviceInfo, UsbWatcher, UsbWatcherBuilder, VendorId,
+};
+
+fn device(vendor_id: u16, product_id: u16, event_type: DeviceEventType) -> UsbDeviceInfo {
+    UsbDeviceInfo::new(
+        "Test Device".to_string(),
+        VendorId(vendor_id),
+        ProductId(product_id),
+        None,
+        event_type,
+    )
+}
+
+#[tokio::test]
+async fn test_mock_backend_replays_script() {
+    let (tx, mut rx) = mpsc::channel(10);
+    let backend = MockBackend::new(tx)
+        .device(device(0x0781, 0x5583, DeviceEventType::Connected))
+        .event(device(0x046d, 0xc31c, DeviceEventType::Connected))
+        .delay(Duration::from_millis(10))
+        .event(device(0x0781, 0x5583, DeviceEventType::Disconnected));
+    let watcher = UsbWatcher::with_backend(backend);
+
+    let before = watcher
+        .list_devices()
+        .await
+        .expect("Failed to list devices");
+    assert_eq!(before.len(), 1);
+    assert_eq!(before[0].vendor_id, VendorId(0x0781));
+
+    watcher
+        .start_monitoring()
+        .await
+        .expect("Mock backend failed");
+
+    let mut events = Vec::new();
+    while let Ok(event) = rx.try_recv() {
+        events.push((event.vendor_id, event.event_type));
+    }
+    assert_eq!(
+        events,
+        [
+            (VendorId(0x0781), DeviceEventType::Connected),
+            (VendorId(0x046d), DeviceEventType::Connected),
+            (VendorId(0x0781), DeviceEventType::Disconnected),
+        ]
+    );
+
+    // Enumeration follows the script
+    let after = watcher
+        .list_devices()
+        .await
+        .expect("Failed to list devices");
+    assert_eq!(after.len(), 1);
+    assert_eq!(after[0].vendor_id, VendorId(0x046d));
+}
+
+#[tokio::test]
+async fn test_mock_backend_stops_early() {
+    let (tx, mut rx) = mpsc::channel(10);
+    let backend = MockBackend::new(tx)
+        .delay(Duration::from_secs(3600))
+        .event(device(0x0781, 0x5583, DeviceEventType::Connected));
+    let watcher = UsbWatcher::with_backend(backend);
+    let stop = watcher.stop_handle();
+
+    let monitor = tokio::spawn(async move { watcher.start_monitoring().await.is_ok() });
+    stop.stop();
+    let stopped = tokio::time::timeout(Duration::from_secs(10), monitor)
+        .await
+        .expect("Mock backend did not stop")
+        .expect("Watcher task panicked");
+    assert!(stopped);
+    assert!(rx.recv().await.is_none());
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_builder_options_apply_to_custom_backends() {
+    let (tx, mut rx) = mpsc::channel(10);
+    let watcher = UsbWatcherBuilder::new()
+        .filter(DeviceFilter::new().vendor_id(0x0781))
+        .debounce(Duration::from_millis(100))
+        .emit_initial(false)
+        .build_with_backend(tx, |tx, config| {
+            MockBackend::with_config(tx, config)
+                .device(device(0x0781, 0x0001, DeviceEventType::Connected))
+                .event(device(0x046d, 0xc31c, DeviceEventType::Connected))
+                .event(device(0x0781, 0x0002, DeviceEventType::Disconnected))
+                .event(device(0x0781, 0x0002, DeviceEventType::Connected))
+                .event(device(0x0781, 0x0003, DeviceEventType::Connected))
+        })
+        .expect("Failed to build watcher");
+
+    watcher
+        .start_monitoring()
+        .await
+        .expect("Mock backend failed");
+    drop(watcher);
+
+    // The other vendor is filtered out and the reconnect is debounced away
+    let event = rx.recv().await.expect("Event channel closed");
+    assert_eq!(event.product_id, ProductId(0x0003));
+    assert_eq!(event.event_type, DeviceEventType::Connected);
+    assert!(rx.recv().await.is_none());
+}
+
+#[tokio::test]
+async fn test_custom_backend_device_tree() {
+    let mut hub = device(0x1d6b, 0x0002, DeviceEventType::Connected);
+    hub.bus_number = Some(1);
+    let mut drive = device(0x0781, 0x5583, DeviceEventType::Connected);
+    drive.bus_number = Some(1);
+    drive.port_path = Some("2".to_string());
+    drive.parent = Some("usb1".to_string());
+
+    let (tx, _rx) = mpsc::channel(10);
+    let watcher = UsbWatcher::with_backend(MockBackend::new(tx).device(drive).device(hub));
+
+    let roots = watcher
+        .device_tree()
+        .await
+        .expect("Failed to build device tree");
+    assert_eq!(roots.len(), 1);
+    assert_eq!(roots[0].device.vendor_id, VendorId(0x1d6b));
+    assert_eq!(roots[0].children[0].device.vendor_id, VendorId(0x0781));
+}
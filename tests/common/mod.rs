@@ -0,0 +1,296 @@
+// Shared fixtures for integration tests
+
+#![allow(dead_code)] // Not every test file uses every helper
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+/// A fake sysfs tree in a temporary directory.
+///
+/// Devices are created under `<root>/bus/usb/devices`, mirroring `/sys`, so a
+/// watcher pointed at [`FakeSysfs::root`] sees them like real hardware.
+pub struct FakeSysfs {
+    root: TempDir,
+}
+
+impl FakeSysfs {
+    /// Creates an empty fake sysfs tree.
+    pub fn new() -> Self {
+        let root = TempDir::new().expect("Failed to create fake sysfs root");
+        fs::create_dir_all(root.path().join("bus/usb/devices"))
+            .expect("Failed to create fake USB devices directory");
+        Self { root }
+    }
+
+    /// Returns the directory to use as the sysfs root.
+    pub fn root(&self) -> &Path {
+        self.root.path()
+    }
+
+    /// Returns the path of a device entry such as "1-1".
+    pub fn device_path(&self, name: &str) -> PathBuf {
+        self.root().join("bus/usb/devices").join(name)
+    }
+
+    /// Creates a device entry with the given attributes.
+    pub fn add_device(&self, name: &str, device: &FakeDevice) -> PathBuf {
+        let path = self.device_path(name);
+        fs::create_dir_all(&path).expect("Failed to create fake device directory");
+
+        let attributes = [
+            ("idVendor", Some(device.vendor_id.as_str())),
+            ("idProduct", Some(device.product_id.as_str())),
+            ("serial", device.serial.as_deref()),
+            ("product", device.product.as_deref()),
+            ("manufacturer", device.manufacturer.as_deref()),
+            ("speed", device.speed.as_deref()),
+            ("bDeviceClass", device.class.as_deref()),
+            ("bDeviceSubClass", device.class.as_ref().map(|_| "00")),
+            ("bDeviceProtocol", device.class.as_ref().map(|_| "00")),
+        ];
+        for (attribute, value) in attributes {
+            if let Some(value) = value {
+                fs::write(path.join(attribute), format!("{value}\n"))
+                    .expect("Failed to write fake device attribute");
+            }
+        }
+
+        path
+    }
+
+    /// Creates an interface such as "1-1:1.0" inside a device, optionally bound to a driver.
+    ///
+    /// The interface number is taken from the name and the subclass and
+    /// protocol are zero. The interface appears atomically, so a running
+    /// watcher never sees it half-written.
+    pub fn add_interface(
+        &self,
+        device: &str,
+        interface: &str,
+        class: u8,
+        driver: Option<&str>,
+    ) -> PathBuf {
+        let staging = self.root().join("staging").join(interface);
+        fs::create_dir_all(&staging).expect("Failed to create fake interface directory");
+
+        let number: u8 = interface
+            .rsplit('.')
+            .next()
+            .and_then(|n| n.parse().ok())
+            .expect("Interface name must end in its number");
+        let attributes = [
+            ("bInterfaceNumber", format!("{number:02x}")),
+            ("bInterfaceClass", format!("{class:02x}")),
+            ("bInterfaceSubClass", "00".to_string()),
+            ("bInterfaceProtocol", "00".to_string()),
+        ];
+        for (attribute, value) in attributes {
+            fs::write(staging.join(attribute), format!("{value}\n"))
+                .expect("Failed to write fake interface attribute");
+        }
+
+        if let Some(driver) = driver {
+            let driver_path = self.root().join("bus/usb/drivers").join(driver);
+            fs::create_dir_all(&driver_path).expect("Failed to create fake driver directory");
+            std::os::unix::fs::symlink(&driver_path, staging.join("driver"))
+                .expect("Failed to link fake interface driver");
+        }
+
+        let path = self.device_path(device).join(interface);
+        fs::rename(&staging, &path).expect("Failed to move fake interface into place");
+        path
+    }
+
+    /// Creates a sysfs directory below a device that backs the `/dev` node `devname`.
+    ///
+    /// `path` is relative to the device directory, e.g. "1-1:1.0/tty/ttyACM0",
+    /// or "." for the device's own node.
+    pub fn add_device_node(&self, device: &str, path: &str, devname: &str) -> PathBuf {
+        let path = self.device_path(device).join(path);
+        fs::create_dir_all(&path).expect("Failed to create fake device node directory");
+        fs::write(
+            path.join("uevent"),
+            format!("MAJOR=188\nMINOR=0\nDEVNAME={devname}\n"),
+        )
+        .expect("Failed to write fake device node uevent");
+        path
+    }
+
+    /// Registers a network interface below an interface of a device, e.g.
+    /// `1-1:1.0/net/usb0`, atomically.
+    pub fn add_network_interface(&self, device: &str, interface: &str, name: &str, mac: &str) {
+        let staging = self.root().join("staging").join(name);
+        fs::create_dir_all(&staging).expect("Failed to create fake network interface directory");
+        fs::write(staging.join("address"), format!("{mac}\n"))
+            .expect("Failed to write fake MAC address");
+
+        let net = self.device_path(device).join(interface).join("net");
+        fs::create_dir_all(&net).expect("Failed to create fake net directory");
+        fs::rename(&staging, net.join(name))
+            .expect("Failed to move fake network interface into place");
+    }
+
+    /// Removes a network interface made by [`FakeSysfs::add_network_interface`].
+    pub fn remove_network_interface(&self, device: &str, interface: &str, name: &str) {
+        fs::remove_dir_all(
+            self.device_path(device)
+                .join(interface)
+                .join("net")
+                .join(name),
+        )
+        .expect("Failed to remove fake network interface");
+    }
+
+    /// Creates a SCSI disk below an interface of a storage device, e.g.
+    /// `1-1:1.0/host2/target2:0:0/2:0:0:0/block/sdb`.
+    ///
+    /// `dev` is the "major:minor" number and `sectors` the size in 512-byte sectors.
+    pub fn add_disk(
+        &self,
+        device: &str,
+        interface: &str,
+        host: u32,
+        disk: &str,
+        dev: &str,
+        sectors: u64,
+    ) -> PathBuf {
+        let path = self.device_path(device).join(format!(
+            "{interface}/host{host}/target{host}:0:0/{host}:0:0:0/block/{disk}"
+        ));
+        write_block_device(&path, dev, sectors);
+        path
+    }
+
+    /// Creates a partition of a disk made by [`FakeSysfs::add_disk`].
+    pub fn add_partition(&self, disk: &Path, name: &str, number: u32, dev: &str, sectors: u64) {
+        let path = disk.join(name);
+        write_block_device(&path, dev, sectors);
+        fs::write(path.join("partition"), format!("{number}\n"))
+            .expect("Failed to write fake partition number");
+    }
+
+    /// Returns the path of the fake mount table, empty until [`FakeSysfs::set_mounts`].
+    pub fn mountinfo_path(&self) -> PathBuf {
+        self.root().join("mountinfo")
+    }
+
+    /// Replaces the fake mount table with `(dev, mount point, filesystem)`
+    /// entries, atomically.
+    ///
+    /// Mount points are escaped like the kernel does, so they may contain spaces.
+    pub fn set_mounts(&self, mounts: &[(&str, &str, &str)]) {
+        let mut table = "22 1 0:21 / / rw,relatime shared:1 - tmpfs tmpfs rw\n".to_string();
+        for (id, (dev, mount_point, filesystem)) in (100..).zip(mounts) {
+            table.push_str(&format!(
+                "{id} 22 {dev} / {} rw,nosuid,nodev shared:{id} - {filesystem} /dev/fake rw\n",
+                mount_point.replace(' ', "\\040")
+            ));
+        }
+        let staging = self.root().join("staging").join("mountinfo");
+        fs::create_dir_all(staging.parent().expect("Staging path has a parent"))
+            .expect("Failed to create staging directory");
+        fs::write(&staging, table).expect("Failed to write fake mount table");
+        fs::rename(&staging, self.mountinfo_path())
+            .expect("Failed to move fake mount table into place");
+    }
+
+    /// Returns the fake udev database directory.
+    pub fn udev_data_dir(&self) -> PathBuf {
+        self.root().join("udev")
+    }
+
+    /// Writes the udev database entry of a block device with the given properties.
+    pub fn add_udev_properties(&self, dev: &str, properties: &[(&str, &str)]) {
+        fs::create_dir_all(self.udev_data_dir()).expect("Failed to create fake udev database");
+        let entry: String = properties
+            .iter()
+            .map(|(key, value)| format!("E:{key}={value}\n"))
+            .collect();
+        fs::write(self.udev_data_dir().join(format!("b{dev}")), entry)
+            .expect("Failed to write fake udev database entry");
+    }
+
+    /// Sets a device attribute such as "authorized", replacing the file atomically.
+    pub fn set_attribute(&self, device: &str, attribute: &str, value: &str) {
+        let staging = self
+            .root()
+            .join("staging")
+            .join(format!("{device}-{attribute}"));
+        fs::create_dir_all(staging.parent().expect("Staging path has a parent"))
+            .expect("Failed to create staging directory");
+        fs::write(&staging, format!("{value}\n")).expect("Failed to write fake device attribute");
+        fs::rename(&staging, self.device_path(device).join(attribute))
+            .expect("Failed to move fake device attribute into place");
+    }
+
+    /// Removes a device entry, as if the device was unplugged.
+    pub fn remove_device(&self, name: &str) {
+        fs::remove_dir_all(self.device_path(name)).expect("Failed to remove fake device");
+    }
+}
+
+/// Descriptor strings written for a fake device.
+pub struct FakeDevice {
+    pub vendor_id: String,
+    pub product_id: String,
+    pub serial: Option<String>,
+    pub product: Option<String>,
+    pub manufacturer: Option<String>,
+    pub speed: Option<String>,
+    pub class: Option<String>,
+}
+
+impl FakeDevice {
+    /// Creates a fake device with only a vendor and product ID.
+    pub fn new(vendor_id: &str, product_id: &str) -> Self {
+        Self {
+            vendor_id: vendor_id.to_string(),
+            product_id: product_id.to_string(),
+            serial: None,
+            product: None,
+            manufacturer: None,
+            speed: None,
+            class: None,
+        }
+    }
+
+    /// Sets the serial number.
+    pub fn serial(mut self, serial: &str) -> Self {
+        self.serial = Some(serial.to_string());
+        self
+    }
+
+    /// Sets the product string.
+    pub fn product(mut self, product: &str) -> Self {
+        self.product = Some(product.to_string());
+        self
+    }
+
+    /// Sets the manufacturer string.
+    pub fn manufacturer(mut self, manufacturer: &str) -> Self {
+        self.manufacturer = Some(manufacturer.to_string());
+        self
+    }
+
+    /// Sets the device class code; the subclass and protocol are zero.
+    pub fn class(mut self, class: u8) -> Self {
+        self.class = Some(format!("{class:02x}"));
+        self
+    }
+
+    /// Sets the link speed in Mbit/s.
+    pub fn speed(mut self, speed: &str) -> Self {
+        self.speed = Some(speed.to_string());
+        self
+    }
+}
+
+/// Writes the attributes shared by fake disks and partitions.
+fn write_block_device(path: &Path, dev: &str, sectors: u64) {
+    fs::create_dir_all(path).expect("Failed to create fake block device directory");
+    fs::write(path.join("dev"), format!("{dev}\n"))
+        .expect("Failed to write fake block device number");
+    fs::write(path.join("size"), format!("{sectors}\n"))
+        .expect("Failed to write fake block device size");
+}
@@ -0,0 +1,210 @@
+// Tests for the configuration file
+
+use std::io::Write;
+use std::time::Duration;
+use usbwatch_rs::config::{Config, FilterConfig, HooksConfig, WatcherSettings};
+use usbwatch_rs::{
+    DeviceEventType, PolicyAction, ProductId, UsbDeviceInfo, UsbWatchError, UsbWatcherBuilder,
+    VendorId,
+};
+
+fn write_config(text: &str) -> tempfile::NamedTempFile {
+    let mut file = tempfile::NamedTempFile::new().expect("Failed to create config file");
+    file.write_all(text.as_bytes()).unwrap();
+    file
+}
+
+/// Returns the line of the error reported for `text`.
+fn error_line(text: &str) -> usize {
+    let file = write_config(text);
+    match Config::load(file.path()) {
+        Err(UsbWatchError::InvalidConfigFile {
+            line: Some(line), ..
+        }) => line,
+        other => panic!("Expected InvalidConfigFile with a line, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_load_full_config() {
+    let file = write_config(
+        r#"
+[output]
+json = true
+logfile = "usb.log"
+
+[filter]
+vid = ["0483"]
+exclude = ["class=hub"]
+
+[watcher]
+max_poll_interval_ms = 2000
+flap_threshold = 6
+
+[hooks]
+on_connect = "true"
+concurrency = 2
+timeout_secs = 5
+"#,
+    );
+    let config = Config::load(file.path()).expect("Failed to load config");
+
+    assert_eq!(config.output.json, Some(true));
+    assert_eq!(config.output.logfile.as_deref(), Some("usb.log"));
+    assert_eq!(
+        config.watcher.max_poll_interval,
+        Some(Duration::from_secs(2))
+    );
+    assert_eq!(config.watcher.flap_threshold, Some(6));
+    assert_eq!(config.watcher.flap_window, None);
+    assert_eq!(config.hooks.concurrency, Some(2));
+    assert_eq!(config.hooks.timeout, Some(Duration::from_secs(5)));
+
+    let filter = config.filter.device_filter().expect("Invalid filter");
+    let probe = UsbDeviceInfo::new(
+        "ST-Link V2".to_string(),
+        VendorId(0x0483),
+        ProductId(0x3748),
+        None,
+        DeviceEventType::Connected,
+    );
+    assert!(filter.matches(&probe));
+}
+
+#[test]
+fn test_errors_report_line() {
+    // Syntax error
+    assert_eq!(error_line("[output]\njson = true\nlogfile = \n"), 3);
+    // Unknown key
+    assert_eq!(
+        error_line("[output]\njson = true\n\n[filter]\nvendor = [\"0483\"]\n"),
+        5
+    );
+    // Wrong type
+    assert_eq!(error_line("[hooks]\ntimeout_secs = \"long\"\n"), 2);
+    // Invalid filter value, on the second line of a list
+    assert_eq!(
+        error_line("[filter]\nclass = [\n  \"hid\",\n  \"bogus\",\n]\n"),
+        4
+    );
+    // Zero where a positive value is required
+    assert_eq!(
+        error_line("[hooks]\non_connect = \"true\"\nconcurrency = 0\n"),
+        3
+    );
+    // Inconsistent poll intervals
+    assert_eq!(
+        error_line("[watcher]\n\nmin_poll_interval_ms = 9000\nmax_poll_interval_ms = 100\n"),
+        3
+    );
+}
+
+#[test]
+fn test_slow_polling_raises_backoff_ceiling() {
+    let file =
+        write_config("[watcher]\nmin_poll_interval_ms = 15000\nmax_poll_interval_ms = 60000\n");
+    let config = Config::load(file.path()).expect("Slow polling rejected");
+    let builder = config.watcher.apply(UsbWatcherBuilder::new());
+    assert_eq!(builder.config().error_backoff_max, Duration::from_secs(15));
+
+    let file = write_config(
+        "[watcher]\nmin_poll_interval_ms = 15000\nmax_poll_interval_ms = 60000\nerror_backoff_max_ms = 120000\n",
+    );
+    let config = Config::load(file.path()).expect("Backoff ceiling rejected");
+    let builder = config.watcher.apply(UsbWatcherBuilder::new());
+    assert_eq!(builder.config().error_backoff_max, Duration::from_secs(120));
+
+    // An explicit ceiling below the minimum poll interval is still an error
+    assert_eq!(
+        error_line("[watcher]\nmin_poll_interval_ms = 15000\nmax_poll_interval_ms = 60000\nerror_backoff_max_ms = 5000\n"),
+        2
+    );
+}
+
+#[test]
+fn test_policy_rules() {
+    let file = write_config(
+        r#"
+[[policy]]
+action = "allow"
+match = ["vid=0483", "pid=3748"]
+
+[[policy]]
+action = "no-hooks"
+match = ["vid=0483"]
+
+[[policy]]
+action = "deny"
+"#,
+    );
+    let config = Config::load(file.path()).expect("Failed to load config");
+
+    let mut device = UsbDeviceInfo::new(
+        "ST-Link V2".to_string(),
+        VendorId(0x0483),
+        ProductId(0x3748),
+        None,
+        DeviceEventType::Connected,
+    );
+    assert_eq!(config.policy.action_for(&device), PolicyAction::Allow);
+    device.product_id = ProductId(0x5740);
+    assert_eq!(config.policy.action_for(&device), PolicyAction::NoHooks);
+    device.vendor_id = VendorId(0x1366);
+    assert_eq!(config.policy.action_for(&device), PolicyAction::Deny);
+
+    // Without rules every event is allowed
+    let file = write_config("");
+    let config = Config::load(file.path()).expect("Failed to load config");
+    assert!(config.policy.is_empty());
+    assert_eq!(config.policy.action_for(&device), PolicyAction::Allow);
+
+    // Invalid actions and rules point at their line
+    assert_eq!(error_line("[[policy]]\naction = \"block\"\n"), 2);
+    assert_eq!(
+        error_line("[[policy]]\naction = \"deny\"\nmatch = [\"vid=0483\", \"colour=blue\"]\n"),
+        3
+    );
+}
+
+#[test]
+fn test_missing_file_is_io_error() {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let result = Config::load(dir.path().join("config.toml"));
+    assert!(matches!(result, Err(UsbWatchError::Io { .. })));
+}
+
+#[test]
+fn test_command_line_overrides_file() {
+    let file = FilterConfig {
+        vid: vec!["0483".to_string()],
+        class: vec!["hid".to_string()],
+        ..FilterConfig::default()
+    };
+    let cli = FilterConfig {
+        vid: vec!["1366".to_string()],
+        ..FilterConfig::default()
+    };
+    let merged = file.overridden_by(cli);
+    assert_eq!(merged.vid, ["1366"]);
+    assert_eq!(merged.class, ["hid"]);
+
+    let file = WatcherSettings {
+        debounce: Some(Duration::from_millis(300)),
+        flap_threshold: Some(6),
+        ..WatcherSettings::default()
+    };
+    let cli = WatcherSettings {
+        flap_threshold: Some(3),
+        ..WatcherSettings::default()
+    };
+    let merged = file.overridden_by(cli);
+    assert_eq!(merged.debounce, Some(Duration::from_millis(300)));
+    assert_eq!(merged.flap_threshold, Some(3));
+
+    let file = HooksConfig {
+        on_connect: Some("notify".to_string()),
+        ..HooksConfig::default()
+    };
+    let merged = file.overridden_by(HooksConfig::default());
+    assert_eq!(merged.on_connect.as_deref(), Some("notify"));
+}
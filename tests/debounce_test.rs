@@ -0,0 +1,150 @@
+// Tests for debouncing and flap detection
+// These feed events in by hand and run on a paused clock, so they work on every platform
+
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::time::Instant;
+use usbwatch_rs::{Debouncer, DeviceEventType, FlapDetection, ProductId, UsbDeviceInfo, VendorId};
+
+fn event(product_id: u16, event_type: DeviceEventType) -> UsbDeviceInfo {
+    UsbDeviceInfo::new(
+        "Test Device".to_string(),
+        VendorId(0x1234),
+        ProductId(product_id),
+        Some("SERIAL".to_string()),
+        event_type,
+    )
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_debounce_drops_short_reconnects() {
+    let (raw_tx, raw_rx) = mpsc::channel(16);
+    let (tx, mut rx) = mpsc::channel(16);
+    Debouncer::new(Some(Duration::from_millis(300)), None).spawn(raw_rx, tx);
+
+    let start = Instant::now();
+    raw_tx
+        .send(event(0x0001, DeviceEventType::Disconnected))
+        .await
+        .unwrap();
+    raw_tx
+        .send(event(0x0001, DeviceEventType::Connected))
+        .await
+        .unwrap();
+    raw_tx
+        .send(event(0x0002, DeviceEventType::Connected))
+        .await
+        .unwrap();
+
+    // Only the device that stayed connected is reported, once the window has passed
+    let delivered = rx.recv().await.expect("Event channel closed");
+    assert_eq!(delivered.product_id, ProductId(0x0002));
+    assert_eq!(delivered.event_type, DeviceEventType::Connected);
+    assert!(start.elapsed() >= Duration::from_millis(300));
+
+    drop(raw_tx);
+    assert!(rx.recv().await.is_none());
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_debounce_cancels_toggles_one_for_one() {
+    let (raw_tx, raw_rx) = mpsc::channel(16);
+    let (tx, mut rx) = mpsc::channel(16);
+    Debouncer::new(Some(Duration::from_millis(300)), None).spawn(raw_rx, tx);
+
+    // The disconnect cancels only the second connect
+    for event_type in [
+        DeviceEventType::Connected,
+        DeviceEventType::Connected,
+        DeviceEventType::Disconnected,
+    ] {
+        raw_tx.send(event(0x0001, event_type)).await.unwrap();
+    }
+    drop(raw_tx);
+
+    let delivered = rx.recv().await.expect("Uncancelled connect was lost");
+    assert_eq!(delivered.event_type, DeviceEventType::Connected);
+    assert!(rx.recv().await.is_none());
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_debounce_flushes_held_events_on_close() {
+    let (raw_tx, raw_rx) = mpsc::channel(16);
+    let (tx, mut rx) = mpsc::channel(16);
+    Debouncer::new(Some(Duration::from_secs(5)), None).spawn(raw_rx, tx);
+
+    raw_tx
+        .send(event(0x0001, DeviceEventType::Connected))
+        .await
+        .unwrap();
+    drop(raw_tx);
+
+    let delivered = rx.recv().await.expect("Held event was lost");
+    assert_eq!(delivered.event_type, DeviceEventType::Connected);
+    assert!(rx.recv().await.is_none());
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_flapping_device_is_reported_once() {
+    let (raw_tx, raw_rx) = mpsc::channel(32);
+    let (tx, mut rx) = mpsc::channel(32);
+    let flap = FlapDetection {
+        threshold: 3,
+        window: Duration::from_secs(10),
+    };
+    Debouncer::new(None, Some(flap)).spawn(raw_rx, tx);
+
+    // Eight toggles, ending disconnected
+    for i in 0..8 {
+        let event_type = if i % 2 == 0 {
+            DeviceEventType::Connected
+        } else {
+            DeviceEventType::Disconnected
+        };
+        raw_tx.send(event(0x0001, event_type)).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+
+    // The first three toggles pass through before the device counts as flapping
+    let mut received = Vec::new();
+    for _ in 0..4 {
+        received.push(rx.recv().await.expect("Event channel closed").event_type);
+    }
+    assert_eq!(
+        received,
+        [
+            DeviceEventType::Connected,
+            DeviceEventType::Disconnected,
+            DeviceEventType::Connected,
+            DeviceEventType::Flapping { count: 4 },
+        ]
+    );
+
+    // Once it settles, the final state is reported because it differs from the last one
+    let settled = rx.recv().await.expect("Event channel closed");
+    assert_eq!(settled.event_type, DeviceEventType::Disconnected);
+
+    drop(raw_tx);
+    assert!(rx.recv().await.is_none());
+}
+
+#[test]
+fn test_builder_stores_debounce_settings() {
+    use usbwatch_rs::UsbWatcherBuilder;
+
+    let builder = UsbWatcherBuilder::new()
+        .debounce(Duration::from_millis(250))
+        .flap_detection(5, Duration::from_secs(30));
+    assert_eq!(
+        builder.config().debounce_window,
+        Some(Duration::from_millis(250))
+    );
+    assert_eq!(
+        builder.config().flap_detection,
+        Some(FlapDetection {
+            threshold: 5,
+            window: Duration::from_secs(30),
+        })
+    );
+    assert!(builder.config().validate().is_ok());
+}
@@ -0,0 +1,227 @@
+// Tests for commands run on device events
+// The hooks run through `sh`, so these only run on Unix
+#![cfg(unix)]
+
+use std::io;
+use std::time::{Duration, Instant};
+use usbwatch_rs::{
+    DeviceEventType, DeviceHandle, EventHooks, ProductId, UsbDeviceInfo, UsbWatchError, VendorId,
+};
+
+fn device(event_type: DeviceEventType) -> UsbDeviceInfo {
+    UsbDeviceInfo::new(
+        "ST-Link V2".to_string(),
+        VendorId(0x0483),
+        ProductId(0x3748),
+        Some("066DFF".to_string()),
+        event_type,
+    )
+}
+
+#[tokio::test]
+async fn test_hook_receives_device() {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let env_file = dir.path().join("env");
+    let stdin_file = dir.path().join("stdin");
+    let hooks = EventHooks::new().on_connect(format!(
+        "echo \"$USBWATCH_EVENT $USBWATCH_VID $USBWATCH_PID $USBWATCH_SERIAL [$USBWATCH_SYSFS_PATH] [$USBWATCH_NET_INTERFACES]\" > {}; cat > {}",
+        env_file.display(),
+        stdin_file.display()
+    ));
+
+    let status = hooks
+        .run(&device(DeviceEventType::Connected))
+        .await
+        .expect("Hook failed")
+        .expect("Hook did not run");
+    assert!(status.success());
+
+    let env = std::fs::read_to_string(&env_file).unwrap();
+    assert_eq!(env.trim(), "connected 0483 3748 066DFF [] []");
+
+    let stdin = std::fs::read_to_string(&stdin_file).unwrap();
+    let event: UsbDeviceInfo = serde_json::from_str(&stdin).expect("Hook stdin is not JSON");
+    assert_eq!(event.serial_number.as_deref(), Some("066DFF"));
+}
+
+#[tokio::test]
+async fn test_hook_matches_event_type() {
+    let hooks = EventHooks::new().on_disconnect("exit 3");
+
+    let connected = hooks.run(&device(DeviceEventType::Connected)).await;
+    assert!(matches!(connected, Ok(None)));
+
+    let status = hooks
+        .run(&device(DeviceEventType::Disconnected))
+        .await
+        .expect("Hook failed")
+        .expect("Hook did not run");
+    assert_eq!(status.code(), Some(3));
+}
+
+#[tokio::test]
+async fn test_hook_timeout_kills_command() {
+    let hooks = EventHooks::new()
+        .on_connect("sleep 10")
+        .timeout(Duration::from_millis(100));
+
+    let start = Instant::now();
+    match hooks.run(&device(DeviceEventType::Connected)).await {
+        Err(UsbWatchError::Io { source, .. }) => {
+            assert_eq!(source.kind(), io::ErrorKind::TimedOut)
+        }
+        other => panic!("Expected a timeout, got {other:?}"),
+    }
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
+
+#[tokio::test]
+async fn test_hook_concurrency_limit() {
+    let hooks = EventHooks::new().on_connect("sleep 0.2").max_concurrent(1);
+
+    let start = Instant::now();
+    let handles: Vec<_> = (0..3)
+        .filter_map(|_| hooks.spawn(&device(DeviceEventType::Connected)))
+        .collect();
+    assert_eq!(handles.len(), 3);
+    for handle in handles {
+        handle.await.expect("Hook task panicked");
+    }
+
+    // With one slot the commands run one after another
+    assert!(start.elapsed() >= Duration::from_millis(600));
+}
+
+/// Replays `events` through the usbwatch binary with JSON output and returns
+/// the events it printed and its stderr.
+fn replay_json(
+    events: &[UsbDeviceInfo],
+    config: &str,
+    args: &[&str],
+) -> (Vec<UsbDeviceInfo>, String) {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let recording = dir.path().join("session.jsonl");
+    let lines: Vec<String> = events
+        .iter()
+        .map(|event| serde_json::to_string(event).unwrap() + "\n")
+        .collect();
+    std::fs::write(&recording, lines.concat()).expect("Failed to write recording");
+    let config_file = dir.path().join("config.toml");
+    std::fs::write(&config_file, config).expect("Failed to write config");
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_usbwatch"))
+        .arg("replay")
+        .arg(&recording)
+        .arg("--config")
+        .arg(&config_file)
+        .args(["--speed", "100x", "--json"])
+        .args(args)
+        .output()
+        .expect("Failed to run usbwatch");
+    assert!(output.status.success(), "usbwatch failed: {output:?}");
+
+    let stdout = String::from_utf8(output.stdout).expect("Output is not UTF-8");
+    let replayed = stdout
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("Output line is not an event"))
+        .collect();
+    (
+        replayed,
+        String::from_utf8_lossy(&output.stderr).into_owned(),
+    )
+}
+
+#[test]
+fn test_hook_output_keeps_json_output_valid() {
+    let (replayed, stderr) = replay_json(
+        &[
+            device(DeviceEventType::Connected),
+            device(DeviceEventType::Disconnected),
+        ],
+        "",
+        &[
+            "--on-connect",
+            "echo connected hook",
+            "--on-disconnect",
+            "printf 'partial line'",
+        ],
+    );
+    assert_eq!(replayed.len(), 2);
+    assert_eq!(replayed[1].event_type, DeviceEventType::Disconnected);
+
+    assert!(stderr.contains("connected hook"));
+    assert!(stderr.contains("partial line"));
+}
+
+#[test]
+fn test_policy_decides_reports_and_hooks() {
+    let mut other_probe = device(DeviceEventType::Connected);
+    other_probe.product_id = ProductId(0x5740);
+    let mut other_vendor = device(DeviceEventType::Connected);
+    other_vendor.vendor_id = VendorId(0x1366);
+    let config = r#"
+[[policy]]
+action = "allow"
+match = ["vid=0483", "pid=3748"]
+
+[[policy]]
+action = "no-hooks"
+match = ["vid=0483"]
+
+[[policy]]
+action = "deny"
+"#;
+
+    let (replayed, stderr) = replay_json(
+        &[
+            device(DeviceEventType::Connected),
+            other_probe,
+            other_vendor,
+        ],
+        config,
+        &["--on-connect", "echo \"hook $USBWATCH_VID:$USBWATCH_PID\""],
+    );
+    let reported: Vec<_> = replayed
+        .iter()
+        .map(|event| (event.vendor_id, event.product_id))
+        .collect();
+    assert_eq!(
+        reported,
+        [
+            (VendorId(0x0483), ProductId(0x3748)),
+            (VendorId(0x0483), ProductId(0x5740))
+        ]
+    );
+
+    assert!(stderr.contains("hook 0483:3748"));
+    assert!(!stderr.contains("hook 0483:5740"));
+    assert!(!stderr.contains("hook 1366"));
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn test_replayed_events_leave_sysfs_path_unset() {
+    let mut connected = device(DeviceEventType::Connected);
+    connected.device_handle = DeviceHandle::Linux {
+        sysfs_path: "/sys/bus/usb/devices/1-1".to_string(),
+        device_nodes: Vec::new(),
+    };
+    let hook = "echo \"sysfs=${USBWATCH_SYSFS_PATH-unset}\"";
+
+    // A live event carries its sysfs path
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let env_file = dir.path().join("env");
+    let hooks = EventHooks::new().on_connect(format!("{hook} > {}", env_file.display()));
+    hooks
+        .run(&connected)
+        .await
+        .expect("Hook failed")
+        .expect("Hook did not run");
+    let env = std::fs::read_to_string(&env_file).unwrap();
+    assert_eq!(env.trim(), "sysfs=/sys/bus/usb/devices/1-1");
+
+    // The recording doesn't keep it
+    let (replayed, stderr) = replay_json(&[connected], "", &["--on-connect", hook]);
+    assert_eq!(replayed.len(), 1);
+    assert!(stderr.contains("sysfs=unset"), "stderr: {stderr}");
+}
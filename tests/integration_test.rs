@@ -1,9 +1,26 @@
-// Integration test for usbwatch-rs
-// This test will only run on Linux for now
+// Integration tests for usbwatch-rs
+// On Linux these run against a fake sysfs tree, so no USB hardware is needed
+
+mod common;
 
 use tokio::sync::mpsc;
 use usbwatch_rs::UsbWatcher;
+#[cfg(target_os = "linux")]
+use usbwatch_rs::{BcdVersion, DeviceEventType, ProductId, UsbDeviceInfo, VendorId};
+
+/// Upper bound on how long the poller may take to notice a change
+#[cfg(target_os = "linux")]
+const EVENT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+#[cfg(target_os = "linux")]
+async fn next_event(rx: &mut mpsc::Receiver<UsbDeviceInfo>) -> UsbDeviceInfo {
+    tokio::time::timeout(EVENT_TIMEOUT, rx.recv())
+        .await
+        .expect("Timed out waiting for device event")
+        .expect("Event channel closed")
+}
 
+#[cfg(not(target_os = "linux"))]
 #[tokio::test]
 async fn test_usbwatcher_start_monitoring() {
     let (tx, mut rx) = mpsc::channel(10);
@@ -20,3 +37,791 @@ async fn test_usbwatcher_start_monitoring() {
     // We can't guarantee a device event, but the test should run without panicking
     // The test passes if it runs without panicking
 }
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn test_usbwatcher_start_monitoring() {
+    use common::{FakeDevice, FakeSysfs};
+
+    let sysfs = FakeSysfs::new();
+    sysfs.add_device(
+        "1-1",
+        &FakeDevice::new("0781", "5583")
+            .serial("4C530001234567891234")
+            .product("Ultra USB 3.0")
+            .manufacturer("SanDisk"),
+    );
+
+    let (tx, mut rx) = mpsc::channel(10);
+    let watcher = UsbWatcher::with_sysfs_root(tx, sysfs.root()).expect("Failed to create watcher");
+    tokio::spawn(async move {
+        let _ = watcher.start_monitoring().await;
+    });
+
+    let event = next_event(&mut rx).await;
+    assert_eq!(event.event_type, DeviceEventType::Connected);
+    assert_eq!(event.device_name, "SanDisk Ultra USB 3.0");
+    assert_eq!(event.vendor_id, VendorId(0x0781));
+    assert_eq!(event.product_id, ProductId(0x5583));
+    assert_eq!(event.serial_number.as_deref(), Some("4C530001234567891234"));
+
+    sysfs.remove_device("1-1");
+
+    let event = next_event(&mut rx).await;
+    assert_eq!(event.event_type, DeviceEventType::Disconnected);
+    assert_eq!(event.vendor_id, VendorId(0x0781));
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn test_usbwatcher_reports_hotplugged_device() {
+    use common::{FakeDevice, FakeSysfs};
+
+    let sysfs = FakeSysfs::new();
+    let (tx, mut rx) = mpsc::channel(10);
+    let watcher = UsbWatcher::with_sysfs_root(tx, sysfs.root()).expect("Failed to create watcher");
+    tokio::spawn(async move {
+        let _ = watcher.start_monitoring().await;
+    });
+
+    sysfs.add_device("2-3", &FakeDevice::new("046d", "c52b"));
+
+    let event = next_event(&mut rx).await;
+    assert_eq!(event.event_type, DeviceEventType::Connected);
+    assert_eq!(event.device_name, "Unknown Device");
+    assert_eq!(event.serial_number, None);
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn test_identical_serialless_devices_stay_distinct() {
+    use common::{FakeDevice, FakeSysfs};
+
+    let sysfs = FakeSysfs::new();
+    sysfs.add_device("1-1", &FakeDevice::new("046d", "c31c").product("Keyboard"));
+    sysfs.add_device("1-2", &FakeDevice::new("046d", "c31c").product("Keyboard"));
+
+    let (tx, mut rx) = mpsc::channel(10);
+    let watcher = UsbWatcher::with_sysfs_root(tx, sysfs.root()).expect("Failed to create watcher");
+    tokio::spawn(async move {
+        let _ = watcher.start_monitoring().await;
+    });
+
+    let mut ids = [
+        next_event(&mut rx).await.device_id.to_string(),
+        next_event(&mut rx).await.device_id.to_string(),
+    ];
+    ids.sort();
+    assert_eq!(ids, ["046d:c31c@1-1", "046d:c31c@1-2"]);
+
+    sysfs.remove_device("1-2");
+
+    let event = next_event(&mut rx).await;
+    assert_eq!(event.event_type, DeviceEventType::Disconnected);
+    assert_eq!(event.device_id.as_str(), "046d:c31c@1-2");
+    let json = serde_json::to_value(&event).expect("Failed to serialize event");
+    assert_eq!(json["device_id"], "046d:c31c@1-2");
+}
+
+#[test]
+fn test_builder_rejects_inconsistent_intervals() {
+    use std::time::Duration;
+    use usbwatch_rs::{UsbWatchError, UsbWatcherBuilder};
+
+    let result = UsbWatcherBuilder::new()
+        .min_poll_interval(Duration::from_secs(10))
+        .max_poll_interval(Duration::from_secs(1))
+        .build_with_channel();
+    assert!(matches!(result, Err(UsbWatchError::InvalidConfig(_))));
+
+    let result = UsbWatcherBuilder::new()
+        .min_poll_interval(Duration::ZERO)
+        .build_with_channel();
+    assert!(result.is_err());
+
+    let result = UsbWatcherBuilder::new()
+        .channel_capacity(0)
+        .build_with_channel();
+    assert!(result.is_err());
+
+    let result = UsbWatcherBuilder::new()
+        .flap_detection(0, Duration::from_secs(10))
+        .build_with_channel();
+    assert!(result.is_err());
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn test_builder_skips_initial_devices() {
+    use common::{FakeDevice, FakeSysfs};
+    use std::time::Duration;
+    use usbwatch_rs::UsbWatcherBuilder;
+
+    let sysfs = FakeSysfs::new();
+    sysfs.add_device("1-1", &FakeDevice::new("1d6b", "0002"));
+
+    let (watcher, mut rx) = UsbWatcherBuilder::new()
+        .sysfs_root(sysfs.root())
+        .min_poll_interval(Duration::from_millis(50))
+        .max_poll_interval(Duration::from_millis(200))
+        .emit_initial(false)
+        .build_with_channel()
+        .expect("Failed to build watcher");
+    tokio::spawn(async move {
+        let _ = watcher.start_monitoring().await;
+    });
+
+    // Give the first scan time to run before plugging in another device
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    sysfs.add_device("1-2", &FakeDevice::new("0403", "6001"));
+
+    let event = next_event(&mut rx).await;
+    assert_eq!(event.event_type, DeviceEventType::Connected);
+    assert_eq!(event.vendor_id, VendorId(0x0403));
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn test_stop_handle_ends_monitoring() {
+    use common::{FakeDevice, FakeSysfs};
+
+    let sysfs = FakeSysfs::new();
+    sysfs.add_device("1-1", &FakeDevice::new("0781", "5583"));
+
+    let (tx, mut rx) = mpsc::channel(10);
+    let watcher = UsbWatcher::with_sysfs_root(tx, sysfs.root()).expect("Failed to create watcher");
+    let stop = watcher.stop_handle();
+    let monitor =
+        tokio::spawn(async move { watcher.start_monitoring().await.map_err(|e| e.to_string()) });
+
+    let event = next_event(&mut rx).await;
+    assert_eq!(event.event_type, DeviceEventType::Connected);
+
+    stop.stop();
+    let result = tokio::time::timeout(EVENT_TIMEOUT, monitor)
+        .await
+        .expect("Watcher did not stop")
+        .expect("Watcher task panicked");
+    assert_eq!(result, Ok(()));
+
+    // The watcher dropped its sender, so the channel closes once drained
+    assert!(rx.recv().await.is_none());
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn test_dropped_receiver_ends_monitoring() {
+    use common::{FakeDevice, FakeSysfs};
+    use usbwatch_rs::UsbWatchError;
+
+    let sysfs = FakeSysfs::new();
+    sysfs.add_device("1-1", &FakeDevice::new("0781", "5583"));
+
+    let (tx, rx) = mpsc::channel(10);
+    let watcher = UsbWatcher::with_sysfs_root(tx, sysfs.root()).expect("Failed to create watcher");
+    drop(rx);
+
+    let result = tokio::time::timeout(EVENT_TIMEOUT, watcher.start_monitoring())
+        .await
+        .expect("Watcher kept running without a receiver");
+    assert!(matches!(result, Err(UsbWatchError::ChannelClosed)));
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn test_missing_sysfs_is_reported() {
+    use usbwatch_rs::UsbWatchError;
+
+    let root = tempfile::TempDir::new().expect("Failed to create empty sysfs root");
+    let (tx, _rx) = mpsc::channel(10);
+    let watcher = UsbWatcher::with_sysfs_root(tx, root.path()).expect("Failed to create watcher");
+
+    match watcher.list_devices().await {
+        Err(UsbWatchError::SysfsUnavailable { path }) => {
+            assert_eq!(path, root.path().join("bus/usb/devices"));
+        }
+        other => panic!("Expected SysfsUnavailable, got {other:?}"),
+    }
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn test_event_stream() {
+    use common::{FakeDevice, FakeSysfs};
+    use futures_util::StreamExt;
+    use usbwatch_rs::UsbWatcherBuilder;
+
+    let sysfs = FakeSysfs::new();
+    sysfs.add_device("3-1", &FakeDevice::new("0403", "6001").serial("FT123"));
+
+    let mut events = UsbWatcherBuilder::new()
+        .sysfs_root(sysfs.root())
+        .build_stream()
+        .expect("Failed to build stream");
+
+    let event = tokio::time::timeout(EVENT_TIMEOUT, events.next())
+        .await
+        .expect("Timed out waiting for device event")
+        .expect("Stream ended")
+        .expect("Watcher failed");
+    assert_eq!(event.event_type, DeviceEventType::Connected);
+    assert_eq!(event.serial_number.as_deref(), Some("FT123"));
+
+    // Stopping ends the stream cleanly instead of yielding an error
+    events.stop();
+    let rest: Vec<_> = tokio::time::timeout(EVENT_TIMEOUT, events.collect())
+        .await
+        .expect("Stream did not end after stop");
+    assert!(rest.iter().all(|event| event.is_ok()));
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn test_list_devices_snapshot() {
+    use common::{FakeDevice, FakeSysfs};
+
+    let sysfs = FakeSysfs::new();
+    sysfs.add_device("1-1", &FakeDevice::new("0781", "5583").product("Ultra"));
+    sysfs.add_device("1-2", &FakeDevice::new("046d", "c52b"));
+    // Interfaces and all-zero IDs are not devices
+    sysfs.add_device("1-1:1.0", &FakeDevice::new("0781", "5583"));
+    sysfs.add_device("2-1", &FakeDevice::new("0000", "0000"));
+    sysfs.set_attribute("1-1", "version", " 3.20");
+    sysfs.set_attribute("1-1", "bcdDevice", "0100");
+
+    let (tx, mut rx) = mpsc::channel(10);
+    let watcher = UsbWatcher::with_sysfs_root(tx, sysfs.root()).expect("Failed to create watcher");
+
+    let mut devices = watcher
+        .list_devices()
+        .await
+        .expect("Failed to list devices");
+    devices.sort_by_key(|d| d.vendor_id);
+    let ids: Vec<_> = devices
+        .iter()
+        .map(|d| (d.vendor_id, d.product_id))
+        .collect();
+    assert_eq!(
+        ids,
+        [
+            (VendorId(0x046d), ProductId(0xc52b)),
+            (VendorId(0x0781), ProductId(0x5583))
+        ]
+    );
+    // sysfs shows bcdUSB formatted and bcdDevice as raw hex
+    assert_eq!(devices[1].usb_version, Some(BcdVersion::from_bcd(0x0320)));
+    assert_eq!(
+        devices[1].device_version,
+        Some(BcdVersion::from_bcd(0x0100))
+    );
+    assert_eq!(devices[0].usb_version, None);
+    assert!(devices
+        .iter()
+        .all(|d| d.event_type == DeviceEventType::Connected));
+
+    // Listing never publishes events
+    assert!(rx.try_recv().is_err());
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn test_device_tree_topology() {
+    use common::{FakeDevice, FakeSysfs};
+    use usbwatch_rs::topology::render_tree;
+    use usbwatch_rs::UsbSpeed;
+
+    let sysfs = FakeSysfs::new();
+    sysfs.add_device(
+        "usb1",
+        &FakeDevice::new("1d6b", "0002")
+            .product("EHCI Host Controller")
+            .speed("480"),
+    );
+    sysfs.add_interface("usb1", "1-0:1.0", 0x09, Some("hub"));
+    sysfs.add_device(
+        "1-1",
+        &FakeDevice::new("05e3", "0608")
+            .product("USB2.0 Hub")
+            .speed("480"),
+    );
+    sysfs.add_interface("1-1", "1-1:1.0", 0x09, Some("hub"));
+    sysfs.add_device(
+        "1-1.4",
+        &FakeDevice::new("0781", "5583")
+            .product("Ultra")
+            .speed("480"),
+    );
+    sysfs.add_interface("1-1.4", "1-1.4:1.0", 0x08, Some("usb-storage"));
+    sysfs.add_interface("1-1.4", "1-1.4:1.1", 0x08, None);
+
+    let (tx, _rx) = mpsc::channel(10);
+    let watcher = UsbWatcher::with_sysfs_root(tx, sysfs.root()).expect("Failed to create watcher");
+
+    let devices = watcher
+        .list_devices()
+        .await
+        .expect("Failed to list devices");
+    let drive = devices
+        .iter()
+        .find(|d| d.vendor_id == VendorId(0x0781))
+        .expect("Drive not listed");
+    assert_eq!(drive.bus_number, Some(1));
+    assert_eq!(drive.port_path.as_deref(), Some("1.4"));
+    assert_eq!(drive.parent.as_deref(), Some("1-1"));
+
+    let roots = watcher
+        .device_tree()
+        .await
+        .expect("Failed to build device tree");
+    assert_eq!(roots.len(), 1);
+    let root_hub = &roots[0];
+    assert_eq!(root_hub.device.port_path, None);
+    assert_eq!(root_hub.drivers, ["hub"]);
+
+    let hub = &root_hub.children[0];
+    assert_eq!(hub.port(), Some("1"));
+    let drive = &hub.children[0];
+    assert_eq!(drive.port(), Some("4"));
+    assert_eq!(drive.device.speed, Some(UsbSpeed::High));
+    assert_eq!(drive.drivers, ["usb-storage"]);
+
+    assert_eq!(
+        render_tree(&roots),
+        "Bus 001: EHCI Host Controller [1d6b:0002] Driver=hub, 480M\n\
+         \x20   |__ Port 1: USB2.0 Hub [05e3:0608] Driver=hub, 480M\n\
+         \x20       |__ Port 4: Ultra [0781:5583] Driver=usb-storage, 480M\n"
+    );
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn test_link_and_power_attributes() {
+    use common::{FakeDevice, FakeSysfs};
+    use usbwatch_rs::UsbSpeed;
+
+    let sysfs = FakeSysfs::new();
+    sysfs.add_device(
+        "2-1",
+        &FakeDevice::new("0bda", "0411").class(0x09).speed("5000"),
+    );
+    for (attribute, value) in [
+        ("maxchild", "4"),
+        ("removable", "fixed"),
+        ("bMaxPower", "0mA"),
+    ] {
+        sysfs.set_attribute("2-1", attribute, value);
+    }
+    // A USB 3 drive that fell back to High Speed
+    sysfs.add_device("2-1.2", &FakeDevice::new("0781", "5583").speed("480"));
+    for (attribute, value) in [
+        ("version", " 3.20"),
+        ("bMaxPower", "896mA"),
+        ("bConfigurationValue", "1"),
+        ("bNumInterfaces", " 1"),
+        ("maxchild", "0"),
+        ("removable", "removable"),
+    ] {
+        sysfs.set_attribute("2-1.2", attribute, value);
+    }
+
+    let (tx, _rx) = mpsc::channel(10);
+    let watcher = UsbWatcher::with_sysfs_root(tx, sysfs.root()).expect("Failed to create watcher");
+    let mut devices = watcher
+        .list_devices()
+        .await
+        .expect("Failed to list devices");
+    devices.sort_by_key(|d| d.vendor_id);
+
+    let drive = &devices[0];
+    assert_eq!(drive.speed, Some(UsbSpeed::High));
+    assert!(drive.usb_version.is_some_and(|v| v.major() >= 3));
+    assert_eq!(drive.max_power_ma, Some(896));
+    assert_eq!(drive.configuration, Some(1));
+    assert_eq!(drive.num_interfaces, Some(1));
+    assert_eq!(drive.max_child, Some(0));
+    assert_eq!(drive.removable, Some(true));
+
+    let hub = &devices[1];
+    assert_eq!(hub.speed, Some(UsbSpeed::Super));
+    assert_eq!(hub.max_child, Some(4));
+    assert_eq!(hub.removable, Some(false));
+    // Not configured
+    assert_eq!(hub.configuration, None);
+
+    let json = serde_json::to_value(drive).unwrap();
+    assert_eq!(json["speed"], "high");
+    assert_eq!(json["max_power_ma"], 896);
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn test_device_nodes_are_resolved() {
+    use common::{FakeDevice, FakeSysfs};
+    use usbwatch_rs::DeviceHandle;
+
+    let sysfs = FakeSysfs::new();
+    sysfs.add_device("1-1", &FakeDevice::new("0403", "6001").product("FT232R"));
+    sysfs.add_device_node("1-1", ".", "bus/usb/001/004");
+    sysfs.add_device_node("1-1", "1-1:1.0/ttyUSB0/tty/ttyUSB0", "ttyUSB0");
+    // Only the node kinds we know about are reported
+    sysfs.add_device_node("1-1", "1-1:1.0/input/input7/event7", "input/event7");
+
+    sysfs.add_device("1-2", &FakeDevice::new("0781", "5583").product("Ultra"));
+    sysfs.add_device_node(
+        "1-2",
+        "1-2:1.0/host2/target2:0:0/2:0:0:0/block/sdb/sdb1",
+        "sdb1",
+    );
+    sysfs.add_device_node("1-2", "1-2:1.0/host2/target2:0:0/2:0:0:0/block/sdb", "sdb");
+
+    let (tx, _rx) = mpsc::channel(10);
+    let watcher = UsbWatcher::with_sysfs_root(tx, sysfs.root()).expect("Failed to create watcher");
+    let devices = watcher
+        .list_devices()
+        .await
+        .expect("Failed to list devices");
+
+    let serial = devices
+        .iter()
+        .find(|d| d.vendor_id == VendorId(0x0403))
+        .expect("Serial adapter not listed");
+    assert_eq!(
+        serial.device_nodes,
+        ["/dev/bus/usb/001/004", "/dev/ttyUSB0"]
+    );
+    match &serial.device_handle {
+        DeviceHandle::Linux { device_nodes, .. } => assert_eq!(device_nodes, &serial.device_nodes),
+        _ => panic!("Expected a Linux device handle"),
+    }
+    let json = serde_json::to_value(serial).expect("Failed to serialize device");
+    assert_eq!(json["device_nodes"][1], "/dev/ttyUSB0");
+
+    let drive = devices
+        .iter()
+        .find(|d| d.vendor_id == VendorId(0x0781))
+        .expect("Drive not listed");
+    assert_eq!(drive.device_nodes, ["/dev/sdb", "/dev/sdb1"]);
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn test_interfaces_and_classes() {
+    use common::{FakeDevice, FakeSysfs};
+    use usbwatch_rs::UsbClass;
+
+    let sysfs = FakeSysfs::new();
+    // A composite keyboard and mouse receiver: class defined per interface
+    sysfs.add_device(
+        "1-3",
+        &FakeDevice::new("046d", "c52b")
+            .product("Unifying Receiver")
+            .class(0x00),
+    );
+    sysfs.add_interface("1-3", "1-3:1.2", 0x03, None);
+    sysfs.add_interface("1-3", "1-3:1.0", 0x03, Some("usbhid"));
+    sysfs.add_interface("1-3", "1-3:1.1", 0xff, None);
+
+    let (tx, _rx) = mpsc::channel(10);
+    let watcher = UsbWatcher::with_sysfs_root(tx, sysfs.root()).expect("Failed to create watcher");
+    let devices = watcher
+        .list_devices()
+        .await
+        .expect("Failed to list devices");
+    let receiver = &devices[0];
+
+    assert_eq!(receiver.device_class, Some(UsbClass::new(0x00, 0x00, 0x00)));
+    let numbers: Vec<_> = receiver.interfaces.iter().map(|i| i.number).collect();
+    assert_eq!(numbers, [0, 1, 2]);
+    assert_eq!(receiver.interfaces[0].driver.as_deref(), Some("usbhid"));
+    assert_eq!(receiver.interfaces[1].driver, None);
+    assert_eq!(receiver.class_names(), ["HID", "Vendor Specific"]);
+
+    let json = serde_json::to_value(receiver).expect("Failed to serialize device");
+    assert_eq!(json["interfaces"][0]["class"]["name"], "HID");
+    assert_eq!(json["interfaces"][0]["driver"], "usbhid");
+    assert_eq!(json["device_class"]["name"], "Per Interface");
+
+    // The class names survive a round trip through JSON
+    let parsed: UsbDeviceInfo = serde_json::from_value(json).expect("Failed to parse device");
+    assert_eq!(parsed.interfaces, receiver.interfaces);
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn test_filter_limits_reported_devices() {
+    use common::{FakeDevice, FakeSysfs};
+    use usbwatch_rs::{DeviceFilter, FilterRule, UsbWatcherBuilder};
+
+    let sysfs = FakeSysfs::new();
+    sysfs.add_device("1-1", &FakeDevice::new("05e3", "0608").class(0x09));
+    sysfs.add_device("1-1.1", &FakeDevice::new("0483", "3748").product("ST-Link"));
+    sysfs.add_device("1-1.2", &FakeDevice::new("1366", "0105").product("J-Link"));
+    sysfs.add_device(
+        "1-1.3",
+        &FakeDevice::new("046d", "c31c").product("Keyboard"),
+    );
+
+    let filter = DeviceFilter::new()
+        .vendor_id(0x0483)
+        .vendor_id(0x1366)
+        .exclude("name=^J-".parse::<FilterRule>().expect("Invalid rule"));
+    let (watcher, mut rx) = UsbWatcherBuilder::new()
+        .sysfs_root(sysfs.root())
+        .filter(filter)
+        .build_with_channel()
+        .expect("Failed to create watcher");
+
+    let devices = watcher
+        .list_devices()
+        .await
+        .expect("Failed to list devices");
+    let names: Vec<_> = devices.iter().map(|d| d.device_name.as_str()).collect();
+    assert_eq!(names, ["ST-Link"]);
+
+    tokio::spawn(async move {
+        let _ = watcher.start_monitoring().await;
+    });
+    assert_eq!(next_event(&mut rx).await.device_name, "ST-Link");
+
+    // Only the matching device's disconnect gets through
+    sysfs.remove_device("1-1.3");
+    sysfs.remove_device("1-1.1");
+    let event = next_event(&mut rx).await;
+    assert_eq!(event.device_name, "ST-Link");
+    assert_eq!(event.event_type, DeviceEventType::Disconnected);
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn test_attribute_changes_are_reported() {
+    use common::{FakeDevice, FakeSysfs};
+
+    let sysfs = FakeSysfs::new();
+    sysfs.add_device("1-1", &FakeDevice::new("0403", "6001").product("FT232R"));
+    sysfs.set_attribute("1-1", "authorized", "1");
+
+    let (tx, mut rx) = mpsc::channel(10);
+    let watcher = UsbWatcher::with_sysfs_root(tx, sysfs.root()).expect("Failed to create watcher");
+    tokio::spawn(async move {
+        let _ = watcher.start_monitoring().await;
+    });
+    assert_eq!(
+        next_event(&mut rx).await.event_type,
+        DeviceEventType::Connected
+    );
+
+    // A driver binds to the serial interface
+    sysfs.add_interface("1-1", "1-1:1.0", 0xff, Some("ftdi_sio"));
+    let event = next_event(&mut rx).await;
+    let DeviceEventType::Changed(changes) = &event.event_type else {
+        panic!("Expected a Changed event, got {:?}", event.event_type);
+    };
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].field, "interfaces");
+    assert_eq!(changes[0].old, None);
+    assert_eq!(
+        changes[0].new.as_deref(),
+        Some("0: Vendor Specific (ff/00/00) [ftdi_sio]")
+    );
+
+    // The device is deauthorized
+    sysfs.set_attribute("1-1", "authorized", "0");
+    let event = next_event(&mut rx).await;
+    let DeviceEventType::Changed(changes) = &event.event_type else {
+        panic!("Expected a Changed event, got {:?}", event.event_type);
+    };
+    assert_eq!(changes[0].field, "authorized");
+    assert_eq!(changes[0].old.as_deref(), Some("true"));
+    assert_eq!(changes[0].new.as_deref(), Some("false"));
+
+    let json = serde_json::to_value(&event).expect("Failed to serialize event");
+    assert_eq!(json["event_type"]["Changed"][0]["field"], "authorized");
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn test_network_interfaces_and_net_class() {
+    use common::{FakeDevice, FakeSysfs};
+    use usbwatch_rs::{DeviceFilter, NetworkInterface, UsbWatcherBuilder};
+
+    let sysfs = FakeSysfs::new();
+    // A vendor-specific Ethernet adapter, a keyboard and a phone about to tether
+    sysfs.add_device(
+        "1-1",
+        &FakeDevice::new("0bda", "8153").product("USB 10/100/1000 LAN"),
+    );
+    sysfs.add_interface("1-1", "1-1:1.0", 0xff, Some("r8152"));
+    sysfs.add_network_interface("1-1", "1-1:1.0", "enx00e04c680001", "00:e0:4c:68:00:01");
+    sysfs.add_device("1-2", &FakeDevice::new("046d", "c31c").product("Keyboard"));
+    sysfs.add_interface("1-2", "1-2:1.0", 0x03, Some("usbhid"));
+    sysfs.add_device("1-3", &FakeDevice::new("18d1", "4ee3").product("Pixel"));
+    sysfs.add_interface("1-3", "1-3:1.0", 0xe0, Some("rndis_host"));
+
+    let filter = DeviceFilter::new()
+        .include("class=net".parse().expect("Invalid rule"))
+        .include("class=hid".parse().expect("Invalid rule"));
+    let (watcher, mut rx) = UsbWatcherBuilder::new()
+        .sysfs_root(sysfs.root())
+        .filter(filter)
+        .build_with_channel()
+        .expect("Failed to create watcher");
+
+    let mut devices = watcher
+        .list_devices()
+        .await
+        .expect("Failed to list devices");
+    devices.sort_by_key(|d| d.vendor_id);
+    let names: Vec<_> = devices.iter().map(|d| d.device_name.as_str()).collect();
+    assert_eq!(names, ["Keyboard", "USB 10/100/1000 LAN"]);
+    assert!(devices[0].network_interfaces.is_empty());
+    assert_eq!(
+        devices[1].network_interfaces,
+        [NetworkInterface {
+            name: "enx00e04c680001".to_string(),
+            mac_address: Some("00:e0:4c:68:00:01".to_string()),
+            usb_interface: 0,
+        }]
+    );
+
+    tokio::spawn(async move {
+        let _ = watcher.start_monitoring().await;
+    });
+    for _ in 0..2 {
+        assert_eq!(
+            next_event(&mut rx).await.event_type,
+            DeviceEventType::Connected
+        );
+    }
+
+    // Tethering starts, and the phone now passes the filter, so it is new to the consumer
+    sysfs.add_network_interface("1-3", "1-3:1.0", "usb0", "02:11:22:33:44:55");
+    let event = next_event(&mut rx).await;
+    assert_eq!(event.device_name, "Pixel");
+    assert_eq!(event.event_type, DeviceEventType::Connected);
+    assert_eq!(event.network_interfaces[0].name, "usb0");
+
+    // Tethering stops, and the phone is gone as far as the consumer is concerned
+    sysfs.remove_network_interface("1-3", "1-3:1.0", "usb0");
+    let event = next_event(&mut rx).await;
+    assert_eq!(event.device_name, "Pixel");
+    assert_eq!(event.event_type, DeviceEventType::Disconnected);
+    assert_eq!(event.network_interfaces[0].name, "usb0");
+
+    // Unplugging it now reports nothing more
+    sysfs.remove_device("1-3");
+    sysfs.add_device("1-4", &FakeDevice::new("046d", "c52b").product("Mouse"));
+    sysfs.add_interface("1-4", "1-4:1.0", 0x03, Some("usbhid"));
+    assert_eq!(next_event(&mut rx).await.device_name, "Mouse");
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn test_storage_devices_and_mounts() {
+    use common::{FakeDevice, FakeSysfs};
+
+    let sysfs = FakeSysfs::new();
+    sysfs.add_device("2-1", &FakeDevice::new("0781", "5583").product("Ultra Fit"));
+    sysfs.add_interface("2-1", "2-1:1.0", 0x08, Some("usb-storage"));
+    // A 16 GB drive with a labelled FAT partition and an unformatted one
+    let disk = sysfs.add_disk("2-1", "2-1:1.0", 6, "sdb", "8:16", 31_266_816);
+    sysfs.add_partition(&disk, "sdb2", 2, "8:18", 1_024);
+    sysfs.add_partition(&disk, "sdb1", 1, "8:17", 31_264_768);
+    sysfs.add_udev_properties(
+        "8:17",
+        &[
+            ("ID_FS_TYPE", "vfat"),
+            ("ID_FS_UUID", "7A3B-1C2D"),
+            ("ID_FS_LABEL", "USB_DRIVE"),
+            ("ID_FS_LABEL_ENC", "USB\\x20DRIVE"),
+        ],
+    );
+    sysfs.set_mounts(&[]);
+
+    let (tx, mut rx) = mpsc::channel(10);
+    let watcher = usbwatch_rs::UsbWatcherBuilder::new()
+        .sysfs_root(sysfs.root())
+        .mountinfo_path(sysfs.mountinfo_path())
+        .udev_data_dir(sysfs.udev_data_dir())
+        .build(tx)
+        .expect("Failed to create watcher");
+    tokio::spawn(async move {
+        let _ = watcher.start_monitoring().await;
+    });
+
+    let event = next_event(&mut rx).await;
+    assert_eq!(event.event_type, DeviceEventType::Connected);
+    assert_eq!(event.storage.len(), 1);
+    let disk = &event.storage[0];
+    assert_eq!(disk.path, "/dev/sdb");
+    assert_eq!(disk.size_bytes, Some(16_008_609_792));
+    assert_eq!(disk.filesystem, None);
+    let partitions: Vec<&str> = disk.partitions.iter().map(|p| p.path.as_str()).collect();
+    assert_eq!(partitions, ["/dev/sdb1", "/dev/sdb2"]);
+    let data = &disk.partitions[0];
+    assert_eq!(data.filesystem.as_deref(), Some("vfat"));
+    assert_eq!(data.label.as_deref(), Some("USB DRIVE"));
+    assert_eq!(data.uuid.as_deref(), Some("7A3B-1C2D"));
+    assert!(data.mount_points.is_empty());
+
+    // The FAT partition is mounted
+    sysfs.set_mounts(&[("8:17", "/media/user/USB DRIVE", "vfat")]);
+    let event = next_event(&mut rx).await;
+    let DeviceEventType::Changed(changes) = &event.event_type else {
+        panic!("Expected a Changed event, got {:?}", event.event_type);
+    };
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].field, "storage");
+    assert_eq!(
+        changes[0].new.as_deref(),
+        Some("/dev/sdb, /dev/sdb1 (vfat \"USB DRIVE\") on /media/user/USB DRIVE, /dev/sdb2")
+    );
+    assert_eq!(
+        event.storage[0].partitions[0].mount_points,
+        ["/media/user/USB DRIVE"]
+    );
+
+    // And unmounted again
+    sysfs.set_mounts(&[]);
+    let event = next_event(&mut rx).await;
+    assert!(matches!(event.event_type, DeviceEventType::Changed(_)));
+    assert!(event.storage[0].partitions[0].mount_points.is_empty());
+}
+
+#[test]
+fn test_typed_ids_keep_json_format() {
+    use usbwatch_rs::filter::FilterRule;
+    use usbwatch_rs::{BcdVersion, ProductId, UsbDeviceInfo, VendorId};
+
+    // Recordings made before the IDs were typed still load
+    let line = r#"{"device_name":"ST-Link V2","vendor_id":"0483","product_id":"374B","serial_number":null,"timestamp":"2025-07-27T10:30:15Z","event_type":"Connected","usb_version":"2.00","device_version":"1.00"}"#;
+    let device: UsbDeviceInfo = serde_json::from_str(line).expect("Failed to parse event");
+    assert_eq!(device.vendor_id, VendorId(0x0483));
+    assert_eq!(device.product_id, ProductId(0x374b));
+    assert_eq!(device.usb_version, Some(BcdVersion::from_bcd(0x0200)));
+
+    let json = serde_json::to_value(&device).unwrap();
+    assert_eq!(json["vendor_id"], "0483");
+    assert_eq!(json["product_id"], "374b");
+    assert_eq!(json["device_version"], "1.00");
+    assert!(serde_json::from_str::<UsbDeviceInfo>(&line.replace("0483", "12345")).is_err());
+
+    // Filters compare numerically, whatever the spelling
+    let rule: FilterRule = "vid=0X483".parse().expect("Invalid rule");
+    assert!(rule.matches(&device));
+    assert!("vid=-483".parse::<FilterRule>().is_err());
+    assert!("pid=".parse::<FilterRule>().is_err());
+
+    let usb11: BcdVersion = "1.1".parse().unwrap();
+    assert_eq!(usb11.to_bcd(), 0x0110);
+    assert!(usb11 < device.usb_version.unwrap());
+    assert!("2".parse::<BcdVersion>().is_err());
+    assert!("2.100".parse::<BcdVersion>().is_err());
+    assert!("1f.af".parse::<BcdVersion>().is_err());
+    assert!("2.0a".parse::<BcdVersion>().is_err());
+
+    // A bcdDevice that isn't valid BCD still survives a recording
+    let odd = BcdVersion::from_bcd(0x1f0a);
+    let json = serde_json::to_string(&odd).unwrap();
+    assert_eq!(json, "\"1f.0a\"");
+    assert_eq!(serde_json::from_str::<BcdVersion>(&json).unwrap(), odd);
+}
@@ -0,0 +1,226 @@
+// Tests for event sinks, log file rotation and reopening
+
+use flate2::read::GzDecoder;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tokio::io::AsyncReadExt;
+use usbwatch_rs::logger::{EventFormat, EventSink, FileSink, SinkFuture, WriterSink};
+use usbwatch_rs::{
+    BcdVersion, BlockDevice, DeviceEventType, Logger, RotationPolicy, UsbDeviceInfo, UsbSpeed,
+    VendorId,
+};
+
+fn event(product_id: &str) -> UsbDeviceInfo {
+    let mut device = UsbDeviceInfo::new(
+        "Test Device".to_string(),
+        VendorId(0x0483),
+        product_id.parse().unwrap(),
+        None,
+        DeviceEventType::Connected,
+    );
+    // Every line has the same length
+    device.timestamp = chrono::DateTime::from_timestamp(1_753_612_215, 0).unwrap();
+    device
+}
+
+fn json_logger(path: &Path, policy: RotationPolicy) -> Logger {
+    let sink = FileSink::open(path, EventFormat::Json).expect("Failed to open log file");
+    Logger::default().with_sink(sink.with_rotation(policy))
+}
+
+/// Returns the product IDs logged to `path`, one per line.
+fn logged(path: &Path) -> Vec<String> {
+    fs::read_to_string(path)
+        .unwrap_or_default()
+        .lines()
+        .map(|line| {
+            serde_json::from_str::<UsbDeviceInfo>(line)
+                .unwrap()
+                .product_id
+                .to_string()
+        })
+        .collect()
+}
+
+fn rotated(path: &Path, suffix: &str) -> PathBuf {
+    PathBuf::from(format!("{}{suffix}", path.display()))
+}
+
+#[tokio::test]
+async fn test_size_rotation_keeps_newest_files() {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let path = dir.path().join("usb.log");
+    let line_length = serde_json::to_string(&event("0001")).unwrap().len() as u64 + 1;
+
+    // Two lines fit in a file, and two rotated files are kept
+    let mut logger = json_logger(
+        &path,
+        RotationPolicy::new().max_size(2 * line_length).keep(2),
+    );
+    for product_id in ["0001", "0002", "0003", "0004", "0005", "0006", "0007"] {
+        logger.log_device_event(&event(product_id)).await.unwrap();
+    }
+
+    assert_eq!(logged(&path), ["0007"]);
+    assert_eq!(logged(&rotated(&path, ".1")), ["0005", "0006"]);
+    assert_eq!(logged(&rotated(&path, ".2")), ["0003", "0004"]);
+    assert!(!rotated(&path, ".3").exists());
+}
+
+#[tokio::test]
+async fn test_rotated_files_are_compressed() {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let path = dir.path().join("usb.log");
+
+    let mut logger = json_logger(&path, RotationPolicy::new().max_size(1).compress(true));
+    logger.log_device_event(&event("0001")).await.unwrap();
+    logger.log_device_event(&event("0002")).await.unwrap();
+
+    assert!(!rotated(&path, ".1").exists());
+    let mut contents = String::new();
+    GzDecoder::new(File::open(rotated(&path, ".1.gz")).unwrap())
+        .read_to_string(&mut contents)
+        .expect("Rotated file is not gzip");
+    assert!(contents.contains("\"0001\""));
+    assert_eq!(logged(&path), ["0002"]);
+}
+
+#[tokio::test]
+async fn test_daily_rotation_of_old_file() {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let path = dir.path().join("usb.log");
+    fs::write(&path, "left over from an earlier run\n").unwrap();
+    File::options()
+        .write(true)
+        .open(&path)
+        .unwrap()
+        .set_modified(SystemTime::now() - Duration::from_secs(2 * 24 * 60 * 60))
+        .unwrap();
+
+    let mut logger = json_logger(&path, RotationPolicy::new().daily());
+    logger.log_device_event(&event("0001")).await.unwrap();
+    logger.log_device_event(&event("0002")).await.unwrap();
+
+    assert_eq!(
+        fs::read_to_string(rotated(&path, ".1")).unwrap(),
+        "left over from an earlier run\n"
+    );
+    assert_eq!(logged(&path), ["0001", "0002"]);
+}
+
+#[tokio::test]
+async fn test_reopen_after_external_rotation() {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let path = dir.path().join("usb.log");
+    let moved = dir.path().join("usb.log.old");
+
+    let mut logger = json_logger(&path, RotationPolicy::new());
+    logger.log_device_event(&event("0001")).await.unwrap();
+    fs::rename(&path, &moved).unwrap();
+
+    logger.reopen().expect("Failed to reopen log file");
+    logger.log_device_event(&event("0002")).await.unwrap();
+
+    assert_eq!(logged(&moved), ["0001"]);
+    assert_eq!(logged(&path), ["0002"]);
+}
+
+/// A sink that keeps the product IDs it receives.
+#[derive(Clone, Default)]
+struct Collect(std::sync::Arc<std::sync::Mutex<Vec<String>>>);
+
+impl EventSink for Collect {
+    fn write_event<'a>(&'a mut self, event: &'a UsbDeviceInfo) -> SinkFuture<'a> {
+        self.0.lock().unwrap().push(event.product_id.to_string());
+        Box::pin(async { Ok(()) })
+    }
+}
+
+#[tokio::test]
+async fn test_sinks_have_their_own_format() {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let text_path = dir.path().join("usb.txt");
+    let json_path = dir.path().join("usb.jsonl");
+    let (writer, mut reader) = tokio::io::duplex(4096);
+    let collected = Collect::default();
+
+    let mut logger = Logger::default()
+        .with_sink(FileSink::open(&text_path, EventFormat::Text { colourful: false }).unwrap())
+        .with_sink(FileSink::open(&json_path, EventFormat::Json).unwrap())
+        .with_sink(WriterSink::new(writer, EventFormat::Json))
+        .with_sink(collected.clone());
+    logger.log_device_event(&event("0001")).await.unwrap();
+    logger.log_device_event(&event("0002")).await.unwrap();
+    drop(logger);
+
+    let text = fs::read_to_string(&text_path).unwrap();
+    assert_eq!(text.lines().count(), 2);
+    assert!(text.starts_with("🔌 Test Device | VID: 0483 PID: 0001 |"));
+    assert!(!text.contains('\x1b'));
+
+    assert_eq!(logged(&json_path), ["0001", "0002"]);
+
+    let mut written = String::new();
+    reader.read_to_string(&mut written).await.unwrap();
+    assert_eq!(written, fs::read_to_string(&json_path).unwrap());
+
+    assert_eq!(*collected.0.lock().unwrap(), ["0001", "0002"]);
+}
+
+#[test]
+fn test_text_shows_link_details() {
+    let mut device = event("5583");
+    let text = EventFormat::Text { colourful: false };
+    assert!(text
+        .format(&device)
+        .unwrap()
+        .contains("| Class: - | Event: Connected |"));
+
+    device.usb_version = Some(BcdVersion::from_bcd(0x0320));
+    device.speed = Some(UsbSpeed::High);
+    device.max_power_ma = Some(896);
+    device.configuration = Some(1);
+    device.num_interfaces = Some(1);
+    device.removable = Some(true);
+    assert!(text.format(&device).unwrap().contains(
+        "| Class: - | USB 3.20, 480 Mbit/s, 896 mA, config 1 with 1 interface, removable | Event:"
+    ));
+
+    let json: serde_json::Value =
+        serde_json::from_str(&EventFormat::Json.format(&device).unwrap()).unwrap();
+    assert_eq!(json["speed"], "high");
+    assert_eq!(json["usb_version"], "3.20");
+    assert_eq!(json["configuration"], 1);
+}
+
+#[test]
+fn test_text_lists_volumes_of_connected_storage() {
+    let mut device = event("5583");
+    device.storage = vec![BlockDevice {
+        path: "/dev/sdb".to_string(),
+        partitions: vec![BlockDevice {
+            path: "/dev/sdb1".to_string(),
+            filesystem: Some("vfat".to_string()),
+            label: Some("DATA".to_string()),
+            mount_points: vec!["/media/data".to_string()],
+            ..BlockDevice::default()
+        }],
+        ..BlockDevice::default()
+    }];
+    let text = EventFormat::Text { colourful: false };
+
+    let lines: Vec<String> = text
+        .format(&device)
+        .unwrap()
+        .lines()
+        .map(str::to_string)
+        .collect();
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[1], "    /dev/sdb");
+    assert_eq!(lines[2], "    /dev/sdb1 (vfat \"DATA\") on /media/data");
+
+    device.event_type = DeviceEventType::Disconnected;
+    assert_eq!(text.format(&device).unwrap().lines().count(), 1);
+}
@@ -0,0 +1,138 @@
+// Tests for recording and replaying event sessions
+// These use paused time, so replays with long gaps finish instantly
+
+use chrono::{DateTime, TimeDelta};
+use std::io::Write;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::time::Instant;
+use usbwatch_rs::watcher::{open_recording, read_recording};
+use usbwatch_rs::{
+    DeviceEventType, ProductId, ReplayBackend, UsbDeviceInfo, UsbWatchError, UsbWatcher, VendorId,
+};
+
+/// Returns a recorded event `offset_ms` milliseconds into the session.
+fn recorded(product_id: u16, event_type: DeviceEventType, offset_ms: i64) -> UsbDeviceInfo {
+    let mut device = UsbDeviceInfo::new(
+        "Test Device".to_string(),
+        VendorId(0x0483),
+        ProductId(product_id),
+        None,
+        event_type,
+    );
+    device.timestamp =
+        DateTime::from_timestamp(1_753_612_215, 0).unwrap() + TimeDelta::milliseconds(offset_ms);
+    device
+}
+
+fn session() -> Vec<UsbDeviceInfo> {
+    vec![
+        recorded(0x3748, DeviceEventType::Connected, 0),
+        recorded(0x5740, DeviceEventType::Connected, 1_000),
+        recorded(0x3748, DeviceEventType::Disconnected, 3_000),
+    ]
+}
+
+/// Replays `events` and returns when each one arrived, relative to the start.
+async fn replay_times(events: Vec<UsbDeviceInfo>, speed: f64) -> Vec<Duration> {
+    let (tx, mut rx) = mpsc::channel(10);
+    let watcher = UsbWatcher::with_backend(ReplayBackend::new(tx, events).speed(speed));
+    let start = Instant::now();
+    tokio::spawn(async move { watcher.start_monitoring().await });
+
+    let mut times = Vec::new();
+    while rx.recv().await.is_some() {
+        times.push(start.elapsed());
+    }
+    times
+}
+
+#[test]
+fn test_recording_round_trip() {
+    let mut file = tempfile::NamedTempFile::new().expect("Failed to create recording");
+    for event in session() {
+        writeln!(file, "{}", serde_json::to_string(&event).unwrap()).unwrap();
+    }
+    writeln!(file).unwrap();
+
+    let events = open_recording(file.path()).expect("Failed to read recording");
+    assert_eq!(events.len(), 3);
+    assert_eq!(events[1].product_id, ProductId(0x5740));
+    assert_eq!(events[2].event_type, DeviceEventType::Disconnected);
+}
+
+#[test]
+fn test_invalid_recording_reports_line() {
+    let good = serde_json::to_string(&session()[0]).unwrap();
+    let recording = format!("{good}\n\n{{\"device_name\": 3}}\n");
+
+    match read_recording(recording.as_bytes()) {
+        Err(UsbWatchError::InvalidRecording { line, .. }) => assert_eq!(line, 3),
+        other => panic!("Expected InvalidRecording, got {other:?}"),
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_replay_keeps_relative_timing() {
+    let times = replay_times(session(), 1.0).await;
+    assert_eq!(
+        times,
+        [
+            Duration::ZERO,
+            Duration::from_secs(1),
+            Duration::from_secs(3)
+        ]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_replay_speed() {
+    let times = replay_times(session(), 2.0).await;
+    assert_eq!(
+        times,
+        [
+            Duration::ZERO,
+            Duration::from_millis(500),
+            Duration::from_millis(1_500)
+        ]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_replay_at_tiny_speed_waits_until_stopped() {
+    let (tx, mut rx) = mpsc::channel(10);
+    // The second event would be due too far in the future to represent
+    let watcher = UsbWatcher::with_backend(ReplayBackend::new(tx, session()).speed(1e-20));
+    let stop = watcher.stop_handle();
+    let replay = tokio::spawn(async move { watcher.start_monitoring().await });
+
+    assert_eq!(rx.recv().await.unwrap().product_id, ProductId(0x3748));
+    tokio::time::sleep(Duration::from_secs(86_400)).await;
+    assert!(rx.try_recv().is_err());
+
+    stop.stop();
+    replay
+        .await
+        .expect("Replay panicked")
+        .expect("Replay failed");
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_replay_tracks_connected_devices() {
+    let (tx, mut rx) = mpsc::channel(10);
+    let events = session();
+    let original = events[0].timestamp;
+    let watcher = UsbWatcher::with_backend(ReplayBackend::new(tx, events));
+
+    watcher.start_monitoring().await.expect("Replay failed");
+
+    // Events keep their recorded timestamps
+    assert_eq!(rx.recv().await.unwrap().timestamp, original);
+
+    let devices = watcher
+        .list_devices()
+        .await
+        .expect("Failed to list devices");
+    assert_eq!(devices.len(), 1);
+    assert_eq!(devices[0].product_id, ProductId(0x5740));
+}
@@ -0,0 +1,284 @@
+// Tests for the Linux kernel uevent parser using captured uevent buffers, and
+// for the uevent listener replaying them against a fake sysfs tree
+
+#![cfg(target_os = "linux")]
+
+mod common;
+
+use common::{FakeDevice, FakeSysfs};
+use std::os::unix::net::UnixDatagram;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use usbwatch_rs::watcher::linux::{parse_uevent, LinuxUsbWatcher, UeventAction};
+use usbwatch_rs::{DeviceEventType, DeviceFilter, UsbDeviceInfo, WatcherConfig};
+
+const EVENT_TIMEOUT: Duration = Duration::from_secs(10);
+
+async fn next_event(rx: &mut mpsc::Receiver<UsbDeviceInfo>) -> UsbDeviceInfo {
+    tokio::time::timeout(EVENT_TIMEOUT, rx.recv())
+        .await
+        .expect("Timed out waiting for device event")
+        .expect("Event channel closed")
+}
+
+/// Builds a kernel uevent datagram from its header and properties.
+fn uevent_buffer(header: &str, properties: &[&str]) -> Vec<u8> {
+    let mut buf = header.as_bytes().to_vec();
+    buf.push(0);
+    for property in properties {
+        buf.extend_from_slice(property.as_bytes());
+        buf.push(0);
+    }
+    buf
+}
+
+/// Builds the uevent the kernel sends for a USB device directly on bus 1.
+fn usb_device_uevent(action: &str, name: &str) -> Vec<u8> {
+    let devpath = format!("/devices/pci0000:00/0000:00:14.0/usb1/{name}");
+    uevent_buffer(
+        &format!("{action}@{devpath}"),
+        &[
+            &format!("ACTION={action}"),
+            &format!("DEVPATH={devpath}"),
+            "SUBSYSTEM=usb",
+            "DEVTYPE=usb_device",
+        ],
+    )
+}
+
+/// Builds the uevent the kernel sends for a network interface of the first
+/// interface of a USB device directly on bus 1.
+fn net_uevent(action: &str, device: &str, name: &str) -> Vec<u8> {
+    let devpath = format!("/devices/pci0000:00/0000:00:14.0/usb1/{device}/{device}:1.0/net/{name}");
+    uevent_buffer(
+        &format!("{action}@{devpath}"),
+        &[
+            &format!("ACTION={action}"),
+            &format!("DEVPATH={devpath}"),
+            "SUBSYSTEM=net",
+            &format!("INTERFACE={name}"),
+        ],
+    )
+}
+
+/// Starts a watcher on `sysfs` and returns the socket to send it uevents on.
+fn watch_uevents(
+    sysfs: &FakeSysfs,
+    filter: DeviceFilter,
+    tx: mpsc::Sender<UsbDeviceInfo>,
+) -> UnixDatagram {
+    let config = WatcherConfig {
+        sysfs_root: sysfs.root().to_path_buf(),
+        mountinfo_path: sysfs.mountinfo_path(),
+        udev_data_dir: sysfs.udev_data_dir(),
+        filter,
+        // The fake mount table is a regular file, so it is reread this often
+        min_poll_interval: Duration::from_millis(50),
+        max_poll_interval: Duration::from_millis(50),
+        ..WatcherConfig::default()
+    };
+    let watcher = LinuxUsbWatcher::with_config(tx, config);
+    let (kernel, socket) = UnixDatagram::pair().expect("Failed to create socket pair");
+    tokio::spawn(async move {
+        let _ = watcher.monitor_uevents(socket.into()).await;
+    });
+    kernel
+}
+
+#[test]
+fn test_parse_usb_device_add() {
+    let buf = uevent_buffer(
+        "add@/devices/pci0000:00/0000:00:14.0/usb1/1-1",
+        &[
+            "ACTION=add",
+            "DEVPATH=/devices/pci0000:00/0000:00:14.0/usb1/1-1",
+            "SUBSYSTEM=usb",
+            "MAJOR=189",
+            "MINOR=2",
+            "DEVNAME=bus/usb/001/003",
+            "DEVTYPE=usb_device",
+            "PRODUCT=781/5583/100",
+            "TYPE=0/0/0",
+            "BUSNUM=001",
+            "DEVNUM=003",
+            "SEQNUM=4821",
+        ],
+    );
+
+    let uevent = parse_uevent(&buf).expect("Failed to parse uevent");
+    assert_eq!(uevent.action, UeventAction::Add);
+    assert_eq!(uevent.devpath, "/devices/pci0000:00/0000:00:14.0/usb1/1-1");
+    assert_eq!(uevent.subsystem.as_deref(), Some("usb"));
+    assert_eq!(uevent.devtype.as_deref(), Some("usb_device"));
+    assert_eq!(uevent.sysfs_name(), Some("1-1"));
+    assert_eq!(
+        uevent.properties.get("PRODUCT").map(String::as_str),
+        Some("781/5583/100")
+    );
+    assert!(uevent.is_usb_device());
+}
+
+#[test]
+fn test_parse_usb_interface_bind() {
+    let buf = uevent_buffer(
+        "bind@/devices/pci0000:00/0000:00:14.0/usb1/1-1/1-1.4/1-1.4:1.0",
+        &[
+            "ACTION=bind",
+            "DEVPATH=/devices/pci0000:00/0000:00:14.0/usb1/1-1/1-1.4/1-1.4:1.0",
+            "SUBSYSTEM=usb",
+            "DEVTYPE=usb_interface",
+            "DRIVER=usb-storage",
+            "INTERFACE=8/6/80",
+        ],
+    );
+
+    let uevent = parse_uevent(&buf).expect("Failed to parse uevent");
+    assert_eq!(uevent.action, UeventAction::Bind);
+    assert_eq!(uevent.sysfs_name(), Some("1-1.4:1.0"));
+    assert!(!uevent.is_usb_device());
+    assert!(uevent.is_usb_interface());
+    assert_eq!(uevent.parent_sysfs_name(), Some("1-1.4"));
+}
+
+#[test]
+fn test_block_device_belongs_to_usb_device() {
+    let devpath =
+        "/devices/pci0000:00/0000:00:14.0/usb2/2-1/2-1.2/2-1.2:1.0/host6/target6:0:0/6:0:0:0/block/sdb/sdb1";
+    let buf = uevent_buffer(
+        &format!("add@{devpath}"),
+        &[
+            "ACTION=add",
+            &format!("DEVPATH={devpath}"),
+            "SUBSYSTEM=block",
+            "DEVNAME=sdb1",
+            "DEVTYPE=partition",
+            "PARTN=1",
+        ],
+    );
+
+    let uevent = parse_uevent(&buf).expect("Failed to parse uevent");
+    assert_eq!(uevent.usb_device_sysfs_name(), Some("2-1.2"));
+    assert!(!uevent.is_usb_device());
+
+    let cpu = uevent_buffer("offline@/devices/system/cpu/cpu1", &["ACTION=offline"]);
+    let uevent = parse_uevent(&cpu).expect("Failed to parse uevent");
+    assert_eq!(uevent.usb_device_sysfs_name(), None);
+}
+
+#[test]
+fn test_parse_remove_and_other_actions() {
+    let remove = uevent_buffer(
+        "remove@/devices/pci0000:00/0000:00:14.0/usb2/2-3",
+        &["ACTION=remove", "SUBSYSTEM=usb", "DEVTYPE=usb_device"],
+    );
+    let uevent = parse_uevent(&remove).expect("Failed to parse uevent");
+    assert_eq!(uevent.action, UeventAction::Remove);
+    // DEVPATH falls back to the header when the property is missing
+    assert_eq!(uevent.sysfs_name(), Some("2-3"));
+
+    let offline = uevent_buffer("offline@/devices/system/cpu/cpu1", &["ACTION=offline"]);
+    let uevent = parse_uevent(&offline).expect("Failed to parse uevent");
+    assert_eq!(uevent.action, UeventAction::Other("offline".to_string()));
+    assert!(!uevent.is_usb_device());
+}
+
+#[test]
+fn test_parse_rejects_udev_and_malformed_buffers() {
+    let udev = uevent_buffer("libudev", &["ACTION=add", "SUBSYSTEM=usb"]);
+    assert!(parse_uevent(&udev).is_none());
+    assert!(parse_uevent(b"").is_none());
+    assert!(parse_uevent(b"garbage without header").is_none());
+}
+
+#[tokio::test]
+async fn test_added_device_is_reported_without_bind() {
+    let sysfs = FakeSysfs::new();
+    let (tx, mut rx) = mpsc::channel(10);
+    let kernel = watch_uevents(&sysfs, DeviceFilter::default(), tx);
+
+    // A kernel that announces the bind
+    sysfs.add_device("1-1", &FakeDevice::new("0781", "5583").product("Ultra Fit"));
+    kernel.send(&usb_device_uevent("add", "1-1")).unwrap();
+    kernel.send(&usb_device_uevent("bind", "1-1")).unwrap();
+    let event = next_event(&mut rx).await;
+    assert_eq!(event.device_name, "Ultra Fit");
+    assert_eq!(event.event_type, DeviceEventType::Connected);
+
+    // A kernel before 4.14 only sends add; the device is held back for a
+    // while in case its interfaces are still being bound
+    sysfs.add_device("1-2", &FakeDevice::new("0403", "6001").product("FT232R"));
+    kernel.send(&usb_device_uevent("add", "1-2")).unwrap();
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert!(rx.try_recv().is_err());
+    sysfs.add_interface("1-2", "1-2:1.0", 0xff, Some("ftdi_sio"));
+
+    let event = next_event(&mut rx).await;
+    assert_eq!(event.device_name, "FT232R");
+    assert_eq!(event.event_type, DeviceEventType::Connected);
+    assert_eq!(event.interfaces.len(), 1);
+}
+
+#[tokio::test]
+async fn test_device_passing_filter_later_is_reported_as_connected() {
+    let sysfs = FakeSysfs::new();
+    let (tx, mut rx) = mpsc::channel(10);
+    let kernel = watch_uevents(&sysfs, DeviceFilter::new().network(), tx);
+
+    // A phone that isn't tethering yet has no network interface
+    sysfs.add_device("1-3", &FakeDevice::new("18d1", "4ee3").product("Pixel"));
+    sysfs.add_interface("1-3", "1-3:1.0", 0xe0, Some("rndis_host"));
+    kernel.send(&usb_device_uevent("add", "1-3")).unwrap();
+    kernel.send(&usb_device_uevent("bind", "1-3")).unwrap();
+
+    sysfs.add_network_interface("1-3", "1-3:1.0", "usb0", "02:11:22:33:44:55");
+    kernel.send(&net_uevent("add", "1-3", "usb0")).unwrap();
+    let event = next_event(&mut rx).await;
+    assert_eq!(event.device_name, "Pixel");
+    assert_eq!(event.event_type, DeviceEventType::Connected);
+
+    // On unplug the network interface goes first, and the phone is reported
+    // as disconnected with it, though it no longer passes the filter
+    sysfs.remove_network_interface("1-3", "1-3:1.0", "usb0");
+    kernel.send(&net_uevent("remove", "1-3", "usb0")).unwrap();
+    let event = next_event(&mut rx).await;
+    assert_eq!(event.event_type, DeviceEventType::Disconnected);
+    assert_eq!(event.network_interfaces[0].name, "usb0");
+
+    sysfs.remove_device("1-3");
+    kernel.send(&usb_device_uevent("remove", "1-3")).unwrap();
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert!(rx.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn test_mount_table_changes_are_reported() {
+    let sysfs = FakeSysfs::new();
+    sysfs.add_device("2-1", &FakeDevice::new("0781", "5583").product("Ultra Fit"));
+    sysfs.add_interface("2-1", "2-1:1.0", 0x08, Some("usb-storage"));
+    let disk = sysfs.add_disk("2-1", "2-1:1.0", 6, "sdb", "8:16", 31_266_816);
+    sysfs.add_partition(&disk, "sdb1", 1, "8:17", 31_264_768);
+    sysfs.set_mounts(&[]);
+
+    let (tx, mut rx) = mpsc::channel(10);
+    let _kernel = watch_uevents(&sysfs, DeviceFilter::default(), tx);
+    let event = next_event(&mut rx).await;
+    assert_eq!(event.event_type, DeviceEventType::Connected);
+    assert!(event.storage[0].partitions[0].mount_points.is_empty());
+
+    // Mounting sends no uevent, so only the mount table shows it
+    sysfs.set_mounts(&[
+        ("0:45", "/run/user/1000", "tmpfs"),
+        ("8:17", "/media/user/USB DRIVE", "vfat"),
+        ("8:17", "/mnt/backup", "vfat"),
+    ]);
+    let event = next_event(&mut rx).await;
+    assert!(matches!(event.event_type, DeviceEventType::Changed(_)));
+    assert_eq!(
+        event.storage[0].partitions[0].mount_points,
+        ["/media/user/USB DRIVE", "/mnt/backup"]
+    );
+
+    sysfs.set_mounts(&[("8:17", "/mnt/backup", "vfat")]);
+    let event = next_event(&mut rx).await;
+    assert_eq!(event.storage[0].partitions[0].mount_points, ["/mnt/backup"]);
+}
@@ -0,0 +1,124 @@
+// Tests for the USB ID database
+
+mod common;
+
+use usbwatch_rs::device_info::UsbClass;
+use usbwatch_rs::usb_ids::UsbIds;
+use usbwatch_rs::{DeviceEventType, ProductId, UsbDeviceInfo, VendorId};
+
+const USB_IDS: &str = "\
+#
+# List of USB ID's
+#
+# Version: 2025.07.26
+
+# Vendors, devices and interfaces. Please keep sorted.
+
+046d  Logitech, Inc.
+\tc52b  Unifying Receiver
+\t\t00  Keyboard interface
+0483  STMicroelectronics
+\t3748  ST-LINK/V2
+
+# List of known device classes, subclasses and protocols
+
+C 09  Hub
+\t00  Unused
+\t\t01  Single TT
+C ff  Vendor Specific Class
+
+# List of HID descriptor types
+
+HID 21  HID
+R 01  Keyboard
+\t0483  Not a product
+";
+
+fn unnamed_device(vendor_id: u16, product_id: u16) -> UsbDeviceInfo {
+    UsbDeviceInfo::new(
+        "Unknown Device".to_string(),
+        VendorId(vendor_id),
+        ProductId(product_id),
+        None,
+        DeviceEventType::Connected,
+    )
+}
+
+#[test]
+fn test_parse_sections() {
+    let ids = UsbIds::parse(USB_IDS);
+
+    assert_eq!(ids.vendor(0x046d), Some("Logitech, Inc."));
+    assert_eq!(ids.product(0x046d, 0xc52b), Some("Unifying Receiver"));
+    assert_eq!(ids.product(0x0483, 0x3748), Some("ST-LINK/V2"));
+    assert_eq!(ids.class(0xff), Some("Vendor Specific Class"));
+    assert_eq!(
+        ids.class_name(&UsbClass::new(0x09, 0x00, 0x01)).as_deref(),
+        Some("Hub / Unused / Single TT")
+    );
+    // Entries of the sections after the classes are not products
+    assert_eq!(ids.vendor(0x0021), None);
+    assert_eq!(ids.product(0x0483, 0x0483), None);
+}
+
+#[test]
+fn test_annotate_keeps_reported_names() {
+    let ids = UsbIds::parse(USB_IDS);
+
+    let mut unnamed = unnamed_device(0x0483, 0x3748);
+    ids.annotate(&mut unnamed);
+    assert_eq!(unnamed.device_name, "STMicroelectronics ST-LINK/V2");
+    assert_eq!(unnamed.product_name.as_deref(), Some("ST-LINK/V2"));
+
+    // Only the vendor is listed
+    let mut unlisted = unnamed_device(0x046d, 0x0001);
+    ids.annotate(&mut unlisted);
+    assert_eq!(unlisted.device_name, "Logitech, Inc. device");
+    assert_eq!(unlisted.product_name.as_deref(), None);
+
+    let mut named = unnamed_device(0x0483, 0x3748);
+    named.device_name = "STLINK-V3".to_string();
+    ids.annotate(&mut named);
+    assert_eq!(named.device_name, "STLINK-V3");
+    assert_eq!(named.vendor_name.as_deref(), Some("STMicroelectronics"));
+}
+
+#[test]
+fn test_load_missing_file_fails() {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    assert!(UsbIds::load(dir.path().join("usb.ids")).is_err());
+}
+
+#[cfg(feature = "bundled-usb-ids")]
+#[test]
+fn test_bundled_snapshot() {
+    let ids = UsbIds::bundled();
+    assert_eq!(ids.product(0x1d6b, 0x0002), Some("2.0 root hub"));
+    assert_eq!(ids.class(0x08), Some("Mass Storage"));
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn test_watcher_names_device_from_database() {
+    use common::{FakeDevice, FakeSysfs};
+    use tokio::sync::mpsc;
+
+    let sysfs = FakeSysfs::new();
+    sysfs.add_device("2-3", &FakeDevice::new("046d", "c52b").class(0x09));
+
+    let (tx, _rx) = mpsc::channel(10);
+    let watcher = usbwatch_rs::UsbWatcherBuilder::new()
+        .sysfs_root(sysfs.root())
+        .usb_ids(Some(UsbIds::parse(USB_IDS)))
+        .build(tx)
+        .expect("Failed to create watcher");
+    let devices = watcher
+        .list_devices()
+        .await
+        .expect("Failed to list devices");
+
+    assert_eq!(devices.len(), 1);
+    assert_eq!(devices[0].device_name, "Logitech, Inc. Unifying Receiver");
+    assert_eq!(devices[0].vendor_name.as_deref(), Some("Logitech, Inc."));
+    assert_eq!(devices[0].class_name.as_deref(), Some("Hub / Unused"));
+}